@@ -0,0 +1,18 @@
+pub mod sologger_geyser_plugin;
+
+pub use agave_geyser_plugin_interface::geyser_plugin_interface;
+
+use crate::geyser_plugin_interface::GeyserPlugin;
+use crate::sologger_geyser_plugin::SologgerGeyserPlugin;
+
+/// Entry point the validator looks up after loading the library named by `libpath`.
+///
+/// # Safety
+///
+/// The returned pointer owns the plugin; the validator takes it back with `Box::from_raw`.
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+pub unsafe extern "C" fn _create_plugin() -> *mut dyn GeyserPlugin {
+    let plugin: Box<dyn GeyserPlugin> = Box::<SologgerGeyserPlugin>::default();
+    Box::into_raw(plugin)
+}
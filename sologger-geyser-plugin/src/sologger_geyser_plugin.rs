@@ -0,0 +1,498 @@
+//! Runs sologger inside the validator. Transaction notifications are copied off the
+//! validator thread into a bounded queue; a tokio runtime owned by the plugin parses each
+//! one into LogContexts and hands them to the same [`LogPipeline`] the WebSocket client
+//! uses — IDL enrichment, telemetry/webhook export and the configured log transport.
+
+use std::sync::Once;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{trace, warn};
+use solana_pubkey::Pubkey;
+use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction_status::{TransactionStatusMeta, VersionedTransactionWithStatusMeta};
+use sologger::config_loader::load_config;
+use sologger::log_subscriber::LogPipeline;
+use sologger::logger_lib::init_logger;
+use sologger_log_context::programs_selector::ProgramsSelector;
+use sologger_log_context::sologger_log_context::LogContext;
+use sologger_log_transformer::log_context_transformer::from_versioned_transaction;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::geyser_plugin_interface::{
+    GeyserPlugin, GeyserPluginError, ReplicaTransactionInfoVersions, Result,
+};
+
+/// Transactions waiting to be parsed. When the pipeline falls this far behind,
+/// notifications are dropped rather than blocking the validator's replay.
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// How long `on_unload` waits for the queue to drain before giving up.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Transports are process-wide and can only be initialized once, including across
+/// plugin reloads.
+static INIT_TRANSPORTS: Once = Once::new();
+
+/// One transaction notification, owned so it can leave the validator thread.
+#[derive(Debug)]
+pub(crate) struct TransactionUpdate {
+    pub slot: u64,
+    pub index: Option<usize>,
+    /// When the validator handed over the transaction, as Unix milliseconds
+    pub received_at: u64,
+    pub transaction: VersionedTransaction,
+    pub meta: TransactionStatusMeta,
+}
+
+#[derive(Debug, Default)]
+pub struct SologgerGeyserPlugin {
+    inner: Option<Inner>,
+}
+
+/// State that exists between `on_load` and `on_unload`.
+#[derive(Debug)]
+struct Inner {
+    runtime: Runtime,
+    sender: mpsc::Sender<TransactionUpdate>,
+    consumer: JoinHandle<()>,
+    include_votes: bool,
+    program_selector: ProgramsSelector,
+}
+
+impl GeyserPlugin for SologgerGeyserPlugin {
+    fn name(&self) -> &'static str {
+        "sologger-geyser-plugin"
+    }
+
+    /// sologger installs its own logger from sologger-config.json in `on_load`; taking the
+    /// validator's logger here would make that fail.
+    fn setup_logger(&self, _logger: &'static dyn log::Log, _level: log::LevelFilter) -> Result<()> {
+        Ok(())
+    }
+
+    /// `config_file` is the plugin config passed to the validator with
+    /// `--geyser-plugin-config`: `{"libpath": ..., "sologgerConfigLocation": ...}`.
+    fn on_load(&mut self, config_file: &str, _is_reload: bool) -> Result<()> {
+        let contents = std::fs::read_to_string(config_file)?;
+        let plugin_config: serde_json::Value = serde_json::from_str(&contents).map_err(|err| {
+            GeyserPluginError::ConfigFileReadError {
+                msg: err.to_string(),
+            }
+        })?;
+        let Some(sologger_config_location) = plugin_config["sologgerConfigLocation"].as_str()
+        else {
+            return Err(GeyserPluginError::ConfigFileReadError {
+                msg: "sologgerConfigLocation is not set".to_string(),
+            });
+        };
+        let (sologger_config, program_selector, idl_registry) =
+            load_config(sologger_config_location).map_err(|err| {
+                GeyserPluginError::ConfigFileReadError {
+                    msg: format!("{}: {}", sologger_config_location, err),
+                }
+            })?;
+
+        INIT_TRANSPORTS.call_once(|| {
+            init_logger(&sologger_config);
+            #[cfg(feature = "enable_webhook")]
+            sologger::webhook_sender::init(&sologger_config);
+        });
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("sologger-geyser")
+            .enable_all()
+            .build()
+            .map_err(|err| GeyserPluginError::Custom(Box::new(err)))?;
+        let pipeline = LogPipeline::new(&sologger_config, &program_selector, &idl_registry);
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        let consumer = runtime.spawn(consume(pipeline, receiver));
+
+        self.inner = Some(Inner {
+            runtime,
+            sender,
+            consumer,
+            include_votes: sologger_config.all_with_votes,
+            program_selector,
+        });
+        Ok(())
+    }
+
+    fn on_unload(&mut self) {
+        let Some(Inner {
+            runtime,
+            sender,
+            consumer,
+            ..
+        }) = self.inner.take()
+        else {
+            return;
+        };
+        // Closing the queue ends the consumer once it has drained
+        drop(sender);
+        let drained =
+            runtime.block_on(async { tokio::time::timeout(SHUTDOWN_TIMEOUT, consumer).await });
+        if drained.is_err() {
+            warn!("sologger queue not drained after {:?}", SHUTDOWN_TIMEOUT);
+        }
+        runtime.shutdown_background();
+        log::logger().flush();
+    }
+
+    fn notify_transaction(
+        &self,
+        transaction: ReplicaTransactionInfoVersions,
+        slot: u64,
+    ) -> Result<()> {
+        let Some(inner) = &self.inner else {
+            return Ok(());
+        };
+        let is_vote = match &transaction {
+            ReplicaTransactionInfoVersions::V0_0_1(info) => info.is_vote,
+            ReplicaTransactionInfoVersions::V0_0_2(info) => info.is_vote,
+            ReplicaTransactionInfoVersions::V0_0_3(info) => info.is_vote,
+        };
+        if is_vote && !inner.include_votes {
+            return Ok(());
+        }
+        if !mentions_selected_program(&transaction, &inner.program_selector) {
+            return Ok(());
+        }
+
+        let received_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        let update = match transaction {
+            ReplicaTransactionInfoVersions::V0_0_1(info) => TransactionUpdate {
+                slot,
+                index: None,
+                received_at,
+                transaction: info.transaction.to_versioned_transaction(),
+                meta: info.transaction_status_meta.clone(),
+            },
+            ReplicaTransactionInfoVersions::V0_0_2(info) => TransactionUpdate {
+                slot,
+                index: Some(info.index),
+                received_at,
+                transaction: info.transaction.to_versioned_transaction(),
+                meta: info.transaction_status_meta.clone(),
+            },
+            ReplicaTransactionInfoVersions::V0_0_3(info) => TransactionUpdate {
+                slot,
+                index: Some(info.index),
+                received_at,
+                transaction: info.transaction.clone(),
+                meta: info.transaction_status_meta.clone(),
+            },
+        };
+        match inner.sender.try_send(update) {
+            Ok(()) => Ok(()),
+            Err(mpsc::error::TrySendError::Full(update)) => {
+                warn!(
+                    "sologger queue full, dropping transaction in slot {}",
+                    update.slot
+                );
+                Ok(())
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                Err(GeyserPluginError::TransactionUpdateError {
+                    msg: "sologger pipeline has stopped".to_string(),
+                })
+            }
+        }
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        false
+    }
+
+    fn account_data_snapshot_notifications_enabled(&self) -> bool {
+        false
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        true
+    }
+}
+
+/// Whether any of the transaction's account keys, static or loaded from a lookup table,
+/// is a selected program. Every program a transaction invokes is one of its keys, so the
+/// others can be skipped here, on the validator thread, before anything is copied.
+fn mentions_selected_program(
+    transaction: &ReplicaTransactionInfoVersions,
+    program_selector: &ProgramsSelector,
+) -> bool {
+    if program_selector.select_all_programs {
+        return true;
+    }
+    let is_selected = |key: &Pubkey| program_selector.is_program_selected(key.as_ref());
+    match transaction {
+        ReplicaTransactionInfoVersions::V0_0_1(info) => info
+            .transaction
+            .message()
+            .account_keys()
+            .iter()
+            .any(is_selected),
+        ReplicaTransactionInfoVersions::V0_0_2(info) => info
+            .transaction
+            .message()
+            .account_keys()
+            .iter()
+            .any(is_selected),
+        ReplicaTransactionInfoVersions::V0_0_3(info) => {
+            let loaded = &info.transaction_status_meta.loaded_addresses;
+            info.transaction
+                .message
+                .static_account_keys()
+                .iter()
+                .chain(&loaded.writable)
+                .chain(&loaded.readonly)
+                .any(is_selected)
+        }
+    }
+}
+
+async fn consume(pipeline: LogPipeline, mut receiver: mpsc::Receiver<TransactionUpdate>) {
+    while let Some(update) = receiver.recv().await {
+        match log_contexts_from_update(update, pipeline.program_selector()) {
+            Ok(log_contexts) => pipeline.process(log_contexts).await,
+            Err(err) => warn!("failed to parse transaction notification: {}", err),
+        }
+    }
+    trace!("sologger queue closed");
+}
+
+/// Parses a notification straight from the validator's own transaction and meta, so the
+/// records carry fees, balance changes, accounts and error details like every other source.
+pub(crate) fn log_contexts_from_update(
+    update: TransactionUpdate,
+    program_selector: &ProgramsSelector,
+) -> anyhow::Result<Vec<LogContext>> {
+    let TransactionUpdate {
+        slot,
+        index,
+        received_at,
+        transaction,
+        meta,
+    } = update;
+    let tx = VersionedTransactionWithStatusMeta { transaction, meta };
+    let mut log_contexts = from_versioned_transaction(&tx, slot, program_selector)?;
+    for log_context in log_contexts.iter_mut() {
+        log_context.transaction_index = index;
+        log_context.received_at = Some(received_at);
+    }
+    Ok(log_contexts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geyser_plugin_interface::ReplicaTransactionInfoV3;
+    use solana_hash::Hash;
+    use solana_message::compiled_instruction::CompiledInstruction;
+    use solana_message::{Message, VersionedMessage};
+    use solana_signature::Signature;
+    use std::str::FromStr;
+
+    const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+
+    fn transaction() -> (VersionedTransaction, TransactionStatusMeta) {
+        let payer = Pubkey::new_unique();
+        let message = Message::new_with_compiled_instructions(
+            1,
+            0,
+            1,
+            vec![payer, Pubkey::from_str(SYSTEM_PROGRAM).unwrap()],
+            Hash::default(),
+            vec![CompiledInstruction::new_from_raw_parts(1, vec![], vec![0])],
+        );
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::from([7; 64])],
+            message: VersionedMessage::Legacy(message),
+        };
+        let meta = TransactionStatusMeta {
+            fee: 5000,
+            pre_balances: vec![1_000_000, 1],
+            post_balances: vec![995_000, 1],
+            log_messages: Some(vec![
+                format!("Program {} invoke [1]", SYSTEM_PROGRAM),
+                format!("Program {} success", SYSTEM_PROGRAM),
+            ]),
+            ..TransactionStatusMeta::default()
+        };
+        (transaction, meta)
+    }
+
+    /// A loaded plugin whose queue the test reads instead of the pipeline.
+    fn loaded_plugin(
+        include_votes: bool,
+    ) -> (SologgerGeyserPlugin, mpsc::Receiver<TransactionUpdate>) {
+        loaded_plugin_selecting(include_votes, ProgramsSelector::new_all_programs())
+    }
+
+    fn loaded_plugin_selecting(
+        include_votes: bool,
+        program_selector: ProgramsSelector,
+    ) -> (SologgerGeyserPlugin, mpsc::Receiver<TransactionUpdate>) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let consumer = runtime.spawn(async {});
+        let (sender, receiver) = mpsc::channel(4);
+        let plugin = SologgerGeyserPlugin {
+            inner: Some(Inner {
+                runtime,
+                sender,
+                consumer,
+                include_votes,
+                program_selector,
+            }),
+        };
+        (plugin, receiver)
+    }
+
+    fn notify(plugin: &SologgerGeyserPlugin, is_vote: bool) -> Result<()> {
+        let (transaction, meta) = transaction();
+        let info = ReplicaTransactionInfoV3 {
+            signature: &transaction.signatures[0],
+            message_hash: &Hash::default(),
+            is_vote,
+            transaction: &transaction,
+            transaction_status_meta: &meta,
+            index: 3,
+        };
+        plugin.notify_transaction(ReplicaTransactionInfoVersions::V0_0_3(&info), 42)
+    }
+
+    #[test]
+    fn test_notify_transaction_parses_into_log_contexts() {
+        let (plugin, mut receiver) = loaded_plugin(false);
+        assert!(plugin.transaction_notifications_enabled());
+        assert!(!plugin.account_data_notifications_enabled());
+
+        notify(&plugin, false).unwrap();
+        let update = receiver.try_recv().unwrap();
+        assert_eq!(update.slot, 42);
+        assert_eq!(update.index, Some(3));
+
+        let log_contexts =
+            log_contexts_from_update(update, &ProgramsSelector::new_all_programs()).unwrap();
+        assert_eq!(log_contexts.len(), 1);
+        let log_context = &log_contexts[0];
+        assert_eq!(log_context.program_id, SYSTEM_PROGRAM);
+        assert_eq!(log_context.slot, 42);
+        assert_eq!(log_context.transaction_index, Some(3));
+        assert!(log_context.received_at.is_some_and(|ms| ms > 0));
+        assert_eq!(log_context.signature, Signature::from([7; 64]).to_string());
+        assert_eq!(log_context.fees.as_ref().unwrap().fee, 5000);
+        assert!(log_context.transaction_error.is_empty());
+    }
+
+    #[test]
+    fn test_notify_transaction_skips_votes_unless_configured() {
+        let (plugin, mut receiver) = loaded_plugin(false);
+        notify(&plugin, true).unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        let (plugin, mut receiver) = loaded_plugin(true);
+        notify(&plugin, true).unwrap();
+        assert!(receiver.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_notify_transaction_skips_unselected_programs() {
+        let other_program = Pubkey::new_unique().to_string();
+        let (plugin, mut receiver) =
+            loaded_plugin_selecting(false, ProgramsSelector::new(&[other_program]));
+        notify(&plugin, false).unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        let (plugin, mut receiver) =
+            loaded_plugin_selecting(false, ProgramsSelector::new(&[SYSTEM_PROGRAM.to_string()]));
+        notify(&plugin, false).unwrap();
+        assert!(receiver.try_recv().is_ok());
+    }
+
+    #[test]
+    fn test_notify_transaction_drops_when_queue_is_full_or_closed() {
+        let (plugin, receiver) = loaded_plugin(false);
+        for _ in 0..5 {
+            notify(&plugin, false).unwrap();
+        }
+        assert_eq!(receiver.len(), 4);
+
+        drop(receiver);
+        assert!(matches!(
+            notify(&plugin, false),
+            Err(GeyserPluginError::TransactionUpdateError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_notify_transaction_before_load_is_ignored() {
+        let plugin = SologgerGeyserPlugin::default();
+        assert!(notify(&plugin, false).is_ok());
+    }
+
+    #[test]
+    fn test_on_load_reports_config_errors() {
+        let dir =
+            std::env::temp_dir().join(format!("sologger-geyser-errors-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut plugin = SologgerGeyserPlugin::default();
+
+        let missing = dir.join("missing.json");
+        assert!(matches!(
+            plugin.on_load(missing.to_str().unwrap(), false),
+            Err(GeyserPluginError::ConfigFileOpenError(_))
+        ));
+
+        let without_location = dir.join("geyser-plugin-config.json");
+        std::fs::write(
+            &without_location,
+            r#"{"libpath": "libsologger_geyser_plugin.so"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            plugin.on_load(without_location.to_str().unwrap(), false),
+            Err(GeyserPluginError::ConfigFileReadError { .. })
+        ));
+        assert!(plugin.inner.is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_on_load_runs_pipeline_until_unload() {
+        let dir = std::env::temp_dir().join(format!("sologger-geyser-load-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let sologger_config = dir.join("sologger-config.json");
+        std::fs::write(
+            &sologger_config,
+            r#"{"rpcUrl": "ws://127.0.0.1:8900", "programsSelector": {"programs": ["*"]}}"#,
+        )
+        .unwrap();
+        let plugin_config = dir.join("geyser-plugin-config.json");
+        std::fs::write(
+            &plugin_config,
+            serde_json::json!({
+                "libpath": "libsologger_geyser_plugin.so",
+                "sologgerConfigLocation": sologger_config,
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let mut plugin = SologgerGeyserPlugin::default();
+        plugin
+            .on_load(plugin_config.to_str().unwrap(), false)
+            .unwrap();
+        notify(&plugin, false).unwrap();
+        // Drains the queued transaction through the pipeline, then stops
+        plugin.on_unload();
+        assert!(plugin.inner.is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
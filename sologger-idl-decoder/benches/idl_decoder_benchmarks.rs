@@ -0,0 +1,118 @@
+use std::hint::black_box;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use criterion::{criterion_group, criterion_main, Criterion};
+use sologger_idl_decoder::{decode_event, event_discriminator, Idl, IdlDecoder, IdlRegistry};
+use sologger_log_context::programs_selector::ProgramsSelector;
+use sologger_log_context::sologger_log_context::LogContext;
+
+const RAYDIUM_IDL: &str = include_str!("../tests/fixtures/raydium_cp_swap_idl.json");
+const LEGACY_IDL: &str = include_str!("../tests/fixtures/legacy_anchor_idl.json");
+const RAYDIUM_PROGRAM_ID: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+/// A Raydium SwapEvent: explicit discriminator, flat struct from `types`.
+fn swap_event() -> String {
+    let mut buf: Vec<u8> = vec![64, 198, 205, 232, 38, 8, 113, 226];
+    buf.extend_from_slice(&[3u8; 32]); // pool_id
+    for amount in [500u64, 600, 1_000_000, 990_000, 10, 12] {
+        buf.extend_from_slice(&amount.to_le_bytes());
+    }
+    buf.push(1); // base_input
+    buf.extend_from_slice(&[4u8; 32]); // input_mint
+    buf.extend_from_slice(&[5u8; 32]); // output_mint
+    buf.extend_from_slice(&2500u64.to_le_bytes()); // trade_fee
+    buf.extend_from_slice(&100u64.to_le_bytes()); // creator_fee
+    buf.push(0); // creator_fee_on_input
+    BASE64.encode(&buf)
+}
+
+/// A legacy TradeEvent: sha256-derived discriminator, nested defined structs and enums.
+fn trade_event() -> String {
+    let mut buf: Vec<u8> = event_discriminator("TradeEvent").to_vec();
+    buf.extend_from_slice(&[7u8; 32]); // trader
+    buf.extend_from_slice(&5000u64.to_le_bytes()); // amount
+    buf.extend_from_slice(&1u128.to_le_bytes()); // price
+    buf.extend_from_slice(&2u32.to_le_bytes());
+    buf.extend_from_slice(b"gm"); // memo
+    buf.push(0); // referrer
+    buf.extend_from_slice(&2u32.to_le_bytes()); // legs
+    for (market, qty) in [(7u16, 9u32), (8, 10)] {
+        buf.extend_from_slice(&market.to_le_bytes());
+        buf.extend_from_slice(&qty.to_le_bytes());
+    }
+    buf.push(1); // side
+    buf.extend_from_slice(&[1, 2, 3, 4]); // tags
+    BASE64.encode(&buf)
+}
+
+fn bench_decode_event(c: &mut Criterion) {
+    let raydium = Idl::from_json(RAYDIUM_IDL).unwrap();
+    let legacy = Idl::from_json(LEGACY_IDL).unwrap();
+    let raydium_decoder = IdlDecoder::new(&raydium);
+    let legacy_decoder = IdlDecoder::new(&legacy);
+    let swap = swap_event();
+    let trade = trade_event();
+
+    c.bench_function("decode_event swap (compile per call)", |b| {
+        b.iter(|| decode_event(black_box(&raydium), black_box(&swap)))
+    });
+    c.bench_function("IdlDecoder::decode_event swap", |b| {
+        b.iter(|| raydium_decoder.decode_event(black_box(&swap)))
+    });
+    c.bench_function("decode_event legacy trade (compile per call)", |b| {
+        b.iter(|| decode_event(black_box(&legacy), black_box(&trade)))
+    });
+    c.bench_function("IdlDecoder::decode_event legacy trade", |b| {
+        b.iter(|| legacy_decoder.decode_event(black_box(&trade)))
+    });
+}
+
+fn bench_compile(c: &mut Criterion) {
+    let raydium = Idl::from_json(RAYDIUM_IDL).unwrap();
+
+    c.bench_function("IdlDecoder::new raydium", |b| {
+        b.iter(|| IdlDecoder::new(black_box(&raydium)))
+    });
+}
+
+fn bench_enrich_block(c: &mut Criterion) {
+    let mut registry = IdlRegistry::new();
+    registry
+        .insert_json(RAYDIUM_PROGRAM_ID, RAYDIUM_IDL)
+        .unwrap();
+
+    // An event-heavy block: 1000 swaps, one event each
+    let swap = swap_event();
+    let mut logs = Vec::new();
+    for _ in 0..1000 {
+        logs.push(format!("Program {} invoke [1]", RAYDIUM_PROGRAM_ID));
+        logs.push(format!("Program data: {}", swap));
+        logs.push(format!("Program {} success", RAYDIUM_PROGRAM_ID));
+    }
+    let parse = || {
+        LogContext::parse_logs(
+            &logs,
+            "".to_string(),
+            &ProgramsSelector::new_all_programs(),
+            1,
+            "sig".to_string(),
+        )
+    };
+
+    c.bench_function("enrich_all 1000 swap events", |b| {
+        b.iter_batched(
+            parse,
+            |mut log_contexts| registry.enrich_all(black_box(&mut log_contexts)),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_decode_event,
+    bench_compile,
+    bench_enrich_block
+);
+criterion_main!(benches);
@@ -1,9 +1,13 @@
+use std::collections::HashMap;
+
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use sha2::{Digest, Sha256};
+use sologger_log_context::sologger_log_context::DecodedLogEvent;
 
-use crate::idl::{Idl, IdlDefinedFields, IdlEvent, IdlField, IdlType, IdlTypeDefTy};
+use crate::idl::{Idl, IdlDefinedFields, IdlField, IdlType, IdlTypeDef, IdlTypeDefTy};
 
 /// Decoding stops recursing into nested `defined`/`option`/`vec` types beyond this depth.
 /// Real event payloads nest a handful of levels; the cap only guards against pathological
@@ -15,21 +19,100 @@ const MAX_DEPTH: usize = 64;
 pub struct DecodedEvent {
     ///The event name as declared in the IDL
     pub name: String,
+    ///The discriminator the payload started with: 8 bytes for Anchor events, any
+    ///length for custom discriminators and user-defined layouts
+    pub discriminator: Vec<u8>,
     ///The decoded fields. u128/i128 values are rendered as decimal strings, pubkeys as
     ///base58 strings, and `bytes` fields as base64 strings
     pub data: Value,
+    ///Only in [`DecodeMode::Lenient`]: where decoding stopped. `data` then holds the
+    ///top-level fields decoded before the failure, or every field when the failure is
+    ///trailing bytes
+    pub failure: Option<DecodeFailure>,
+}
+
+/// An Anchor account decoded from its raw data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedAccount {
+    ///The account name as declared in the IDL
+    pub name: String,
+    ///The discriminator the data started with
+    pub discriminator: Vec<u8>,
+    ///The decoded fields, rendered as for [`DecodedEvent::data`]
+    pub data: Value,
+}
+
+/// How strictly a payload must match the event layout once its discriminator matched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecodeMode {
+    ///Fail on malformed payloads; ignore bytes left after the last field
+    #[default]
+    Standard,
+    ///Also fail when bytes are left after the last field, a strong sign the IDL no
+    ///longer matches the program
+    Strict,
+    ///Never fail a matched payload: return the fields decoded so far, with the failure
+    ///(or the trailing bytes) in `DecodedEvent::failure`
+    Lenient,
+}
+
+/// Where and why decoding a matched event stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeFailure {
+    ///Byte offset into the payload, discriminator included
+    pub offset: usize,
+    ///The top-level field being decoded, when the event is a struct with named fields
+    pub field: Option<String>,
+    pub error: DecodeError,
+}
+
+impl DecodeFailure {
+    /// Whether every field decoded and only trailing bytes remain.
+    pub fn is_trailing_bytes(&self) -> bool {
+        matches!(self.error, DecodeError::TrailingBytes { .. })
+    }
+}
+
+impl std::fmt::Display for DecodeFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(
+                f,
+                "{} (field {}, offset {})",
+                self.error, field, self.offset
+            ),
+            None => write!(f, "{} (offset {})", self.error, self.offset),
+        }
+    }
 }
 
 impl DecodedEvent {
-    /// Renders the event as a compact JSON string of the form {"name":...,"data":{...}},
-    /// the format stored in `LogContext::decoded_events`.
+    /// Renders the event as a compact JSON string of the form {"name":...,"data":{...}}.
     pub fn to_json(&self) -> String {
         serde_json::json!({ "name": self.name, "data": self.data }).to_string()
     }
+
+    /// The structured form stored in `LogContext::decoded_events`, for an event emitted
+    /// by `program_id` and decoded from `data_logs[data_log_index]`.
+    pub fn into_log_event(self, program_id: &str, data_log_index: usize) -> DecodedLogEvent {
+        DecodedLogEvent {
+            name: self.name,
+            program: program_id.to_string(),
+            discriminator: hex(&self.discriminator),
+            partial: self
+                .failure
+                .as_ref()
+                .is_some_and(|failure| !failure.is_trailing_bytes()),
+            data: self.data,
+            data_log_index,
+            from_log_message: false,
+        }
+    }
 }
 
 /// Why a decode attempt failed.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DecodeError {
     ///The IDL JSON itself could not be parsed
     InvalidIdl(String),
@@ -39,6 +122,8 @@ pub enum DecodeError {
     UnknownType(String),
     ///The field uses a type this decoder does not support (e.g. generics, u256)
     UnsupportedType(String),
+    ///Bytes were left after the event's last field ([`DecodeMode::Strict`])
+    TrailingBytes { event: String, remaining: usize },
 }
 
 impl std::fmt::Display for DecodeError {
@@ -48,6 +133,11 @@ impl std::fmt::Display for DecodeError {
             DecodeError::InvalidData(msg) => write!(f, "invalid event data: {}", msg),
             DecodeError::UnknownType(name) => write!(f, "unknown type in IDL: {}", name),
             DecodeError::UnsupportedType(name) => write!(f, "unsupported IDL type: {}", name),
+            DecodeError::TrailingBytes { event, remaining } => write!(
+                f,
+                "{} trailing bytes after event {}; the IDL may not match the program",
+                remaining, event
+            ),
         }
     }
 }
@@ -57,8 +147,30 @@ impl std::error::Error for DecodeError {}
 /// The discriminator anchor-lang derives for an event name: sha256("event:<Name>")[..8].
 /// 0.30+ IDLs list the same bytes explicitly; legacy IDLs rely on this computation.
 pub fn event_discriminator(name: &str) -> [u8; 8] {
+    sighash("event", name)
+}
+
+/// The discriminator anchor-lang derives for an instruction:
+/// sha256("global:<snake_case_name>")[..8]. Legacy IDLs spell instruction names in
+/// camelCase, 0.30+ in snake_case; both hash the snake_case form.
+pub fn instruction_discriminator(name: &str) -> [u8; 8] {
+    sighash("global", &snake_case(name))
+}
+
+/// The discriminator anchor-lang derives for an account type: sha256("account:<Name>")[..8].
+pub fn account_discriminator(name: &str) -> [u8; 8] {
+    sighash("account", name)
+}
+
+/// Lowercase hex, the rendering of discriminators in `DecodedLogEvent` and reports.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn sighash(namespace: &str, name: &str) -> [u8; 8] {
     let mut hasher = Sha256::new();
-    hasher.update(b"event:");
+    hasher.update(namespace.as_bytes());
+    hasher.update(b":");
     hasher.update(name.as_bytes());
     let digest = hasher.finalize();
     let mut out = [0u8; 8];
@@ -66,214 +178,542 @@ pub fn event_discriminator(name: &str) -> [u8; 8] {
     out
 }
 
+/// `SwapEvent` -> `swap_event`, `LPChange` -> `lp_change`, `swapBaseInput` ->
+/// `swap_base_input`, matching the conversion anchor-lang applies.
+pub(crate) fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (index, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev_lower = index > 0 && !chars[index - 1].is_uppercase();
+            let next_lower = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+            if index > 0 && (prev_lower || next_lower) && chars[index - 1] != '_' {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
 /// Attempts to decode one base64 'Program data:' payload against the IDL's events.
 ///
 /// Returns Ok(None) when the payload is well-formed but matches no event discriminator
 /// (common: programs also emit non-event data), and Err when a matched event's payload
-/// cannot be decoded.
+/// cannot be decoded. Compiles the IDL on every call; hold an [`IdlDecoder`] to decode
+/// many payloads.
 pub fn decode_event(idl: &Idl, data_b64: &str) -> Result<Option<DecodedEvent>, DecodeError> {
-    let bytes = BASE64
-        .decode(data_b64.trim())
-        .map_err(|e| DecodeError::InvalidData(format!("base64: {}", e)))?;
-    if bytes.len() < 8 {
-        return Ok(None);
-    }
-
-    for event in &idl.events {
-        if bytes[..8] == event.discriminator_bytes() {
-            let mut reader = Reader::new(&bytes[8..]);
-            let data = decode_event_fields(idl, event, &mut reader)?;
-            return Ok(Some(DecodedEvent {
-                name: event.name.clone(),
-                data,
-            }));
-        }
-    }
-    Ok(None)
+    IdlDecoder::new(idl).decode_event(data_b64)
 }
 
 /// Decodes every payload in `data_logs`, silently skipping entries that match no event
 /// or fail to decode. The lossy convenience wrapper used for log enrichment.
 pub fn decode_events(idl: &Idl, data_logs: &[String]) -> Vec<DecodedEvent> {
-    data_logs
-        .iter()
-        .filter_map(|data| match decode_event(idl, data) {
-            Ok(found) => found,
-            Err(err) => {
-                log::debug!("skipping undecodable data log: {}", err);
-                None
-            }
-        })
-        .collect()
+    IdlDecoder::new(idl).decode_events(data_logs)
 }
 
-fn decode_event_fields(
-    idl: &Idl,
-    event: &IdlEvent,
-    reader: &mut Reader,
-) -> Result<Value, DecodeError> {
-    match &event.fields {
-        // Legacy spec: fields inline on the event
-        Some(fields) => decode_named_fields(idl, fields, reader, 0),
-        // 0.30+ spec: fields live in a type definition of the same name
-        None => decode_defined(idl, &event.name, reader, 0),
-    }
+/// An IDL compiled for decoding: events are indexed by discriminator and every `defined`
+/// reference is resolved to its type definition up front, so decoding a payload is one
+/// hash lookup plus a walk of the layout — no per-payload sha256 or type-name searches.
+/// Build one per IDL at registration time; `IdlRegistry` does this for you.
+#[derive(Clone, Debug)]
+pub struct IdlDecoder {
+    events: HashMap<Vec<u8>, CompiledEvent>,
+    ///The distinct discriminator lengths, longest first
+    discriminator_lens: Vec<usize>,
+    accounts: HashMap<Vec<u8>, CompiledEvent>,
+    ///The distinct account discriminator lengths, longest first
+    account_discriminator_lens: Vec<usize>,
+    ///Layouts of the IDL's `types`, indexed by `Layout::Defined`
+    types: Vec<DefinedLayout>,
+}
+
+/// A failed event body: the fields decoded so far, the failing field and the error.
+type PartialDecode = (Value, Option<String>, DecodeError);
+
+#[derive(Clone, Debug)]
+struct CompiledEvent {
+    name: String,
+    layout: Layout,
+}
+
+/// An `IdlType` with `defined` references resolved to indices into `IdlDecoder::types`.
+#[derive(Clone, Debug)]
+enum Layout {
+    Bool,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    U128,
+    I128,
+    F32,
+    F64,
+    Bytes,
+    String,
+    Pubkey,
+    Vec(Box<Layout>),
+    Option(Box<Layout>),
+    Array(Box<Layout>, usize),
+    Defined(usize),
+    ///Inline fields: a legacy event's body
+    Fields(FieldsLayout),
+    ///A `defined` reference missing from `types`; only fails when a payload reaches it
+    Unknown(String),
+    Unsupported(String),
+}
+
+#[derive(Clone, Debug)]
+enum DefinedLayout {
+    Struct(FieldsLayout),
+    Enum {
+        name: String,
+        variants: Vec<(String, Option<FieldsLayout>)>,
+    },
+    Alias(Layout),
+}
+
+#[derive(Clone, Debug)]
+enum FieldsLayout {
+    Named(Vec<(String, Layout)>),
+    Tuple(Vec<Layout>),
 }
 
-fn decode_defined(
-    idl: &Idl,
-    name: &str,
-    reader: &mut Reader,
-    depth: usize,
-) -> Result<Value, DecodeError> {
-    if depth > MAX_DEPTH {
-        return Err(DecodeError::InvalidData(
-            "max nesting depth exceeded".into(),
-        ));
+impl IdlDecoder {
+    /// Compiles an IDL. Never fails: unknown type references and unsupported types are
+    /// kept and reported when a payload actually needs them, as with the IDL itself.
+    pub fn new(idl: &Idl) -> Self {
+        // The first definition of a name wins, matching `Idl::find_type`
+        let mut type_indices: HashMap<&str, usize> = HashMap::new();
+        for (index, type_def) in idl.types.iter().enumerate() {
+            type_indices.entry(type_def.name.as_str()).or_insert(index);
+        }
+        let compiler = Compiler { type_indices };
+
+        let types = idl
+            .types
+            .iter()
+            .map(|type_def| compiler.defined(type_def))
+            .collect();
+
+        let mut events = HashMap::with_capacity(idl.events.len());
+        for event in &idl.events {
+            let layout = match &event.fields {
+                // Legacy spec: fields inline on the event
+                Some(fields) => Layout::Fields(compiler.named_fields(fields)),
+                // 0.30+ spec: fields live in a type definition of the same name
+                None => compiler.defined_ref(&event.name),
+            };
+            // The first event with a discriminator wins, as the linear scan did
+            events
+                .entry(event.discriminator_bytes())
+                .or_insert_with(|| CompiledEvent {
+                    name: event.name.clone(),
+                    layout,
+                });
+        }
+
+        let mut accounts = HashMap::with_capacity(idl.accounts.len());
+        for account in &idl.accounts {
+            let layout = match &account.ty {
+                // Legacy spec: the layout inline on the account
+                Some(IdlTypeDefTy::Struct { fields }) => Layout::Fields(
+                    fields
+                        .as_ref()
+                        .map_or(FieldsLayout::Named(vec![]), |fields| {
+                            compiler.fields(fields)
+                        }),
+                ),
+                Some(_) => Layout::Unsupported(format!("non-struct account {}", account.name)),
+                // 0.30+ spec: the layout is the type definition of the same name
+                None => compiler.defined_ref(&account.name),
+            };
+            accounts
+                .entry(account.discriminator_bytes())
+                .or_insert_with(|| CompiledEvent {
+                    name: account.name.clone(),
+                    layout,
+                });
+        }
+
+        Self {
+            discriminator_lens: discriminator_lens(&events),
+            events,
+            account_discriminator_lens: discriminator_lens(&accounts),
+            accounts,
+            types,
+        }
     }
-    let type_def = idl
-        .find_type(name)
-        .ok_or_else(|| DecodeError::UnknownType(name.to_string()))?;
-    match &type_def.ty {
-        IdlTypeDefTy::Struct { fields } => {
-            decode_defined_fields(idl, fields.as_ref(), reader, depth)
+
+    /// Decodes one base64 'Program data:' payload in [`DecodeMode::Standard`]. See
+    /// [`decode_event`].
+    pub fn decode_event(&self, data_b64: &str) -> Result<Option<DecodedEvent>, DecodeError> {
+        self.decode_event_with(data_b64, DecodeMode::Standard)
+    }
+
+    /// Decodes one base64 'Program data:' payload. Ok(None) when no event matches;
+    /// otherwise `mode` decides whether a payload that doesn't fit the event's layout is
+    /// an error or a partial [`DecodedEvent`].
+    pub fn decode_event_with(
+        &self,
+        data_b64: &str,
+        mode: DecodeMode,
+    ) -> Result<Option<DecodedEvent>, DecodeError> {
+        let bytes = BASE64
+            .decode(data_b64.trim())
+            .map_err(|e| DecodeError::InvalidData(format!("base64: {}", e)))?;
+
+        let Some((len, event)) = lookup(&self.events, &self.discriminator_lens, &bytes) else {
+            return Ok(None);
+        };
+        let mut reader = Reader::new(&bytes[len..]);
+        let (data, failure) = match self.decode_top_level(&event.layout, &mut reader) {
+            Ok(data) if reader.remaining() == 0 || mode == DecodeMode::Standard => (data, None),
+            Ok(data) => {
+                let error = DecodeError::TrailingBytes {
+                    event: event.name.clone(),
+                    remaining: reader.remaining(),
+                };
+                (data, Some((None, error)))
+            }
+            Err((partial, field, error)) => (partial, Some((field, error))),
+        };
+        let failure = failure.map(|(field, error)| DecodeFailure {
+            offset: len + reader.pos,
+            field,
+            error,
+        });
+        match failure {
+            Some(failure) if mode != DecodeMode::Lenient => Err(failure.error),
+            failure => Ok(Some(DecodedEvent {
+                name: event.name.clone(),
+                discriminator: bytes[..len].to_vec(),
+                data,
+                failure,
+            })),
         }
-        IdlTypeDefTy::Enum { variants } => {
-            let index = reader.read_u8()? as usize;
-            let variant = variants.get(index).ok_or_else(|| {
-                DecodeError::InvalidData(format!("enum {} has no variant {}", name, index))
-            })?;
-            match &variant.fields {
-                None => Ok(Value::String(variant.name.clone())),
-                Some(fields) => {
-                    let inner = decode_defined_fields(idl, Some(fields), reader, depth)?;
-                    let mut map = Map::new();
-                    map.insert(variant.name.clone(), inner);
-                    Ok(Value::Object(map))
+    }
+
+    /// Decodes the raw data of an account owned by the IDL's program. Ok(None) when the
+    /// data matches no account discriminator. Accounts are usually allocated with spare
+    /// room, so bytes after the last field are ignored.
+    pub fn decode_account(&self, data: &[u8]) -> Result<Option<DecodedAccount>, DecodeError> {
+        let Some((len, account)) = lookup(&self.accounts, &self.account_discriminator_lens, data)
+        else {
+            return Ok(None);
+        };
+        let mut reader = Reader::new(&data[len..]);
+        let data_value = self
+            .decode_top_level(&account.layout, &mut reader)
+            .map_err(|(_, _, error)| error)?;
+        Ok(Some(DecodedAccount {
+            name: account.name.clone(),
+            discriminator: data[..len].to_vec(),
+            data: data_value,
+        }))
+    }
+
+    /// Decodes an event body. Structs with named fields are decoded field by field so a
+    /// failure carries the fields decoded before it and the failing field's name.
+    fn decode_top_level(
+        &self,
+        layout: &Layout,
+        reader: &mut Reader,
+    ) -> Result<Value, PartialDecode> {
+        let fields = match layout {
+            Layout::Fields(FieldsLayout::Named(fields)) => fields,
+            Layout::Defined(index) => match &self.types[*index] {
+                DefinedLayout::Struct(FieldsLayout::Named(fields)) => fields,
+                _ => {
+                    return self
+                        .decode_layout(layout, reader, 0)
+                        .map_err(|error| (Value::Null, None, error))
                 }
+            },
+            _ => {
+                return self
+                    .decode_layout(layout, reader, 0)
+                    .map_err(|error| (Value::Null, None, error))
+            }
+        };
+        let mut map = Map::new();
+        for (name, field_layout) in fields {
+            match self.decode_layout(field_layout, reader, 1) {
+                Ok(value) => {
+                    map.insert(name.clone(), value);
+                }
+                Err(error) => return Err((Value::Object(map), Some(name.clone()), error)),
             }
         }
-        IdlTypeDefTy::Alias { value } => decode_type(idl, value, reader, depth + 1),
+        Ok(Value::Object(map))
     }
-}
 
-fn decode_defined_fields(
-    idl: &Idl,
-    fields: Option<&IdlDefinedFields>,
-    reader: &mut Reader,
-    depth: usize,
-) -> Result<Value, DecodeError> {
-    match fields {
-        None => Ok(Value::Object(Map::new())),
-        Some(IdlDefinedFields::Named(fields)) => decode_named_fields(idl, fields, reader, depth),
-        Some(IdlDefinedFields::Tuple(types)) => {
-            let mut items = Vec::with_capacity(types.len());
-            for ty in types {
-                items.push(decode_type(idl, ty, reader, depth + 1)?);
+    /// Decodes every payload in `data_logs`. See [`decode_events`].
+    pub fn decode_events(&self, data_logs: &[String]) -> Vec<DecodedEvent> {
+        data_logs
+            .iter()
+            .filter_map(|data| match self.decode_event(data) {
+                Ok(found) => found,
+                Err(err) => {
+                    log::debug!("skipping undecodable data log: {}", err);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn decode_defined(
+        &self,
+        index: usize,
+        reader: &mut Reader,
+        depth: usize,
+    ) -> Result<Value, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::InvalidData(
+                "max nesting depth exceeded".into(),
+            ));
+        }
+        match &self.types[index] {
+            DefinedLayout::Struct(fields) => self.decode_fields(fields, reader, depth),
+            DefinedLayout::Enum { name, variants } => {
+                let index = reader.read_u8()? as usize;
+                let (variant_name, fields) = variants.get(index).ok_or_else(|| {
+                    DecodeError::InvalidData(format!("enum {} has no variant {}", name, index))
+                })?;
+                match fields {
+                    None => Ok(Value::String(variant_name.clone())),
+                    Some(fields) => {
+                        let inner = self.decode_fields(fields, reader, depth)?;
+                        let mut map = Map::new();
+                        map.insert(variant_name.clone(), inner);
+                        Ok(Value::Object(map))
+                    }
+                }
             }
-            Ok(Value::Array(items))
+            DefinedLayout::Alias(layout) => self.decode_layout(layout, reader, depth + 1),
         }
     }
-}
 
-fn decode_named_fields(
-    idl: &Idl,
-    fields: &[IdlField],
-    reader: &mut Reader,
-    depth: usize,
-) -> Result<Value, DecodeError> {
-    let mut map = Map::new();
-    for field in fields {
-        let value = decode_type(idl, &field.ty, reader, depth + 1)?;
-        map.insert(field.name.clone(), value);
+    fn decode_fields(
+        &self,
+        fields: &FieldsLayout,
+        reader: &mut Reader,
+        depth: usize,
+    ) -> Result<Value, DecodeError> {
+        match fields {
+            FieldsLayout::Named(fields) => {
+                let mut map = Map::new();
+                for (name, layout) in fields {
+                    let value = self.decode_layout(layout, reader, depth + 1)?;
+                    map.insert(name.clone(), value);
+                }
+                Ok(Value::Object(map))
+            }
+            FieldsLayout::Tuple(layouts) => {
+                let mut items = Vec::with_capacity(layouts.len());
+                for layout in layouts {
+                    items.push(self.decode_layout(layout, reader, depth + 1)?);
+                }
+                Ok(Value::Array(items))
+            }
+        }
     }
-    Ok(Value::Object(map))
-}
 
-fn decode_type(
-    idl: &Idl,
-    ty: &IdlType,
-    reader: &mut Reader,
-    depth: usize,
-) -> Result<Value, DecodeError> {
-    if depth > MAX_DEPTH {
-        return Err(DecodeError::InvalidData(
-            "max nesting depth exceeded".into(),
-        ));
-    }
-    match ty {
-        IdlType::Bool => match reader.read_u8()? {
-            0 => Ok(Value::Bool(false)),
-            1 => Ok(Value::Bool(true)),
-            other => Err(DecodeError::InvalidData(format!(
-                "invalid bool tag {}",
-                other
-            ))),
-        },
-        IdlType::U8 => Ok(Value::from(reader.read_u8()?)),
-        IdlType::I8 => Ok(Value::from(reader.read_i8()?)),
-        IdlType::U16 => Ok(Value::from(reader.read_u16()?)),
-        IdlType::I16 => Ok(Value::from(reader.read_i16()?)),
-        IdlType::U32 => Ok(Value::from(reader.read_u32()?)),
-        IdlType::I32 => Ok(Value::from(reader.read_i32()?)),
-        IdlType::U64 => Ok(Value::from(reader.read_u64()?)),
-        IdlType::I64 => Ok(Value::from(reader.read_i64()?)),
-        // 128-bit integers exceed JSON number precision; render as decimal strings
-        IdlType::U128 => Ok(Value::String(reader.read_u128()?.to_string())),
-        IdlType::I128 => Ok(Value::String(reader.read_i128()?.to_string())),
-        IdlType::F32 => Ok(Value::from(reader.read_f32()? as f64)),
-        IdlType::F64 => Ok(Value::from(reader.read_f64()?)),
-        IdlType::Bytes => {
-            let len = reader.read_len()?;
-            let bytes = reader.take(len)?;
-            Ok(Value::String(BASE64.encode(bytes)))
+    fn decode_layout(
+        &self,
+        layout: &Layout,
+        reader: &mut Reader,
+        depth: usize,
+    ) -> Result<Value, DecodeError> {
+        if depth > MAX_DEPTH {
+            return Err(DecodeError::InvalidData(
+                "max nesting depth exceeded".into(),
+            ));
         }
-        IdlType::String => {
-            let len = reader.read_len()?;
-            let bytes = reader.take(len)?;
-            Ok(Value::String(String::from_utf8_lossy(bytes).into_owned()))
+        match layout {
+            Layout::Bool => match reader.read_u8()? {
+                0 => Ok(Value::Bool(false)),
+                1 => Ok(Value::Bool(true)),
+                other => Err(DecodeError::InvalidData(format!(
+                    "invalid bool tag {}",
+                    other
+                ))),
+            },
+            Layout::U8 => Ok(Value::from(reader.read_u8()?)),
+            Layout::I8 => Ok(Value::from(reader.read_i8()?)),
+            Layout::U16 => Ok(Value::from(reader.read_u16()?)),
+            Layout::I16 => Ok(Value::from(reader.read_i16()?)),
+            Layout::U32 => Ok(Value::from(reader.read_u32()?)),
+            Layout::I32 => Ok(Value::from(reader.read_i32()?)),
+            Layout::U64 => Ok(Value::from(reader.read_u64()?)),
+            Layout::I64 => Ok(Value::from(reader.read_i64()?)),
+            // 128-bit integers exceed JSON number precision; render as decimal strings
+            Layout::U128 => Ok(Value::String(reader.read_u128()?.to_string())),
+            Layout::I128 => Ok(Value::String(reader.read_i128()?.to_string())),
+            Layout::F32 => Ok(Value::from(reader.read_f32()? as f64)),
+            Layout::F64 => Ok(Value::from(reader.read_f64()?)),
+            Layout::Bytes => {
+                let len = reader.read_len()?;
+                let bytes = reader.take(len)?;
+                Ok(Value::String(BASE64.encode(bytes)))
+            }
+            Layout::String => {
+                let len = reader.read_len()?;
+                let bytes = reader.take(len)?;
+                Ok(Value::String(String::from_utf8_lossy(bytes).into_owned()))
+            }
+            Layout::Pubkey => {
+                let bytes = reader.take(32)?;
+                Ok(Value::String(bs58::encode(bytes).into_string()))
+            }
+            Layout::Vec(elem) => {
+                let len = reader.read_len()?;
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.decode_layout(elem, reader, depth + 1)?);
+                }
+                Ok(Value::Array(items))
+            }
+            Layout::Option(inner) => match reader.read_u8()? {
+                0 => Ok(Value::Null),
+                1 => self.decode_layout(inner, reader, depth + 1),
+                other => Err(DecodeError::InvalidData(format!(
+                    "invalid option tag {}",
+                    other
+                ))),
+            },
+            Layout::Array(elem, len) => {
+                let mut items = Vec::with_capacity((*len).min(4096));
+                for _ in 0..*len {
+                    items.push(self.decode_layout(elem, reader, depth + 1)?);
+                }
+                Ok(Value::Array(items))
+            }
+            Layout::Defined(index) => self.decode_defined(*index, reader, depth + 1),
+            Layout::Fields(fields) => self.decode_fields(fields, reader, depth),
+            Layout::Unknown(name) => Err(DecodeError::UnknownType(name.clone())),
+            Layout::Unsupported(desc) => Err(DecodeError::UnsupportedType(desc.clone())),
         }
-        IdlType::Pubkey => {
-            let bytes = reader.take(32)?;
-            Ok(Value::String(bs58::encode(bytes).into_string()))
+    }
+}
+
+/// The distinct discriminator lengths of a table, longest first.
+fn discriminator_lens(table: &HashMap<Vec<u8>, CompiledEvent>) -> Vec<usize> {
+    let mut lens: Vec<usize> = table.keys().map(Vec::len).collect();
+    lens.sort_unstable_by(|a, b| b.cmp(a));
+    lens.dedup();
+    lens
+}
+
+/// The entry whose discriminator prefixes `bytes`, with the discriminator length. Anchor
+/// IDLs have a single length (8); with mixed lengths the longest match wins.
+fn lookup<'a>(
+    table: &'a HashMap<Vec<u8>, CompiledEvent>,
+    lens: &[usize],
+    bytes: &[u8],
+) -> Option<(usize, &'a CompiledEvent)> {
+    lens.iter().find_map(|&len| {
+        let discriminator = bytes.get(..len)?;
+        table.get(discriminator).map(|entry| (len, entry))
+    })
+}
+
+/// Resolves IDL types into layouts against the IDL's type name index.
+struct Compiler<'a> {
+    type_indices: HashMap<&'a str, usize>,
+}
+
+impl Compiler<'_> {
+    fn defined(&self, type_def: &IdlTypeDef) -> DefinedLayout {
+        match &type_def.ty {
+            IdlTypeDefTy::Struct { fields } => DefinedLayout::Struct(
+                fields
+                    .as_ref()
+                    .map_or(FieldsLayout::Named(vec![]), |fields| self.fields(fields)),
+            ),
+            IdlTypeDefTy::Enum { variants } => DefinedLayout::Enum {
+                name: type_def.name.clone(),
+                variants: variants
+                    .iter()
+                    .map(|variant| {
+                        (
+                            variant.name.clone(),
+                            variant.fields.as_ref().map(|fields| self.fields(fields)),
+                        )
+                    })
+                    .collect(),
+            },
+            IdlTypeDefTy::Alias { value } => DefinedLayout::Alias(self.layout(value)),
         }
-        IdlType::Vec(elem) => {
-            let len = reader.read_len()?;
-            let mut items = Vec::new();
-            for _ in 0..len {
-                items.push(decode_type(idl, elem, reader, depth + 1)?);
+    }
+
+    fn fields(&self, fields: &IdlDefinedFields) -> FieldsLayout {
+        match fields {
+            IdlDefinedFields::Named(fields) => self.named_fields(fields),
+            IdlDefinedFields::Tuple(types) => {
+                FieldsLayout::Tuple(types.iter().map(|ty| self.layout(ty)).collect())
             }
-            Ok(Value::Array(items))
         }
-        IdlType::Option(inner) => match reader.read_u8()? {
-            0 => Ok(Value::Null),
-            1 => decode_type(idl, inner, reader, depth + 1),
-            other => Err(DecodeError::InvalidData(format!(
-                "invalid option tag {}",
-                other
-            ))),
-        },
-        IdlType::Array(elem, len) => {
-            let mut items = Vec::with_capacity((*len).min(4096));
-            for _ in 0..*len {
-                items.push(decode_type(idl, elem, reader, depth + 1)?);
-            }
-            Ok(Value::Array(items))
+    }
+
+    fn named_fields(&self, fields: &[IdlField]) -> FieldsLayout {
+        FieldsLayout::Named(
+            fields
+                .iter()
+                .map(|field| (field.name.clone(), self.layout(&field.ty)))
+                .collect(),
+        )
+    }
+
+    fn defined_ref(&self, name: &str) -> Layout {
+        match self.type_indices.get(name) {
+            Some(index) => Layout::Defined(*index),
+            None => Layout::Unknown(name.to_string()),
+        }
+    }
+
+    fn layout(&self, ty: &IdlType) -> Layout {
+        match ty {
+            IdlType::Bool => Layout::Bool,
+            IdlType::U8 => Layout::U8,
+            IdlType::I8 => Layout::I8,
+            IdlType::U16 => Layout::U16,
+            IdlType::I16 => Layout::I16,
+            IdlType::U32 => Layout::U32,
+            IdlType::I32 => Layout::I32,
+            IdlType::U64 => Layout::U64,
+            IdlType::I64 => Layout::I64,
+            IdlType::U128 => Layout::U128,
+            IdlType::I128 => Layout::I128,
+            IdlType::F32 => Layout::F32,
+            IdlType::F64 => Layout::F64,
+            IdlType::Bytes => Layout::Bytes,
+            IdlType::String => Layout::String,
+            IdlType::Pubkey => Layout::Pubkey,
+            IdlType::Vec(elem) => Layout::Vec(Box::new(self.layout(elem))),
+            IdlType::Option(inner) => Layout::Option(Box::new(self.layout(inner))),
+            IdlType::Array(elem, len) => Layout::Array(Box::new(self.layout(elem)), *len),
+            IdlType::Defined(name) => self.defined_ref(name),
+            IdlType::Unsupported(desc) => Layout::Unsupported(desc.clone()),
         }
-        IdlType::Defined(name) => decode_defined(idl, name, reader, depth + 1),
-        IdlType::Unsupported(desc) => Err(DecodeError::UnsupportedType(desc.clone())),
     }
 }
 
 /// Little-endian cursor over a borsh payload.
-struct Reader<'a> {
+pub(crate) struct Reader<'a> {
     data: &'a [u8],
     pos: usize,
 }
 
 macro_rules! read_le {
     ($name:ident, $ty:ty) => {
-        fn $name(&mut self) -> Result<$ty, DecodeError> {
+        pub(crate) fn $name(&mut self) -> Result<$ty, DecodeError> {
             let bytes = self.take(std::mem::size_of::<$ty>())?;
             Ok(<$ty>::from_le_bytes(bytes.try_into().unwrap()))
         }
@@ -281,15 +721,15 @@ macro_rules! read_le {
 }
 
 impl<'a> Reader<'a> {
-    fn new(data: &'a [u8]) -> Self {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
         Self { data, pos: 0 }
     }
 
-    fn remaining(&self) -> usize {
+    pub(crate) fn remaining(&self) -> usize {
         self.data.len() - self.pos
     }
 
-    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
         if len > self.remaining() {
             return Err(DecodeError::InvalidData(format!(
                 "payload truncated: wanted {} bytes at offset {}, {} remain",
@@ -305,7 +745,7 @@ impl<'a> Reader<'a> {
 
     /// Reads a borsh u32 length prefix, rejecting lengths that exceed the remaining
     /// payload so corrupt data cannot trigger runaway loops.
-    fn read_len(&mut self) -> Result<usize, DecodeError> {
+    pub(crate) fn read_len(&mut self) -> Result<usize, DecodeError> {
         let len = self.read_u32()? as usize;
         if len > self.remaining() {
             return Err(DecodeError::InvalidData(format!(
@@ -329,7 +769,7 @@ impl<'a> Reader<'a> {
     read_le!(read_f32, f32);
     read_le!(read_f64, f64);
 
-    fn read_u8(&mut self) -> Result<u8, DecodeError> {
+    pub(crate) fn read_u8(&mut self) -> Result<u8, DecodeError> {
         Ok(self.take(1)?[0])
     }
 }
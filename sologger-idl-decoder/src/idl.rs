@@ -1,14 +1,19 @@
+use std::io::Read;
+
+use flate2::read::ZlibDecoder;
 use serde::Deserialize;
 use serde_json::Value;
 
-use crate::decoder::{event_discriminator, DecodeError};
+use crate::decoder::{
+    account_discriminator, event_discriminator, instruction_discriminator, DecodeError,
+};
 
 /// A parsed Anchor IDL. One serde model covers both the legacy (pre-0.30) spec and the
 /// 0.30+ spec; the accessors below paper over the differences:
 ///
 /// - legacy keeps `name`/`version` at the top level, 0.30+ nests them under `metadata`
 /// - legacy events carry inline `fields`, 0.30+ events carry an explicit `discriminator`
-///   and define their fields as a struct of the same name in `types`
+///   and define their fields as a struct of the same name in `types`; likewise accounts
 #[derive(Deserialize, Clone, Debug, Default)]
 pub struct Idl {
     ///Program name (legacy spec top-level field)
@@ -23,6 +28,12 @@ pub struct Idl {
     ///Metadata block holding name/version/spec (0.30+ spec)
     #[serde(default)]
     pub metadata: Option<IdlMetadata>,
+    ///Instructions the program accepts
+    #[serde(default)]
+    pub instructions: Vec<IdlInstruction>,
+    ///Account types the program owns
+    #[serde(default)]
+    pub accounts: Vec<IdlAccount>,
     ///Events the program can emit
     #[serde(default)]
     pub events: Vec<IdlEvent>,
@@ -40,6 +51,11 @@ impl Idl {
         serde_json::from_str(json).map_err(|e| DecodeError::InvalidIdl(e.to_string()))
     }
 
+    /// Parses an IDL from the raw data of a program's on-chain Anchor IDL account.
+    pub fn from_account_data(data: &[u8]) -> Result<Self, DecodeError> {
+        Self::from_json(&inflate_idl_account(data)?)
+    }
+
     /// The program name, from whichever spec location holds it.
     pub fn program_name(&self) -> &str {
         self.name
@@ -48,6 +64,20 @@ impl Idl {
             .unwrap_or("")
     }
 
+    /// The program address, from whichever spec location holds it.
+    pub fn program_address(&self) -> Option<&str> {
+        self.address
+            .as_deref()
+            .or_else(|| self.metadata.as_ref().and_then(|m| m.address.as_deref()))
+    }
+
+    /// The IDL version, from whichever spec location holds it.
+    pub fn program_version(&self) -> Option<&str> {
+        self.version
+            .as_deref()
+            .or_else(|| self.metadata.as_ref().and_then(|m| m.version.as_deref()))
+    }
+
     /// Looks up a named type definition.
     pub fn find_type(&self, name: &str) -> Option<&IdlTypeDef> {
         self.types.iter().find(|t| t.name == name)
@@ -59,12 +89,48 @@ impl Idl {
     }
 }
 
-/// The `metadata` block of a 0.30+ spec IDL.
+/// Offset of the length prefix in an Anchor `IdlAccount`: 8-byte account discriminator
+/// followed by the 32-byte authority.
+const IDL_ACCOUNT_HEADER_LEN: usize = 8 + 32;
+
+/// Extracts the IDL JSON from the raw data of an on-chain Anchor IDL account. The account
+/// layout is `discriminator (8) | authority (32) | data_len (u32) | zlib(idl json)`; the
+/// account is usually over-allocated, so anything past `data_len` is ignored.
+pub fn inflate_idl_account(data: &[u8]) -> Result<String, DecodeError> {
+    let prefix_end = IDL_ACCOUNT_HEADER_LEN + 4;
+    if data.len() < prefix_end {
+        return Err(DecodeError::InvalidIdl(format!(
+            "IDL account data is {} bytes, shorter than its {} byte header",
+            data.len(),
+            prefix_end
+        )));
+    }
+    let len =
+        u32::from_le_bytes(data[IDL_ACCOUNT_HEADER_LEN..prefix_end].try_into().unwrap()) as usize;
+    let compressed = data.get(prefix_end..prefix_end + len).ok_or_else(|| {
+        DecodeError::InvalidIdl(format!(
+            "IDL account length prefix {} exceeds account data {}",
+            len,
+            data.len() - prefix_end
+        ))
+    })?;
+
+    let mut json = String::new();
+    ZlibDecoder::new(compressed)
+        .read_to_string(&mut json)
+        .map_err(|e| DecodeError::InvalidIdl(format!("failed to inflate IDL account: {}", e)))?;
+    Ok(json)
+}
+
+/// The `metadata` block of a 0.30+ spec IDL. Legacy IDLs emitted by `anchor build` may
+/// also carry one, holding just the deployed `address`.
 #[derive(Deserialize, Clone, Debug, Default)]
 pub struct IdlMetadata {
     #[serde(default)]
     pub name: Option<String>,
     #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
     pub version: Option<String>,
     #[serde(default)]
     pub spec: Option<String>,
@@ -82,17 +148,58 @@ pub struct IdlEvent {
 }
 
 impl IdlEvent {
-    /// The 8-byte discriminator that prefixes this event's borsh payload: the explicit
-    /// one when the IDL provides it (0.30+), otherwise sha256("event:<Name>")[..8]
-    /// exactly as anchor-lang derives it for legacy programs.
-    pub fn discriminator_bytes(&self) -> [u8; 8] {
+    /// The discriminator that prefixes this event's borsh payload: the explicit one when
+    /// the IDL provides it (0.30+, where Anchor 0.31 custom discriminators may be any
+    /// length), otherwise sha256("event:<Name>")[..8] exactly as anchor-lang derives it
+    /// for legacy programs.
+    pub fn discriminator_bytes(&self) -> Vec<u8> {
         match &self.discriminator {
-            Some(explicit) if explicit.len() == 8 => {
-                let mut out = [0u8; 8];
-                out.copy_from_slice(explicit);
-                out
-            }
-            _ => event_discriminator(&self.name),
+            Some(explicit) if !explicit.is_empty() => explicit.clone(),
+            _ => event_discriminator(&self.name).to_vec(),
+        }
+    }
+}
+
+/// An instruction definition. Only what identifies and decodes its data is kept: the
+/// account metas are not modelled.
+#[derive(Deserialize, Clone, Debug)]
+pub struct IdlInstruction {
+    pub name: String,
+    #[serde(default)]
+    pub discriminator: Option<Vec<u8>>,
+    #[serde(default)]
+    pub args: Vec<IdlField>,
+}
+
+impl IdlInstruction {
+    /// The discriminator that prefixes this instruction's data: the explicit one when
+    /// the IDL provides it, otherwise sha256("global:<snake_case_name>")[..8].
+    pub fn discriminator_bytes(&self) -> Vec<u8> {
+        match &self.discriminator {
+            Some(explicit) if !explicit.is_empty() => explicit.clone(),
+            _ => instruction_discriminator(&self.name).to_vec(),
+        }
+    }
+}
+
+/// An account definition. Legacy IDLs inline the layout as `type`; 0.30+ IDLs carry an
+/// explicit discriminator and define the layout as a struct of the same name in `types`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct IdlAccount {
+    pub name: String,
+    #[serde(default)]
+    pub discriminator: Option<Vec<u8>>,
+    #[serde(default, rename = "type")]
+    pub ty: Option<IdlTypeDefTy>,
+}
+
+impl IdlAccount {
+    /// The discriminator that prefixes this account's data: the explicit one when the IDL
+    /// provides it, otherwise sha256("account:<Name>")[..8].
+    pub fn discriminator_bytes(&self) -> Vec<u8> {
+        match &self.discriminator {
+            Some(explicit) if !explicit.is_empty() => explicit.clone(),
+            _ => account_discriminator(&self.name).to_vec(),
         }
     }
 }
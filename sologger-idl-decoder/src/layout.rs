@@ -0,0 +1,135 @@
+//! User-defined borsh layouts for programs without an Anchor IDL, such as in-house native
+//! programs that emit `Program data:` payloads of their own design. A layout declares
+//! each event's discriminator bytes (any length, e.g. a one-byte tag) and its fields,
+//! using the same type spellings as IDL fields. It compiles to an [`Idl`], so layouts
+//! decode through the same [`IdlDecoder`](crate::IdlDecoder) and registry as IDLs.
+//!
+//! ```toml
+//! name = "vault"
+//! version = "1.2.0"
+//!
+//! [[events]]
+//! name = "Deposit"
+//! discriminator = [1]
+//! fields = [
+//!   { name = "owner", type = "pubkey" },
+//!   { name = "amount", type = "u64" },
+//!   { name = "memo", type = { option = "string" } },
+//!   { name = "splits", type = { vec = "u16" } },
+//! ]
+//!
+//! [[errors]]
+//! code = 1
+//! name = "VaultLocked"
+//! ```
+//!
+//! The JSON form has the same shape.
+
+use serde::Deserialize;
+
+use crate::decoder::DecodeError;
+use crate::idl::{Idl, IdlErrorCode, IdlEvent, IdlField, IdlType};
+
+/// A parsed layout file. Build with [`LayoutSchema::from_json`] or
+/// [`LayoutSchema::from_toml`], which also validate it.
+#[derive(Deserialize, Clone, Debug)]
+pub struct LayoutSchema {
+    ///Reported as the program name
+    pub name: String,
+    ///Reported in `LogContext::idl_version`
+    #[serde(default)]
+    pub version: Option<String>,
+    pub events: Vec<LayoutEvent>,
+    ///Custom error codes, resolved into `error_name` like an IDL's
+    #[serde(default)]
+    pub errors: Vec<IdlErrorCode>,
+}
+
+/// One event: the payload is `discriminator` followed by the borsh-encoded `fields`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct LayoutEvent {
+    pub name: String,
+    pub discriminator: Vec<u8>,
+    #[serde(default)]
+    pub fields: Vec<IdlField>,
+}
+
+impl LayoutSchema {
+    /// Parses and validates a layout from JSON.
+    pub fn from_json(json: &str) -> Result<Self, DecodeError> {
+        let layout: Self =
+            serde_json::from_str(json).map_err(|e| DecodeError::InvalidIdl(e.to_string()))?;
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    /// Parses and validates a layout from TOML.
+    pub fn from_toml(toml: &str) -> Result<Self, DecodeError> {
+        let layout: Self =
+            toml::from_str(toml).map_err(|e| DecodeError::InvalidIdl(e.to_string()))?;
+        layout.validate()?;
+        Ok(layout)
+    }
+
+    /// Layouts are written by hand, so mistakes fail when the layout is loaded rather
+    /// than on the first payload, as an IDL's unsupported fields would.
+    fn validate(&self) -> Result<(), DecodeError> {
+        for (index, event) in self.events.iter().enumerate() {
+            if event.discriminator.is_empty() {
+                return Err(DecodeError::InvalidIdl(format!(
+                    "layout event {} has an empty discriminator",
+                    event.name
+                )));
+            }
+            let duplicate = self.events[..index]
+                .iter()
+                .find(|other| other.discriminator == event.discriminator);
+            if let Some(other) = duplicate {
+                return Err(DecodeError::InvalidIdl(format!(
+                    "layout events {} and {} share a discriminator",
+                    other.name, event.name
+                )));
+            }
+            for field in &event.fields {
+                check_type(&field.ty).map_err(|ty| {
+                    DecodeError::UnsupportedType(format!(
+                        "{} in field {}.{} (layouts support primitives, string, bytes, pubkey, vec, option and array)",
+                        ty, event.name, field.name
+                    ))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The equivalent IDL: legacy-style inline event fields with explicit discriminators.
+    pub fn into_idl(self) -> Idl {
+        Idl {
+            name: Some(self.name),
+            version: self.version,
+            events: self
+                .events
+                .into_iter()
+                .map(|event| IdlEvent {
+                    name: event.name,
+                    discriminator: Some(event.discriminator),
+                    fields: Some(event.fields),
+                })
+                .collect(),
+            errors: self.errors,
+            ..Idl::default()
+        }
+    }
+}
+
+/// Layouts have no `types` section, so `defined` references can never resolve.
+fn check_type(ty: &IdlType) -> Result<(), String> {
+    match ty {
+        IdlType::Vec(inner) | IdlType::Option(inner) | IdlType::Array(inner, _) => {
+            check_type(inner)
+        }
+        IdlType::Defined(name) => Err(format!("defined type {}", name)),
+        IdlType::Unsupported(desc) => Err(format!("type {}", desc)),
+        _ => Ok(()),
+    }
+}
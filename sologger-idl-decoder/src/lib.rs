@@ -17,7 +17,7 @@
 //!    registry.enrich_all(&mut log_contexts);
 //!
 //!    // Each matching context now carries:
-//!    //   decoded_events: [r#"{"name":"SwapEvent","data":{...}}"#]
+//!    //   decoded_events: [DecodedLogEvent { name: "SwapEvent", data: {...}, .. }]
 //!    //   error_name:     Some("NotApproved")   // when error_code matched the IDL
 //!```
 //!
@@ -27,15 +27,49 @@
 //!    let idl = Idl::from_json(idl_json)?;
 //!    let event = decode_event(&idl, base64_payload)?; // Option<DecodedEvent>
 //!    let error = idl.lookup_error(6001);              // Option<&IdlErrorCode>
+//!
+//!    // Decoding many payloads: compile once, then one hash lookup per payload
+//!    let decoder = IdlDecoder::new(&idl);
+//!    let event = decoder.decode_event(base64_payload)?;
+//!
+//!    // Account data, matched against the IDL's `accounts` discriminators
+//!    let account = decoder.decode_account(&account_data)?; // Option<DecodedAccount>
+//!```
+//!
+//!Programs without an Anchor IDL (e.g. native programs) can declare their events in a
+//!small layout file instead:
+//!
+//!```rust
+//!    let layout = LayoutSchema::from_toml(layout_toml)?; // or from_json
+//!    registry.insert_layout("Vau1t...", layout);
+//!```
+//!
+//!Table shapes for downstream storage, matching what the decoder produces:
+//!
+//!```rust
+//!    let json_schema = events_json_schema(&idl);                          // serde_json::Value
+//!    let ddl = events_ddl(&idl, SqlDialect::ClickHouse, "raydium_");      // CREATE TABLE ...
 //!```
 
 pub mod decoder;
 pub mod idl;
+pub mod layout;
+pub mod lint;
+pub mod native;
 pub mod registry;
+pub mod schema;
 
-pub use decoder::{decode_event, decode_events, event_discriminator, DecodeError, DecodedEvent};
-pub use idl::{Idl, IdlErrorCode, IdlEvent};
-pub use registry::IdlRegistry;
+pub use decoder::{
+    account_discriminator, decode_event, decode_events, event_discriminator, hex,
+    instruction_discriminator, DecodeError, DecodeFailure, DecodeMode, DecodedAccount,
+    DecodedEvent, IdlDecoder,
+};
+pub use idl::{inflate_idl_account, Idl, IdlAccount, IdlErrorCode, IdlEvent, IdlInstruction};
+pub use layout::{LayoutEvent, LayoutSchema};
+pub use lint::{lint_idl, IdlLint};
+pub use native::{decode_native_instruction, DecodedInstruction};
+pub use registry::{IdlRegistry, VersionedIdl};
+pub use schema::{event_metadata, events_ddl, events_json_schema, SqlDialect};
 
 #[cfg(test)]
 mod tests {
@@ -45,9 +79,15 @@ mod tests {
     use sologger_log_context::programs_selector::ProgramsSelector;
     use sologger_log_context::sologger_log_context::LogContext;
 
-    use crate::decoder::{decode_event, decode_events, event_discriminator};
+    use crate::decoder::{
+        account_discriminator, decode_event, decode_events, event_discriminator,
+        instruction_discriminator, DecodeError, DecodeMode, IdlDecoder,
+    };
     use crate::idl::{Idl, IdlType};
-    use crate::registry::IdlRegistry;
+    use crate::layout::LayoutSchema;
+    use crate::lint::lint_idl;
+    use crate::registry::{IdlRegistry, VersionedIdl};
+    use crate::schema::{event_metadata, events_ddl, events_json_schema, SqlDialect};
 
     /// Real 0.30+ spec IDL (Raydium CP-AMM), with explicit event discriminators.
     const RAYDIUM_IDL: &str = include_str!("../tests/fixtures/raydium_cp_swap_idl.json");
@@ -55,6 +95,11 @@ mod tests {
     /// "publicKey" spelling, {"defined": "Name"} type references.
     const LEGACY_IDL: &str = include_str!("../tests/fixtures/legacy_anchor_idl.json");
 
+    /// Raw data of an on-chain Anchor IDL account holding LEGACY_IDL, base64-encoded as
+    /// getAccountInfo returns it.
+    const LEGACY_IDL_ACCOUNT: &str =
+        include_str!("../tests/fixtures/legacy_anchor_idl_account.b64");
+
     const RAYDIUM_PROGRAM_ID: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
 
     fn push_pubkey(buf: &mut Vec<u8>, byte: u8) -> String {
@@ -101,6 +146,117 @@ mod tests {
         );
     }
 
+    #[test]
+    fn instruction_and_account_discriminators_match_anchor() {
+        // Every explicit discriminator in the real Raydium IDL equals the derived one
+        let idl = Idl::from_json(RAYDIUM_IDL).unwrap();
+        assert!(!idl.instructions.is_empty());
+        for instruction in &idl.instructions {
+            assert_eq!(
+                instruction.discriminator_bytes(),
+                instruction_discriminator(&instruction.name).to_vec(),
+                "{}",
+                instruction.name
+            );
+        }
+        assert!(!idl.accounts.is_empty());
+        for account in &idl.accounts {
+            assert_eq!(
+                account.discriminator_bytes(),
+                account_discriminator(&account.name).to_vec(),
+                "{}",
+                account.name
+            );
+        }
+        // Legacy camelCase instruction names hash as snake_case
+        assert_eq!(
+            instruction_discriminator("closePermissionPda"),
+            instruction_discriminator("close_permission_pda")
+        );
+    }
+
+    #[test]
+    fn decodes_account_data() {
+        let decoder = IdlDecoder::new(&Idl::from_json(RAYDIUM_IDL).unwrap());
+
+        // AmmConfig: bump, disable_create_pool, index, 4 fee u64s, 2 pubkeys, a fee u64,
+        // [u64; 15] padding, then spare allocated room
+        let mut data = account_discriminator("AmmConfig").to_vec();
+        data.extend_from_slice(&[254, 0]);
+        data.extend_from_slice(&3u16.to_le_bytes());
+        for fee in [2500u64, 120000, 40000, 150000000] {
+            data.extend_from_slice(&fee.to_le_bytes());
+        }
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&[0; 8 * 15]);
+        data.extend_from_slice(&[0; 16]);
+
+        let account = decoder.decode_account(&data).unwrap().unwrap();
+        assert_eq!(account.name, "AmmConfig");
+        assert_eq!(account.discriminator, account_discriminator("AmmConfig").to_vec());
+        assert_eq!(account.data["bump"], 254);
+        assert_eq!(account.data["index"], 3);
+        assert_eq!(account.data["trade_fee_rate"], 2500);
+        assert_eq!(
+            account.data["protocol_owner"],
+            "11111111111111111111111111111111"
+        );
+
+        // Unknown discriminator, then a truncated AmmConfig
+        assert!(decoder.decode_account(&[9; 16]).unwrap().is_none());
+        assert!(decoder.decode_account(&data[..20]).is_err());
+
+        // Legacy spec: the layout is inline on the account
+        let legacy = Idl::from_json(
+            r#"{"name": "vault", "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [{"name": "balance", "type": "u64"}]}}]}"#,
+        )
+        .unwrap();
+        let mut data = account_discriminator("Vault").to_vec();
+        data.extend_from_slice(&42u64.to_le_bytes());
+        let account = IdlDecoder::new(&legacy).decode_account(&data).unwrap().unwrap();
+        assert_eq!(account.data, json!({"balance": 42}));
+    }
+
+    #[test]
+    fn lints_idls() {
+        assert!(lint_idl(&Idl::from_json(RAYDIUM_IDL).unwrap()).is_empty());
+        assert!(lint_idl(&Idl::from_json(LEGACY_IDL).unwrap()).is_empty());
+
+        let broken = Idl::from_json(
+            r#"{
+                "name": "broken",
+                "instructions": [
+                    {"name": "a", "discriminator": [1], "args": [{"name": "x", "type": {"defined": "Missing"}}]},
+                    {"name": "b", "discriminator": [1], "args": []}
+                ],
+                "events": [
+                    {"name": "Ghost", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]},
+                    {"name": "Wide", "fields": [{"name": "v", "type": "u256"}]}
+                ],
+                "errors": [{"code": 6000, "name": "A"}, {"code": 6000, "name": "B"}],
+                "types": [
+                    {"name": "T", "type": {"kind": "enum", "variants": [{"name": "V", "fields": [{"option": {"defined": "Nope"}}]}]}},
+                    {"name": "T", "type": {"kind": "struct", "fields": []}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let lints: Vec<String> = lint_idl(&broken).iter().map(|lint| lint.to_string()).collect();
+        assert_eq!(
+            lints,
+            vec![
+                "instructions a, b share discriminator 01",
+                "errors A, B share code 6000",
+                "type T is defined twice",
+                "event Ghost: type Ghost is not defined in types",
+                "event Wide.v: unsupported type u256",
+                "instruction a.x: type Missing is not defined in types",
+                "type T::V.0: type Nope is not defined in types",
+            ]
+        );
+    }
+
     #[test]
     fn parses_both_idl_specs() {
         let raydium = Idl::from_json(RAYDIUM_IDL).unwrap();
@@ -119,6 +275,13 @@ mod tests {
             Some("The trade is invalid")
         );
         assert!(legacy.lookup_error(42).is_none());
+
+        assert_eq!(raydium.program_address(), Some(RAYDIUM_PROGRAM_ID));
+        assert_eq!(raydium.program_version(), Some("0.2.0"));
+        assert_eq!(legacy.program_address(), None);
+        let deployed =
+            Idl::from_json(r#"{"name":"demo","metadata":{"address":"Demo1111"}}"#).unwrap();
+        assert_eq!(deployed.program_address(), Some("Demo1111"));
     }
 
     #[test]
@@ -194,6 +357,46 @@ mod tests {
         assert_eq!(event.data["tags"], json!([1, 2, 3, 4]));
     }
 
+    #[test]
+    fn precompiled_decoder_resolves_types() {
+        let decoder = IdlDecoder::new(&Idl::from_json(LEGACY_IDL).unwrap());
+        let (payload, trader) = encode_trade_event();
+        for _ in 0..2 {
+            let event = decoder.decode_event(&payload).unwrap().unwrap();
+            assert_eq!(event.data["trader"], json!(trader));
+            assert_eq!(event.data["side"], json!("Sell"));
+        }
+
+        // Self-referential types resolve; a dangling reference compiles and only fails
+        // for payloads that reach it
+        let idl = Idl::from_json(
+            r#"{
+                "events": [
+                    {"name": "Chain", "fields": [{"name": "head", "type": {"defined": "Node"}}]},
+                    {"name": "Dangling", "fields": [{"name": "x", "type": {"defined": "Missing"}}]}
+                ],
+                "types": [{"name": "Node", "type": {"kind": "struct", "fields": [
+                    {"name": "value", "type": "u8"},
+                    {"name": "next", "type": {"option": {"defined": "Node"}}}
+                ]}}]
+            }"#,
+        )
+        .unwrap();
+        let decoder = IdlDecoder::new(&idl);
+        let mut buf = event_discriminator("Chain").to_vec();
+        buf.extend_from_slice(&[1, 1, 2, 0]);
+        let event = decoder.decode_event(&BASE64.encode(&buf)).unwrap().unwrap();
+        assert_eq!(
+            event.data,
+            json!({"head": {"value": 1, "next": {"value": 2, "next": null}}})
+        );
+        let dangling = BASE64.encode(event_discriminator("Dangling"));
+        assert!(matches!(
+            decoder.decode_event(&dangling),
+            Err(crate::decoder::DecodeError::UnknownType(name)) if name == "Missing"
+        ));
+    }
+
     #[test]
     fn unknown_discriminator_returns_none() {
         let idl = Idl::from_json(RAYDIUM_IDL).unwrap();
@@ -269,19 +472,124 @@ mod tests {
 
         assert_eq!(log_contexts.len(), 1);
         assert_eq!(log_contexts[0].decoded_events.len(), 1);
-        let event: serde_json::Value =
-            serde_json::from_str(&log_contexts[0].decoded_events[0]).unwrap();
-        assert_eq!(event["name"], json!("SwapEvent"));
-        assert_eq!(event["data"]["input_amount"], json!(1_000_000u64));
+        let event = &log_contexts[0].decoded_events[0];
+        assert_eq!(event.name, "SwapEvent");
+        assert_eq!(event.program, RAYDIUM_PROGRAM_ID);
+        assert_eq!(event.discriminator, "40c6cde8260871e2");
+        assert_eq!(event.data_log_index, 0);
+        assert_eq!(event.data["input_amount"], json!(1_000_000u64));
         assert_eq!(log_contexts[0].error_code, Some(6000));
         assert_eq!(log_contexts[0].error_name.as_deref(), Some("NotApproved"));
 
         // The enriched context serializes with the new fields present
         let json = log_contexts[0].to_json();
-        assert!(json.contains("\"decoded_events\""));
+        assert!(json.contains("\"decoded_events\":[{\"name\":\"SwapEvent\""));
         assert!(json.contains("\"error_name\":\"NotApproved\""));
     }
 
+    #[test]
+    fn registry_selects_idl_by_slot() {
+        let mut registry = IdlRegistry::new();
+        // Before the upgrade the program ran a layout without SwapEvent
+        registry.insert_versioned(
+            RAYDIUM_PROGRAM_ID,
+            VersionedIdl {
+                idl: Idl::from_json(LEGACY_IDL).unwrap(),
+                valid_from_slot: None,
+                valid_until_slot: Some(999),
+                label: Some("v1".to_string()),
+            },
+        );
+        registry.insert_versioned(
+            RAYDIUM_PROGRAM_ID,
+            VersionedIdl {
+                valid_from_slot: Some(1000),
+                ..VersionedIdl::new(Idl::from_json(RAYDIUM_IDL).unwrap())
+            },
+        );
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.versions(RAYDIUM_PROGRAM_ID).len(), 2);
+        assert_eq!(registry.get(RAYDIUM_PROGRAM_ID).unwrap().program_name(), "raydium_cp_swap");
+        assert_eq!(
+            registry.get_for_slot(RAYDIUM_PROGRAM_ID, 10).unwrap().version(),
+            "v1"
+        );
+
+        let (payload, _) = encode_swap_event();
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", RAYDIUM_PROGRAM_ID),
+            format!("Program data: {}", payload),
+            format!("Program {} success", RAYDIUM_PROGRAM_ID),
+        ];
+        let parse_at = |slot| {
+            LogContext::parse_logs(
+                &logs,
+                "".to_string(),
+                &ProgramsSelector::new_all_programs(),
+                slot,
+                "sig".to_string(),
+            )
+        };
+
+        let mut upgraded = parse_at(1500);
+        registry.enrich_all(&mut upgraded);
+        assert_eq!(upgraded[0].decoded_events.len(), 1);
+        assert_eq!(upgraded[0].idl_version.as_deref(), Some("0.2.0"));
+
+        // The pre-upgrade IDL doesn't know the SwapEvent discriminator
+        let mut old = parse_at(500);
+        registry.enrich_all(&mut old);
+        assert!(old[0].decoded_events.is_empty());
+        assert!(old[0].idl_version.is_none());
+
+        // Inserting without a range replaces the history
+        registry.insert_json(RAYDIUM_PROGRAM_ID, RAYDIUM_IDL).unwrap();
+        assert_eq!(registry.versions(RAYDIUM_PROGRAM_ID).len(), 1);
+        assert!(registry.get_for_slot(RAYDIUM_PROGRAM_ID, 500).is_some());
+    }
+
+    #[test]
+    fn registry_decodes_legacy_log_message_events() {
+        let mut registry = IdlRegistry::new();
+        registry
+            .insert_json(RAYDIUM_PROGRAM_ID, LEGACY_IDL)
+            .unwrap();
+
+        // Anchor before 0.24: emit! wrote the event as a 'Program log:' base64 message
+        let (payload, trader) = encode_trade_event();
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", RAYDIUM_PROGRAM_ID),
+            "Program log: Instruction: Trade".to_string(),
+            "Program log: AAAAAAAAAAAAAAAA".to_string(),
+            format!("Program log: {}", payload),
+            format!("Program {} success", RAYDIUM_PROGRAM_ID),
+        ];
+        let parse = || {
+            LogContext::parse_logs(
+                &logs,
+                "".to_string(),
+                &ProgramsSelector::new_all_programs(),
+                1,
+                "sig".to_string(),
+            )
+        };
+
+        let mut log_contexts = parse();
+        registry.enrich_all(&mut log_contexts);
+        assert!(log_contexts[0].decoded_events.is_empty());
+
+        registry.set_log_message_events(true);
+        let mut log_contexts = parse();
+        registry.enrich_all(&mut log_contexts);
+        assert_eq!(log_contexts[0].decoded_events.len(), 1);
+        let event = &log_contexts[0].decoded_events[0];
+        assert_eq!(event.name, "TradeEvent");
+        assert_eq!(event.data["trader"], json!(trader));
+        assert!(event.from_log_message);
+        assert_eq!(event.data_log_index, 2);
+        assert_eq!(log_contexts[0].idl_version.as_deref(), Some("0.1.0"));
+    }
+
     #[test]
     fn registry_skips_unregistered_programs() {
         let mut registry = IdlRegistry::new();
@@ -308,10 +616,476 @@ mod tests {
         assert!(log_contexts[0].error_name.is_none());
     }
 
+    #[test]
+    fn parses_idl_from_account_data() {
+        let account_data = BASE64
+            .decode(LEGACY_IDL_ACCOUNT.trim())
+            .expect("fixture is base64");
+
+        let idl = Idl::from_account_data(&account_data).unwrap();
+        assert_eq!(idl.program_name(), "legacy_demo");
+        assert_eq!(
+            crate::idl::inflate_idl_account(&account_data).unwrap(),
+            LEGACY_IDL
+        );
+
+        let mut registry = IdlRegistry::new();
+        registry
+            .insert_account_data(RAYDIUM_PROGRAM_ID, &account_data)
+            .unwrap();
+        assert_eq!(registry.len(), 1);
+
+        // Header only, a length prefix past the end, and a non-zlib payload all fail
+        assert!(Idl::from_account_data(&account_data[..40]).is_err());
+        let mut overlong = account_data[..44].to_vec();
+        overlong[40..44].copy_from_slice(&1_000u32.to_le_bytes());
+        assert!(Idl::from_account_data(&overlong).is_err());
+        let mut garbage = account_data[..44].to_vec();
+        garbage[40..44].copy_from_slice(&4u32.to_le_bytes());
+        garbage.extend_from_slice(b"nope");
+        assert!(Idl::from_account_data(&garbage).is_err());
+    }
+
     #[test]
     fn registry_rejects_invalid_idl_json() {
         let mut registry = IdlRegistry::new();
         assert!(registry.insert_json("SomeProgram", "{ not json").is_err());
         assert!(registry.is_empty());
     }
+
+    #[test]
+    fn decodes_native_instructions() {
+        use crate::native::*;
+
+        let accounts: Vec<String> = ["Src1", "Dst1", "Auth1", "Extra"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        // System transfer: u32 tag 2, u64 lamports
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&5_000u64.to_le_bytes());
+        let ix = decode_native_instruction(SYSTEM_PROGRAM_ID, &data, &accounts)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ix.program, "system");
+        assert_eq!(ix.name, "transfer");
+        assert_eq!(
+            ix.args,
+            json!({"source": "Src1", "destination": "Dst1", "lamports": 5_000})
+        );
+
+        // SPL Token transferChecked: u8 tag 12, u64 amount, u8 decimals
+        let mut data = vec![12u8];
+        data.extend_from_slice(&1_500_000u64.to_le_bytes());
+        data.push(6);
+        let ix = decode_native_instruction(TOKEN_2022_PROGRAM_ID, &data, &accounts)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ix.program, "spl-token-2022");
+        assert_eq!(ix.name, "transferChecked");
+        assert_eq!(ix.args["mint"], json!("Dst1"));
+        assert_eq!(ix.args["authority"], json!("Extra"));
+        assert_eq!(ix.args["amount"], json!(1_500_000u64));
+        assert_eq!(ix.args["decimals"], json!(6));
+
+        // Token-2022 extension tags are named but not decoded; plain SPL Token doesn't know them
+        let ix = decode_native_instruction(TOKEN_2022_PROGRAM_ID, &[26, 1], &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(ix.name, "transferFeeExtension");
+        assert!(decode_native_instruction(TOKEN_PROGRAM_ID, &[26, 1], &[])
+            .unwrap()
+            .is_none());
+
+        // ATA create has empty instruction data
+        let ix = decode_native_instruction(ASSOCIATED_TOKEN_PROGRAM_ID, &[], &accounts)
+            .unwrap()
+            .unwrap();
+        assert_eq!(ix.name, "create");
+        assert_eq!(ix.args["wallet"], json!("Auth1"));
+
+        let ix = decode_native_instruction(MEMO_PROGRAM_ID, b"gm", &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(ix.to_json(), r#"{"args":{"memo":"gm"},"name":"memo","program":"spl-memo"}"#);
+
+        let mut data = vec![3u8];
+        data.extend_from_slice(&25_000u64.to_le_bytes());
+        let ix = decode_native_instruction(COMPUTE_BUDGET_PROGRAM_ID, &data, &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(ix.name, "setComputeUnitPrice");
+        assert_eq!(ix.args, json!({"microLamports": 25_000}));
+
+        // Truncated payloads error; unknown programs and tags are skipped
+        assert!(decode_native_instruction(SYSTEM_PROGRAM_ID, &[2, 0, 0, 0, 1], &[]).is_err());
+        assert!(decode_native_instruction(RAYDIUM_PROGRAM_ID, &[2], &[])
+            .unwrap()
+            .is_none());
+        assert!(decode_native_instruction(COMPUTE_BUDGET_PROGRAM_ID, &[9], &[])
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn registry_decodes_native_instructions_without_idls() {
+        let logs: Vec<String> = vec![
+            "Program ComputeBudget111111111111111111111111111111 invoke [1]".to_string(),
+            "Program ComputeBudget111111111111111111111111111111 success".to_string(),
+        ];
+        let mut log_contexts = LogContext::parse_logs(
+            &logs,
+            "".to_string(),
+            &ProgramsSelector::new_all_programs(),
+            1,
+            "sig".to_string(),
+        );
+        let mut data = vec![2u8];
+        data.extend_from_slice(&200_000u32.to_le_bytes());
+        log_contexts[0].instruction_data = Some(bs58::encode(&data).into_string());
+
+        let mut registry = IdlRegistry::new();
+        registry.set_native_decoders(false);
+        registry.enrich_all(&mut log_contexts);
+        assert!(log_contexts[0].decoded_instruction.is_none());
+
+        let registry = IdlRegistry::new();
+        assert!(registry.is_empty());
+        registry.enrich_all(&mut log_contexts);
+        let instruction: serde_json::Value =
+            serde_json::from_str(log_contexts[0].decoded_instruction.as_ref().unwrap()).unwrap();
+        assert_eq!(instruction["name"], json!("setComputeUnitLimit"));
+        assert_eq!(instruction["args"]["units"], json!(200_000));
+    }
+
+    #[test]
+    fn exports_event_schemas() {
+        let idl = Idl::from_json(LEGACY_IDL).unwrap();
+        let schema = events_json_schema(&idl);
+        let trade = &schema["oneOf"][0]["properties"];
+        assert_eq!(trade["name"]["const"], json!("TradeEvent"));
+        let fields = &trade["data"]["properties"];
+        // Mirrors the decoder's rendering: u128 as string, pubkey as base58
+        assert_eq!(fields["price"]["type"], json!("string"));
+        assert_eq!(fields["trader"]["type"], json!("string"));
+        assert_eq!(fields["amount"]["maximum"], json!(u64::MAX));
+        assert_eq!(fields["referrer"]["anyOf"][0]["type"], json!("null"));
+        assert_eq!(fields["tags"]["maxItems"], json!(4));
+        assert_eq!(fields["legs"]["items"]["$ref"], json!("#/definitions/Leg"));
+        assert_eq!(
+            schema["definitions"]["Side"]["oneOf"][0]["enum"],
+            json!(["Buy", "Sell"])
+        );
+
+        // The decoded event validates structurally against its schema
+        let raydium = Idl::from_json(RAYDIUM_IDL).unwrap();
+        let schema = events_json_schema(&raydium);
+        let (payload, _) = encode_swap_event();
+        let event = decode_event(&raydium, &payload).unwrap().unwrap();
+        let swap = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|event_schema| event_schema["properties"]["name"]["const"] == "SwapEvent")
+            .unwrap();
+        assert_eq!(
+            swap["properties"]["data"]["$ref"],
+            json!("#/definitions/SwapEvent")
+        );
+        let required = schema["definitions"]["SwapEvent"]["required"]
+            .as_array()
+            .unwrap();
+        for field in required {
+            assert!(event.data.get(field.as_str().unwrap()).is_some());
+        }
+
+        let clickhouse = events_ddl(&idl, SqlDialect::ClickHouse, "demo_");
+        assert!(clickhouse.contains("CREATE TABLE IF NOT EXISTS `demo_trade_event`"));
+        assert!(clickhouse.contains("`price` UInt128"));
+        assert!(clickhouse.contains("`referrer` Nullable(String)"));
+        assert!(clickhouse.contains("`tags` Array(UInt8)"));
+        // Unit-only enums are their variant name, nested structs JSON
+        assert!(clickhouse.contains("`side` String"));
+        assert!(clickhouse.contains("`legs` String"));
+        assert!(clickhouse.contains(
+            "ORDER BY (_slot, _signature, _invocation_index, _from_log_message, _data_log_index)"
+        ));
+
+        let postgres = events_ddl(&raydium, SqlDialect::Postgres, "");
+        assert!(postgres.contains("CREATE TABLE IF NOT EXISTS \"swap_event\""));
+        assert!(postgres.contains("CREATE TABLE IF NOT EXISTS \"lp_change_event\""));
+        assert!(postgres.contains("\"input_amount\" NUMERIC(20, 0) NOT NULL"));
+        assert!(postgres.contains("\"base_input\" BOOLEAN NOT NULL"));
+        assert!(postgres.contains("\"_invocation_index\" INTEGER NOT NULL"));
+        assert!(postgres.contains(
+            "PRIMARY KEY (_signature, _invocation_index, _from_log_message, _data_log_index)"
+        ));
+    }
+
+    #[test]
+    fn event_rows_are_keyed_per_invocation() {
+        let mut registry = IdlRegistry::new();
+        registry
+            .insert_json(RAYDIUM_PROGRAM_ID, RAYDIUM_IDL)
+            .unwrap();
+
+        // Two top-level instructions of one transaction, each emitting its first event
+        let (payload, _) = encode_swap_event();
+        let mut logs = Vec::new();
+        for _ in 0..2 {
+            logs.push(format!("Program {} invoke [1]", RAYDIUM_PROGRAM_ID));
+            logs.push(format!("Program data: {}", payload));
+            logs.push(format!("Program {} success", RAYDIUM_PROGRAM_ID));
+        }
+        let mut log_contexts = LogContext::parse_logs(
+            &logs,
+            "".to_string(),
+            &ProgramsSelector::new_all_programs(),
+            1,
+            "sig".to_string(),
+        );
+        registry.enrich_all(&mut log_contexts);
+        assert_eq!(log_contexts.len(), 2);
+
+        let key_columns = [
+            "_signature",
+            "_invocation_index",
+            "_from_log_message",
+            "_data_log_index",
+        ];
+        let keys: Vec<Vec<serde_json::Value>> = log_contexts
+            .iter()
+            .map(|log_context| {
+                assert_eq!(log_context.decoded_events.len(), 1);
+                let event = &log_context.decoded_events[0];
+                assert_eq!(event.data_log_index, 0);
+                let metadata = event_metadata(log_context, event);
+                key_columns.iter().map(|column| metadata[*column].clone()).collect()
+            })
+            .collect();
+        assert_eq!(
+            keys[0],
+            vec![json!("sig"), json!(0), json!(false), json!(0)]
+        );
+        assert_eq!(
+            keys[1],
+            vec![json!("sig"), json!(1), json!(false), json!(0)]
+        );
+
+        let metadata = event_metadata(&log_contexts[1], &log_contexts[1].decoded_events[0]);
+        assert_eq!(metadata["_instruction_index"], json!(1));
+        assert_eq!(metadata["_depth"], json!(1));
+        assert_eq!(metadata["_program_id"], json!(RAYDIUM_PROGRAM_ID));
+    }
+
+    #[test]
+    fn registry_decodes_user_defined_layouts() {
+        const VAULT_PROGRAM_ID: &str = "Vau1t11111111111111111111111111111111111111";
+        let layout = LayoutSchema::from_toml(
+            r#"
+            name = "vault"
+            version = "1.2.0"
+
+            [[events]]
+            name = "Deposit"
+            discriminator = [1]
+            fields = [
+              { name = "owner", type = "pubkey" },
+              { name = "amount", type = "u64" },
+              { name = "memo", type = { option = "string" } },
+              { name = "splits", type = { vec = "u16" } },
+            ]
+
+            [[events]]
+            name = "Withdraw"
+            discriminator = [2, 0]
+            fields = [{ name = "amount", type = "u64" }]
+
+            [[errors]]
+            code = 1
+            name = "VaultLocked"
+            "#,
+        )
+        .unwrap();
+        let mut registry = IdlRegistry::new();
+        registry.insert_layout(VAULT_PROGRAM_ID, layout);
+
+        let owner = [7u8; 32];
+        let mut deposit = vec![1u8];
+        deposit.extend_from_slice(&owner);
+        deposit.extend_from_slice(&500u64.to_le_bytes());
+        deposit.push(1);
+        deposit.extend_from_slice(&2u32.to_le_bytes());
+        deposit.extend_from_slice(b"hi");
+        deposit.extend_from_slice(&2u32.to_le_bytes());
+        deposit.extend_from_slice(&10u16.to_le_bytes());
+        deposit.extend_from_slice(&20u16.to_le_bytes());
+        let mut withdraw = vec![2u8, 0];
+        withdraw.extend_from_slice(&75u64.to_le_bytes());
+
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", VAULT_PROGRAM_ID),
+            format!("Program data: {}", BASE64.encode(&deposit)),
+            format!("Program data: {}", BASE64.encode(&withdraw)),
+            // No layout event starts with 3
+            format!("Program data: {}", BASE64.encode([3u8, 1, 2])),
+            format!(
+                "Program {} failed: custom program error: 0x1",
+                VAULT_PROGRAM_ID
+            ),
+        ];
+        let mut log_contexts = LogContext::parse_logs(
+            &logs,
+            "".to_string(),
+            &ProgramsSelector::new_all_programs(),
+            1,
+            "sig".to_string(),
+        );
+        registry.enrich_all(&mut log_contexts);
+
+        let events = &log_contexts[0].decoded_events;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].name, "Deposit");
+        assert_eq!(events[0].discriminator, "01");
+        assert_eq!(events[0].data["owner"], json!(bs58::encode(owner).into_string()));
+        assert_eq!(events[0].data["amount"], json!(500));
+        assert_eq!(events[0].data["memo"], json!("hi"));
+        assert_eq!(events[0].data["splits"], json!([10, 20]));
+        assert_eq!(events[1].name, "Withdraw");
+        assert_eq!(events[1].discriminator, "0200");
+        assert_eq!(events[1].data_log_index, 1);
+        assert_eq!(log_contexts[0].error_name.as_deref(), Some("VaultLocked"));
+        assert_eq!(log_contexts[0].idl_version.as_deref(), Some("1.2.0"));
+
+        // The JSON form has the same shape
+        let layout = LayoutSchema::from_json(
+            r#"{"name": "vault", "events": [{"name": "Ping", "discriminator": [9], "fields": []}]}"#,
+        )
+        .unwrap();
+        assert_eq!(layout.into_idl().events[0].discriminator_bytes(), vec![9]);
+
+        // Mistakes are reported at load time
+        let empty = r#"{"name": "v", "events": [{"name": "A", "discriminator": []}]}"#;
+        assert!(LayoutSchema::from_json(empty).is_err());
+        let shared = r#"{"name": "v", "events": [{"name": "A", "discriminator": [1]}, {"name": "B", "discriminator": [1]}]}"#;
+        assert!(LayoutSchema::from_json(shared).is_err());
+        let defined = r#"{"name": "v", "events": [{"name": "A", "discriminator": [1], "fields": [{"name": "x", "type": {"vec": {"defined": "X"}}}]}]}"#;
+        assert!(LayoutSchema::from_json(defined).is_err());
+        assert!(LayoutSchema::from_toml("name = ").is_err());
+    }
+
+    #[test]
+    fn decode_modes_report_trailing_and_partial_payloads() {
+        const VAULT_PROGRAM_ID: &str = "Vau1t11111111111111111111111111111111111111";
+        let layout = LayoutSchema::from_json(
+            r#"{"name": "vault", "events": [{"name": "Deposit", "discriminator": [1],
+                "fields": [{"name": "amount", "type": "u64"}, {"name": "memo", "type": "string"}]}]}"#,
+        )
+        .unwrap();
+        let idl = layout.clone().into_idl();
+        let decoder = IdlDecoder::new(&idl);
+
+        let mut exact = vec![1u8];
+        exact.extend_from_slice(&500u64.to_le_bytes());
+        exact.extend_from_slice(&2u32.to_le_bytes());
+        exact.extend_from_slice(b"hi");
+        let mut trailing = exact.clone();
+        trailing.extend_from_slice(&[0, 0]);
+        // memo claims 10 bytes, 2 remain
+        let mut truncated = exact[..9].to_vec();
+        truncated.extend_from_slice(&10u32.to_le_bytes());
+        truncated.extend_from_slice(b"hi");
+        let (exact, trailing, truncated) = (
+            BASE64.encode(&exact),
+            BASE64.encode(&trailing),
+            BASE64.encode(&truncated),
+        );
+
+        for mode in [DecodeMode::Standard, DecodeMode::Strict, DecodeMode::Lenient] {
+            let event = decoder.decode_event_with(&exact, mode).unwrap().unwrap();
+            assert!(event.failure.is_none());
+        }
+
+        // Standard ignores trailing bytes, strict fails, lenient reports them
+        let event = decoder.decode_event(&trailing).unwrap().unwrap();
+        assert!(event.failure.is_none());
+        assert_eq!(
+            decoder.decode_event_with(&trailing, DecodeMode::Strict).unwrap_err(),
+            DecodeError::TrailingBytes {
+                event: "Deposit".to_string(),
+                remaining: 2
+            }
+        );
+        let event = decoder
+            .decode_event_with(&trailing, DecodeMode::Lenient)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.data, json!({"amount": 500, "memo": "hi"}));
+        let failure = event.failure.unwrap();
+        assert!(failure.is_trailing_bytes());
+        assert_eq!(failure.offset, 15);
+
+        // Lenient keeps the fields decoded before the failure
+        assert!(decoder.decode_event(&truncated).is_err());
+        let event = decoder
+            .decode_event_with(&truncated, DecodeMode::Lenient)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.data, json!({"amount": 500}));
+        let failure = event.failure.unwrap();
+        assert_eq!(failure.field.as_deref(), Some("memo"));
+        assert_eq!(failure.offset, 13);
+        assert!(failure.to_string().contains("field memo, offset 13"));
+
+        // The registry records every failure on the LogContext
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", VAULT_PROGRAM_ID),
+            format!("Program data: {}", exact),
+            format!("Program data: {}", trailing),
+            format!("Program data: {}", truncated),
+            "Program data: !!".to_string(),
+            format!("Program {} success", VAULT_PROGRAM_ID),
+        ];
+        let enrich = |mode: DecodeMode| {
+            let mut registry = IdlRegistry::new();
+            registry.insert_layout(VAULT_PROGRAM_ID, layout.clone());
+            registry.set_decode_mode(mode);
+            let mut log_contexts = LogContext::parse_logs(
+                &logs,
+                "".to_string(),
+                &ProgramsSelector::new_all_programs(),
+                1,
+                "sig".to_string(),
+            );
+            registry.enrich_all(&mut log_contexts);
+            log_contexts.remove(0)
+        };
+
+        let standard = enrich(DecodeMode::Standard);
+        assert_eq!(standard.decoded_events.len(), 2);
+        let failed: Vec<usize> = standard
+            .decode_errors
+            .iter()
+            .map(|error| error.data_log_index)
+            .collect();
+        assert_eq!(failed, vec![2, 3]);
+        assert_eq!(standard.decode_errors[0].event.as_deref(), Some("Deposit"));
+        assert_eq!(standard.decode_errors[0].offset, Some(13));
+        // Unreadable base64 matched no event
+        assert!(standard.decode_errors[1].event.is_none());
+        assert!(standard.decode_errors[1].message.contains("base64"));
+
+        let strict = enrich(DecodeMode::Strict);
+        assert_eq!(strict.decoded_events.len(), 1);
+        assert_eq!(strict.decode_errors.len(), 3);
+        assert!(strict.decode_errors[0].message.contains("trailing bytes"));
+
+        let lenient = enrich(DecodeMode::Lenient);
+        assert_eq!(lenient.decoded_events.len(), 3);
+        assert!(!lenient.decoded_events[1].partial);
+        assert!(lenient.decoded_events[2].partial);
+        assert_eq!(lenient.decoded_events[2].data, json!({"amount": 500}));
+        assert_eq!(lenient.decode_errors.len(), 3);
+    }
 }
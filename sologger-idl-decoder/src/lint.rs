@@ -0,0 +1,228 @@
+//! Static checks for an IDL. The decoder tolerates broken IDLs and only fails when a
+//! payload reaches the broken part (`DecodeError::UnknownType`, `UnsupportedType`), or
+//! silently decodes the wrong event when two share a discriminator. [`lint_idl`]
+//! reports all of these up front.
+
+use std::collections::BTreeMap;
+
+use crate::decoder::hex;
+use crate::idl::{Idl, IdlDefinedFields, IdlField, IdlType, IdlTypeDefTy};
+
+/// One problem found by [`lint_idl`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdlLint {
+    ///Several events, instructions or accounts share a discriminator; only the first
+    ///is ever decoded
+    DuplicateDiscriminator {
+        kind: &'static str,
+        discriminator: Vec<u8>,
+        names: Vec<String>,
+    },
+    ///Several errors share a code; `error_name` resolves to the first
+    DuplicateErrorCode { code: u32, names: Vec<String> },
+    ///Several type definitions share a name; references resolve to the first
+    DuplicateTypeName { name: String },
+    ///A `defined` reference (or a 0.30+ event/account) has no entry in `types`
+    UnresolvedType { location: String, name: String },
+    ///A type the decoder does not support (e.g. generics, u256)
+    UnsupportedType {
+        location: String,
+        description: String,
+    },
+}
+
+impl std::fmt::Display for IdlLint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IdlLint::DuplicateDiscriminator {
+                kind,
+                discriminator,
+                names,
+            } => write!(
+                f,
+                "{} {} share discriminator {}",
+                kind,
+                names.join(", "),
+                hex(discriminator)
+            ),
+            IdlLint::DuplicateErrorCode { code, names } => {
+                write!(f, "errors {} share code {}", names.join(", "), code)
+            }
+            IdlLint::DuplicateTypeName { name } => write!(f, "type {} is defined twice", name),
+            IdlLint::UnresolvedType { location, name } => {
+                write!(f, "{}: type {} is not defined in types", location, name)
+            }
+            IdlLint::UnsupportedType {
+                location,
+                description,
+            } => write!(f, "{}: unsupported type {}", location, description),
+        }
+    }
+}
+
+/// Checks an IDL for duplicate discriminators, error codes and type names, and for type
+/// references the decoder cannot resolve. Returns an empty list for a clean IDL.
+pub fn lint_idl(idl: &Idl) -> Vec<IdlLint> {
+    let mut lints = Vec::new();
+
+    duplicate_discriminators(
+        "events",
+        idl.events
+            .iter()
+            .map(|event| (event.discriminator_bytes(), &event.name)),
+        &mut lints,
+    );
+    duplicate_discriminators(
+        "instructions",
+        idl.instructions
+            .iter()
+            .map(|instruction| (instruction.discriminator_bytes(), &instruction.name)),
+        &mut lints,
+    );
+    duplicate_discriminators(
+        "accounts",
+        idl.accounts
+            .iter()
+            .map(|account| (account.discriminator_bytes(), &account.name)),
+        &mut lints,
+    );
+
+    let mut codes: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+    for error in &idl.errors {
+        codes
+            .entry(error.code)
+            .or_default()
+            .push(error.name.clone());
+    }
+    for (code, names) in codes {
+        if names.len() > 1 {
+            lints.push(IdlLint::DuplicateErrorCode { code, names });
+        }
+    }
+
+    for (index, type_def) in idl.types.iter().enumerate() {
+        if idl.types[..index]
+            .iter()
+            .any(|other| other.name == type_def.name)
+        {
+            lints.push(IdlLint::DuplicateTypeName {
+                name: type_def.name.clone(),
+            });
+        }
+    }
+
+    let checker = TypeChecker { idl };
+    for event in &idl.events {
+        let location = format!("event {}", event.name);
+        match &event.fields {
+            Some(fields) => checker.fields(&location, fields, &mut lints),
+            None => checker.named(&location, &event.name, &mut lints),
+        }
+    }
+    for instruction in &idl.instructions {
+        let location = format!("instruction {}", instruction.name);
+        checker.fields(&location, &instruction.args, &mut lints);
+    }
+    for account in &idl.accounts {
+        let location = format!("account {}", account.name);
+        match &account.ty {
+            Some(ty) => checker.type_def(&location, ty, &mut lints),
+            None => checker.named(&location, &account.name, &mut lints),
+        }
+    }
+    for type_def in &idl.types {
+        let location = format!("type {}", type_def.name);
+        checker.type_def(&location, &type_def.ty, &mut lints);
+    }
+
+    lints
+}
+
+fn duplicate_discriminators<'a>(
+    kind: &'static str,
+    entries: impl Iterator<Item = (Vec<u8>, &'a String)>,
+    lints: &mut Vec<IdlLint>,
+) {
+    let mut by_discriminator: BTreeMap<Vec<u8>, Vec<String>> = BTreeMap::new();
+    for (discriminator, name) in entries {
+        by_discriminator
+            .entry(discriminator)
+            .or_default()
+            .push(name.clone());
+    }
+    for (discriminator, names) in by_discriminator {
+        if names.len() > 1 {
+            lints.push(IdlLint::DuplicateDiscriminator {
+                kind,
+                discriminator,
+                names,
+            });
+        }
+    }
+}
+
+struct TypeChecker<'a> {
+    idl: &'a Idl,
+}
+
+impl TypeChecker<'_> {
+    /// A 0.30+ event or account, whose layout is the type of the same name.
+    fn named(&self, location: &str, name: &str, lints: &mut Vec<IdlLint>) {
+        if self.idl.find_type(name).is_none() {
+            lints.push(IdlLint::UnresolvedType {
+                location: location.to_string(),
+                name: name.to_string(),
+            });
+        }
+    }
+
+    fn type_def(&self, location: &str, ty: &IdlTypeDefTy, lints: &mut Vec<IdlLint>) {
+        match ty {
+            IdlTypeDefTy::Struct { fields } => {
+                if let Some(fields) = fields {
+                    self.defined_fields(location, fields, lints);
+                }
+            }
+            IdlTypeDefTy::Enum { variants } => {
+                for variant in variants {
+                    if let Some(fields) = &variant.fields {
+                        let location = format!("{}::{}", location, variant.name);
+                        self.defined_fields(&location, fields, lints);
+                    }
+                }
+            }
+            IdlTypeDefTy::Alias { value } => self.ty(location, value, lints),
+        }
+    }
+
+    fn defined_fields(&self, location: &str, fields: &IdlDefinedFields, lints: &mut Vec<IdlLint>) {
+        match fields {
+            IdlDefinedFields::Named(fields) => self.fields(location, fields, lints),
+            IdlDefinedFields::Tuple(types) => {
+                for (index, ty) in types.iter().enumerate() {
+                    self.ty(&format!("{}.{}", location, index), ty, lints);
+                }
+            }
+        }
+    }
+
+    fn fields(&self, location: &str, fields: &[IdlField], lints: &mut Vec<IdlLint>) {
+        for field in fields {
+            self.ty(&format!("{}.{}", location, field.name), &field.ty, lints);
+        }
+    }
+
+    fn ty(&self, location: &str, ty: &IdlType, lints: &mut Vec<IdlLint>) {
+        match ty {
+            IdlType::Vec(inner) | IdlType::Option(inner) | IdlType::Array(inner, _) => {
+                self.ty(location, inner, lints)
+            }
+            IdlType::Defined(name) => self.named(location, name, lints),
+            IdlType::Unsupported(description) => lints.push(IdlLint::UnsupportedType {
+                location: location.to_string(),
+                description: description.clone(),
+            }),
+            _ => {}
+        }
+    }
+}
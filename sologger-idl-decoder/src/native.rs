@@ -0,0 +1,478 @@
+use serde_json::{Map, Value};
+
+use crate::decoder::{DecodeError, Reader};
+
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+pub const MEMO_V1_PROGRAM_ID: &str = "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo";
+pub const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+pub const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+/// An instruction decoded by one of the built-in native program decoders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedInstruction {
+    ///Short program label, matching the `program` field of the RPC's jsonParsed encoding
+    ///(e.g. "system", "spl-token", "spl-token-2022")
+    pub program: String,
+    ///The instruction name, camelCase as in jsonParsed (e.g. "transferChecked")
+    pub name: String,
+    ///The instruction arguments together with its named accounts. u64 amounts are plain
+    ///JSON numbers and pubkeys are base58 strings
+    pub args: Value,
+}
+
+impl DecodedInstruction {
+    /// Renders the instruction as a compact JSON string of the form
+    /// {"program":...,"name":...,"args":{...}}, the format stored in
+    /// `LogContext::decoded_instruction`.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({ "program": self.program, "name": self.name, "args": self.args })
+            .to_string()
+    }
+}
+
+/// Whether `program_id` is one of the native programs `decode_native_instruction` handles.
+pub fn is_native_program(program_id: &str) -> bool {
+    matches!(
+        program_id,
+        SYSTEM_PROGRAM_ID
+            | TOKEN_PROGRAM_ID
+            | TOKEN_2022_PROGRAM_ID
+            | ASSOCIATED_TOKEN_PROGRAM_ID
+            | MEMO_V1_PROGRAM_ID
+            | MEMO_PROGRAM_ID
+            | COMPUTE_BUDGET_PROGRAM_ID
+    )
+}
+
+/// Decodes raw instruction data for the System, SPL Token, Token-2022, Associated Token
+/// Account, Memo and Compute Budget programs. `accounts` are the instruction's account
+/// keys in order and are attached to the args under their role names.
+///
+/// Returns `Ok(None)` when the program is not a supported native program, or when the
+/// instruction tag is one this decoder does not know (e.g. a newer Token-2022 extension).
+pub fn decode_native_instruction(
+    program_id: &str,
+    data: &[u8],
+    accounts: &[String],
+) -> Result<Option<DecodedInstruction>, DecodeError> {
+    let (program, decoded) = match program_id {
+        SYSTEM_PROGRAM_ID => ("system", decode_system(data)?),
+        TOKEN_PROGRAM_ID => ("spl-token", decode_token(data, false)?),
+        TOKEN_2022_PROGRAM_ID => ("spl-token-2022", decode_token(data, true)?),
+        ASSOCIATED_TOKEN_PROGRAM_ID => ("spl-associated-token-account", decode_ata(data)),
+        MEMO_V1_PROGRAM_ID | MEMO_PROGRAM_ID => ("spl-memo", Some(decode_memo(data)?)),
+        COMPUTE_BUDGET_PROGRAM_ID => ("compute-budget", decode_compute_budget(data)?),
+        _ => return Ok(None),
+    };
+
+    Ok(decoded.map(|(name, account_names, fields)| {
+        let mut args = Map::new();
+        for (role, key) in account_names.iter().zip(accounts) {
+            args.insert((*role).to_string(), Value::String(key.clone()));
+        }
+        for (field, value) in fields {
+            args.insert(field.to_string(), value);
+        }
+        DecodedInstruction {
+            program: program.to_string(),
+            name: name.to_string(),
+            args: Value::Object(args),
+        }
+    }))
+}
+
+/// Instruction name, account role names in instruction order, and decoded arguments.
+type Decoded = (
+    &'static str,
+    &'static [&'static str],
+    Vec<(&'static str, Value)>,
+);
+
+/// System program instructions are bincode: a u32 tag, u64 lengths for strings.
+fn decode_system(data: &[u8]) -> Result<Option<Decoded>, DecodeError> {
+    let mut reader = Reader::new(data);
+    let decoded: Decoded = match reader.read_u32()? {
+        0 => (
+            "createAccount",
+            &["source", "newAccount"],
+            vec![
+                ("lamports", reader.read_u64()?.into()),
+                ("space", reader.read_u64()?.into()),
+                ("owner", read_pubkey(&mut reader)?),
+            ],
+        ),
+        1 => (
+            "assign",
+            &["account"],
+            vec![("owner", read_pubkey(&mut reader)?)],
+        ),
+        2 => (
+            "transfer",
+            &["source", "destination"],
+            vec![("lamports", reader.read_u64()?.into())],
+        ),
+        3 => (
+            "createAccountWithSeed",
+            &["source", "newAccount"],
+            vec![
+                ("base", read_pubkey(&mut reader)?),
+                ("seed", read_bincode_string(&mut reader)?),
+                ("lamports", reader.read_u64()?.into()),
+                ("space", reader.read_u64()?.into()),
+                ("owner", read_pubkey(&mut reader)?),
+            ],
+        ),
+        4 => (
+            "advanceNonce",
+            &["nonceAccount", "recentBlockhashesSysvar", "nonceAuthority"],
+            vec![],
+        ),
+        5 => (
+            "withdrawFromNonce",
+            &[
+                "nonceAccount",
+                "destination",
+                "recentBlockhashesSysvar",
+                "rentSysvar",
+                "nonceAuthority",
+            ],
+            vec![("lamports", reader.read_u64()?.into())],
+        ),
+        6 => (
+            "initializeNonce",
+            &["nonceAccount", "recentBlockhashesSysvar", "rentSysvar"],
+            vec![("nonceAuthority", read_pubkey(&mut reader)?)],
+        ),
+        7 => (
+            "authorizeNonce",
+            &["nonceAccount", "nonceAuthority"],
+            vec![("newAuthorized", read_pubkey(&mut reader)?)],
+        ),
+        8 => (
+            "allocate",
+            &["account"],
+            vec![("space", reader.read_u64()?.into())],
+        ),
+        9 => (
+            "allocateWithSeed",
+            &["account", "base"],
+            vec![
+                ("base", read_pubkey(&mut reader)?),
+                ("seed", read_bincode_string(&mut reader)?),
+                ("space", reader.read_u64()?.into()),
+                ("owner", read_pubkey(&mut reader)?),
+            ],
+        ),
+        10 => (
+            "assignWithSeed",
+            &["account", "base"],
+            vec![
+                ("base", read_pubkey(&mut reader)?),
+                ("seed", read_bincode_string(&mut reader)?),
+                ("owner", read_pubkey(&mut reader)?),
+            ],
+        ),
+        11 => (
+            "transferWithSeed",
+            &["source", "sourceBase", "destination"],
+            vec![
+                ("lamports", reader.read_u64()?.into()),
+                ("sourceSeed", read_bincode_string(&mut reader)?),
+                ("sourceOwner", read_pubkey(&mut reader)?),
+            ],
+        ),
+        12 => ("upgradeNonce", &["nonceAccount"], vec![]),
+        _ => return Ok(None),
+    };
+    Ok(Some(decoded))
+}
+
+/// SPL Token and Token-2022 share tags 0–24. Token-2022 extension instructions (25+)
+/// carry their own nested sub-instruction layouts, so only their names are decoded.
+fn decode_token(data: &[u8], token_2022: bool) -> Result<Option<Decoded>, DecodeError> {
+    let mut reader = Reader::new(data);
+    let decoded: Decoded = match reader.read_u8()? {
+        0 => (
+            "initializeMint",
+            &["mint", "rentSysvar"],
+            vec![
+                ("decimals", reader.read_u8()?.into()),
+                ("mintAuthority", read_pubkey(&mut reader)?),
+                ("freezeAuthority", read_coption_pubkey(&mut reader)?),
+            ],
+        ),
+        1 => (
+            "initializeAccount",
+            &["account", "mint", "owner", "rentSysvar"],
+            vec![],
+        ),
+        2 => (
+            "initializeMultisig",
+            &["multisig", "rentSysvar"],
+            vec![("m", reader.read_u8()?.into())],
+        ),
+        3 => (
+            "transfer",
+            &["source", "destination", "authority"],
+            vec![("amount", reader.read_u64()?.into())],
+        ),
+        4 => (
+            "approve",
+            &["source", "delegate", "owner"],
+            vec![("amount", reader.read_u64()?.into())],
+        ),
+        5 => ("revoke", &["source", "owner"], vec![]),
+        6 => (
+            "setAuthority",
+            &["account", "authority"],
+            vec![
+                ("authorityType", authority_type(reader.read_u8()?)),
+                ("newAuthority", read_coption_pubkey(&mut reader)?),
+            ],
+        ),
+        7 => (
+            "mintTo",
+            &["mint", "account", "mintAuthority"],
+            vec![("amount", reader.read_u64()?.into())],
+        ),
+        8 => (
+            "burn",
+            &["account", "mint", "authority"],
+            vec![("amount", reader.read_u64()?.into())],
+        ),
+        9 => ("closeAccount", &["account", "destination", "owner"], vec![]),
+        10 => (
+            "freezeAccount",
+            &["account", "mint", "freezeAuthority"],
+            vec![],
+        ),
+        11 => (
+            "thawAccount",
+            &["account", "mint", "freezeAuthority"],
+            vec![],
+        ),
+        12 => (
+            "transferChecked",
+            &["source", "mint", "destination", "authority"],
+            vec![
+                ("amount", reader.read_u64()?.into()),
+                ("decimals", reader.read_u8()?.into()),
+            ],
+        ),
+        13 => (
+            "approveChecked",
+            &["source", "mint", "delegate", "owner"],
+            vec![
+                ("amount", reader.read_u64()?.into()),
+                ("decimals", reader.read_u8()?.into()),
+            ],
+        ),
+        14 => (
+            "mintToChecked",
+            &["mint", "account", "mintAuthority"],
+            vec![
+                ("amount", reader.read_u64()?.into()),
+                ("decimals", reader.read_u8()?.into()),
+            ],
+        ),
+        15 => (
+            "burnChecked",
+            &["account", "mint", "authority"],
+            vec![
+                ("amount", reader.read_u64()?.into()),
+                ("decimals", reader.read_u8()?.into()),
+            ],
+        ),
+        16 => (
+            "initializeAccount2",
+            &["account", "mint", "rentSysvar"],
+            vec![("owner", read_pubkey(&mut reader)?)],
+        ),
+        17 => ("syncNative", &["account"], vec![]),
+        18 => (
+            "initializeAccount3",
+            &["account", "mint"],
+            vec![("owner", read_pubkey(&mut reader)?)],
+        ),
+        19 => (
+            "initializeMultisig2",
+            &["multisig"],
+            vec![("m", reader.read_u8()?.into())],
+        ),
+        20 => (
+            "initializeMint2",
+            &["mint"],
+            vec![
+                ("decimals", reader.read_u8()?.into()),
+                ("mintAuthority", read_pubkey(&mut reader)?),
+                ("freezeAuthority", read_coption_pubkey(&mut reader)?),
+            ],
+        ),
+        21 => ("getAccountDataSize", &["mint"], vec![]),
+        22 => ("initializeImmutableOwner", &["account"], vec![]),
+        23 => (
+            "amountToUiAmount",
+            &["mint"],
+            vec![("amount", reader.read_u64()?.into())],
+        ),
+        24 => {
+            let rest = reader.take(reader.remaining())?;
+            (
+                "uiAmountToAmount",
+                &["mint"],
+                vec![(
+                    "uiAmount",
+                    String::from_utf8_lossy(rest).into_owned().into(),
+                )],
+            )
+        }
+        tag if token_2022 => match token_2022_extension_name(tag) {
+            Some(name) => (name, &[], vec![]),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(decoded))
+}
+
+fn token_2022_extension_name(tag: u8) -> Option<&'static str> {
+    Some(match tag {
+        25 => "initializeMintCloseAuthority",
+        26 => "transferFeeExtension",
+        27 => "confidentialTransferExtension",
+        28 => "defaultAccountStateExtension",
+        29 => "reallocate",
+        30 => "memoTransferExtension",
+        31 => "createNativeMint",
+        32 => "initializeNonTransferableMint",
+        33 => "interestBearingMintExtension",
+        34 => "cpiGuardExtension",
+        35 => "initializePermanentDelegate",
+        36 => "transferHookExtension",
+        37 => "confidentialTransferFeeExtension",
+        38 => "withdrawExcessLamports",
+        39 => "metadataPointerExtension",
+        40 => "groupPointerExtension",
+        41 => "groupMemberPointerExtension",
+        42 => "confidentialMintBurnExtension",
+        43 => "scaledUiAmountExtension",
+        44 => "pausableExtension",
+        _ => return None,
+    })
+}
+
+fn authority_type(tag: u8) -> Value {
+    match tag {
+        0 => "mintTokens".into(),
+        1 => "freezeAccount".into(),
+        2 => "accountOwner".into(),
+        3 => "closeAccount".into(),
+        other => other.into(),
+    }
+}
+
+/// The ATA program predates instruction data: an empty payload is `create`.
+fn decode_ata(data: &[u8]) -> Option<Decoded> {
+    const CREATE_ACCOUNTS: &[&str] = &[
+        "source",
+        "account",
+        "wallet",
+        "mint",
+        "systemProgram",
+        "tokenProgram",
+    ];
+    match data.first() {
+        None | Some(0) => Some(("create", CREATE_ACCOUNTS, vec![])),
+        Some(1) => Some(("createIdempotent", CREATE_ACCOUNTS, vec![])),
+        Some(2) => Some((
+            "recoverNested",
+            &[
+                "nestedSource",
+                "nestedMint",
+                "destination",
+                "nestedOwner",
+                "ownerMint",
+                "wallet",
+                "tokenProgram",
+            ],
+            vec![],
+        )),
+        Some(_) => None,
+    }
+}
+
+/// Memo instruction data is the memo text itself.
+fn decode_memo(data: &[u8]) -> Result<Decoded, DecodeError> {
+    let memo = std::str::from_utf8(data)
+        .map_err(|err| DecodeError::InvalidData(format!("memo is not valid UTF-8: {}", err)))?;
+    Ok(("memo", &[], vec![("memo", memo.into())]))
+}
+
+fn decode_compute_budget(data: &[u8]) -> Result<Option<Decoded>, DecodeError> {
+    let mut reader = Reader::new(data);
+    let decoded: Decoded = match reader.read_u8()? {
+        0 => (
+            "requestUnits",
+            &[],
+            vec![
+                ("units", reader.read_u32()?.into()),
+                ("additionalFee", reader.read_u32()?.into()),
+            ],
+        ),
+        1 => (
+            "requestHeapFrame",
+            &[],
+            vec![("bytes", reader.read_u32()?.into())],
+        ),
+        2 => (
+            "setComputeUnitLimit",
+            &[],
+            vec![("units", reader.read_u32()?.into())],
+        ),
+        3 => (
+            "setComputeUnitPrice",
+            &[],
+            vec![("microLamports", reader.read_u64()?.into())],
+        ),
+        4 => (
+            "setLoadedAccountsDataSizeLimit",
+            &[],
+            vec![("bytes", reader.read_u32()?.into())],
+        ),
+        _ => return Ok(None),
+    };
+    Ok(Some(decoded))
+}
+
+fn read_pubkey(reader: &mut Reader) -> Result<Value, DecodeError> {
+    Ok(Value::String(bs58::encode(reader.take(32)?).into_string()))
+}
+
+/// spl-token packs `COption<Pubkey>` as a one-byte tag followed by the key when present.
+fn read_coption_pubkey(reader: &mut Reader) -> Result<Value, DecodeError> {
+    match reader.read_u8()? {
+        0 => Ok(Value::Null),
+        1 => read_pubkey(reader),
+        other => Err(DecodeError::InvalidData(format!(
+            "invalid COption tag {}",
+            other
+        ))),
+    }
+}
+
+/// bincode prefixes strings with a u64 length rather than borsh's u32.
+fn read_bincode_string(reader: &mut Reader) -> Result<Value, DecodeError> {
+    let len = reader.read_u64()?;
+    if len > reader.remaining() as u64 {
+        return Err(DecodeError::InvalidData(format!(
+            "length prefix {} exceeds remaining payload {}",
+            len,
+            reader.remaining()
+        )));
+    }
+    let bytes = reader.take(len as usize)?;
+    Ok(Value::String(String::from_utf8_lossy(bytes).into_owned()))
+}
@@ -1,16 +1,104 @@
 use std::collections::HashMap;
 
-use sologger_log_context::sologger_log_context::LogContext;
+use sologger_log_context::sologger_log_context::{LogContext, LogDecodeError};
 
-use crate::decoder::{decode_event, DecodeError};
+use crate::decoder::{DecodeError, DecodeFailure, DecodeMode, DecodedEvent, IdlDecoder};
 use crate::idl::Idl;
+use crate::layout::LayoutSchema;
+use crate::native::decode_native_instruction;
 
 /// IDLs keyed by program ID. The enrichment entry point for both the sologger binary
 /// (loaded from the `idls` map in sologger-config.json) and the WASM transformer
 /// (loaded via `add_idl` from the browser).
-#[derive(Default, Clone, Debug)]
+///
+/// A program can hold several IDLs over slot ranges of its upgrade history; `enrich`
+/// picks the one covering the LogContext's slot, so backfills of old transactions decode
+/// with the layout that was live at the time.
+///
+/// Every IDL is compiled into an [`IdlDecoder`] when it is registered, so enrichment
+/// pays no per-payload IDL lookups.
+///
+/// The built-in native program decoders (see `native`) are enabled by default, so a
+/// registry with no IDLs still decodes System, SPL Token and friends.
+#[derive(Clone, Debug)]
 pub struct IdlRegistry {
-    idls: HashMap<String, Idl>,
+    ///Per program, sorted by valid_from_slot
+    idls: HashMap<String, Vec<RegisteredIdl>>,
+    native_decoders: bool,
+    log_message_events: bool,
+    decode_mode: DecodeMode,
+}
+
+/// A registered IDL and its compiled decoder.
+#[derive(Clone, Debug)]
+struct RegisteredIdl {
+    versioned: VersionedIdl,
+    decoder: IdlDecoder,
+}
+
+impl RegisteredIdl {
+    fn new(versioned: VersionedIdl) -> Self {
+        let decoder = IdlDecoder::new(&versioned.idl);
+        Self { versioned, decoder }
+    }
+}
+
+/// An IDL that applies to a slot range of a program's history.
+#[derive(Clone, Debug)]
+pub struct VersionedIdl {
+    pub idl: Idl,
+    ///First slot (inclusive) the IDL applies to. None: since deployment
+    pub valid_from_slot: Option<u64>,
+    ///Last slot (inclusive) the IDL applies to. None: still current
+    pub valid_until_slot: Option<u64>,
+    ///Reported in `LogContext::idl_version`. None falls back to the IDL's own version
+    pub label: Option<String>,
+}
+
+impl VersionedIdl {
+    /// An IDL that applies to every slot.
+    pub fn new(idl: Idl) -> Self {
+        Self {
+            idl,
+            valid_from_slot: None,
+            valid_until_slot: None,
+            label: None,
+        }
+    }
+
+    pub fn covers(&self, slot: u64) -> bool {
+        self.valid_from_slot.is_none_or(|from| slot >= from)
+            && self.valid_until_slot.is_none_or(|until| slot <= until)
+    }
+
+    /// The version reported for contexts this IDL enriched: the label, else the IDL's
+    /// own version, else its slot range.
+    pub fn version(&self) -> String {
+        if let Some(label) = &self.label {
+            return label.clone();
+        }
+        if let Some(version) = self.idl.program_version() {
+            return version.to_string();
+        }
+        format!(
+            "slots {}..{}",
+            self.valid_from_slot.unwrap_or(0),
+            self.valid_until_slot
+                .map(|until| until.to_string())
+                .unwrap_or_default()
+        )
+    }
+}
+
+impl Default for IdlRegistry {
+    fn default() -> Self {
+        Self {
+            idls: HashMap::new(),
+            native_decoders: true,
+            log_message_events: false,
+            decode_mode: DecodeMode::Standard,
+        }
+    }
 }
 
 impl IdlRegistry {
@@ -18,17 +106,63 @@ impl IdlRegistry {
         Self::default()
     }
 
+    /// Turns the built-in native program decoders on or off.
+    pub fn set_native_decoders(&mut self, enabled: bool) {
+        self.native_decoders = enabled;
+    }
+
+    pub fn native_decoders(&self) -> bool {
+        self.native_decoders
+    }
+
+    /// Also tries base64-looking `log_messages` against the IDL's events. Anchor before
+    /// 0.24 emitted events as `Program log: <base64>` rather than `Program data:`, so this
+    /// is needed to decode the history of programs built with those versions. Off by
+    /// default: it costs a base64 decode per candidate message.
+    pub fn set_log_message_events(&mut self, enabled: bool) {
+        self.log_message_events = enabled;
+    }
+
+    pub fn log_message_events(&self) -> bool {
+        self.log_message_events
+    }
+
+    /// How payloads that match an event but don't fit its layout are handled. Every
+    /// failure is recorded in `LogContext::decode_errors`; in [`DecodeMode::Lenient`] the
+    /// fields decoded before it are also kept as a `partial` event. Trailing bytes count
+    /// as a failure in strict and lenient mode only.
+    pub fn set_decode_mode(&mut self, mode: DecodeMode) {
+        self.decode_mode = mode;
+    }
+
+    pub fn decode_mode(&self) -> DecodeMode {
+        self.decode_mode
+    }
+
     pub fn is_empty(&self) -> bool {
         self.idls.is_empty()
     }
 
+    /// The number of programs with at least one IDL.
     pub fn len(&self) -> usize {
         self.idls.len()
     }
 
-    /// Registers a parsed IDL for a program ID, replacing any previous one.
+    /// Registers a parsed IDL for a program ID, valid for every slot and replacing any
+    /// previous ones.
     pub fn insert(&mut self, program_id: impl Into<String>, idl: Idl) {
-        self.idls.insert(program_id.into(), idl);
+        self.idls.insert(
+            program_id.into(),
+            vec![RegisteredIdl::new(VersionedIdl::new(idl))],
+        );
+    }
+
+    /// Adds an IDL for a slot range of a program's history, alongside any already
+    /// registered. Where ranges overlap, the one starting later wins.
+    pub fn insert_versioned(&mut self, program_id: impl Into<String>, versioned: VersionedIdl) {
+        let versions = self.idls.entry(program_id.into()).or_default();
+        versions.push(RegisteredIdl::new(versioned));
+        versions.sort_by_key(|version| version.versioned.valid_from_slot.unwrap_or(0));
     }
 
     /// Parses IDL JSON (either spec version) and registers it for a program ID.
@@ -38,27 +172,101 @@ impl IdlRegistry {
         Ok(())
     }
 
+    /// Inflates the raw data of an on-chain Anchor IDL account and registers the IDL for
+    /// a program ID.
+    pub fn insert_account_data(
+        &mut self,
+        program_id: &str,
+        data: &[u8],
+    ) -> Result<(), DecodeError> {
+        let idl = Idl::from_account_data(data)?;
+        self.insert(program_id, idl);
+        Ok(())
+    }
+
+    /// Registers a user-defined layout for a program without an Anchor IDL. Replaces
+    /// any previous IDLs or layouts for the program, like [`IdlRegistry::insert`].
+    pub fn insert_layout(&mut self, program_id: impl Into<String>, layout: LayoutSchema) {
+        self.insert(program_id, layout.into_idl());
+    }
+
+    /// Registers every IDL of `other`, replacing this registry's IDLs for the same
+    /// programs.
+    pub fn merge(&mut self, other: &IdlRegistry) {
+        for (program_id, versions) in &other.idls {
+            self.idls.insert(program_id.clone(), versions.clone());
+        }
+    }
+
+    /// The most recent IDL registered for a program.
     pub fn get(&self, program_id: &str) -> Option<&Idl> {
-        self.idls.get(program_id)
+        self.idls
+            .get(program_id)
+            .and_then(|versions| versions.last())
+            .map(|version| &version.versioned.idl)
+    }
+
+    /// The IDL that was live for a program at `slot`, if any registered range covers it.
+    pub fn get_for_slot(&self, program_id: &str, slot: u64) -> Option<&VersionedIdl> {
+        self.registered_for_slot(program_id, slot)
+            .map(|version| &version.versioned)
+    }
+
+    /// The compiled decoder of the IDL that was live for a program at `slot`.
+    pub fn decoder_for_slot(&self, program_id: &str, slot: u64) -> Option<&IdlDecoder> {
+        self.registered_for_slot(program_id, slot)
+            .map(|version| &version.decoder)
     }
 
-    /// Enriches a LogContext in place when an IDL is registered for its program:
-    /// decodes `data_logs` into `decoded_events`, and resolves `error_code` into
-    /// `error_name`. A LogContext for an unregistered program is left untouched.
+    fn registered_for_slot(&self, program_id: &str, slot: u64) -> Option<&RegisteredIdl> {
+        self.idls
+            .get(program_id)?
+            .iter()
+            .rev()
+            .find(|version| version.versioned.covers(slot))
+    }
+
+    /// Every IDL registered for a program, oldest range first.
+    pub fn versions(&self, program_id: &str) -> Vec<&VersionedIdl> {
+        self.idls.get(program_id).map_or(vec![], |versions| {
+            versions.iter().map(|version| &version.versioned).collect()
+        })
+    }
+
+    /// Enriches a LogContext in place when an IDL is registered for its program at its
+    /// slot: decodes `data_logs` (and, when enabled, base64 `log_messages`) into
+    /// `decoded_events`, resolves `error_code` into `error_name`, and records the IDL used
+    /// in `idl_version`. Independently of any IDL, `instruction_data` of a native program
+    /// is decoded into `decoded_instruction` when native decoders are enabled.
     pub fn enrich(&self, log_context: &mut LogContext) {
-        let Some(idl) = self.idls.get(&log_context.program_id) else {
+        if self.native_decoders && log_context.decoded_instruction.is_none() {
+            self.decode_native(log_context);
+        }
+
+        let Some(RegisteredIdl { versioned, decoder }) =
+            self.registered_for_slot(&log_context.program_id, log_context.slot as u64)
+        else {
             return;
         };
+        let idl = &versioned.idl;
+        let mut enriched = false;
 
-        for data_log in &log_context.data_logs {
-            match decode_event(idl, data_log) {
-                Ok(Some(event)) => log_context.decoded_events.push(event.to_json()),
-                Ok(None) => {}
-                Err(err) => log::debug!(
-                    "failed to decode data log for program {}: {}",
-                    log_context.program_id,
-                    err
-                ),
+        for index in 0..log_context.data_logs.len() {
+            // Always lenient, so a failure carries its event, field and offset; the
+            // registry's mode then decides what is kept
+            let outcome =
+                decoder.decode_event_with(&log_context.data_logs[index], DecodeMode::Lenient);
+            enriched |= self.record(log_context, outcome, index, false);
+        }
+
+        if self.log_message_events {
+            for index in 0..log_context.log_messages.len() {
+                if !looks_like_event_payload(&log_context.log_messages[index]) {
+                    continue;
+                }
+                let outcome = decoder
+                    .decode_event_with(&log_context.log_messages[index], DecodeMode::Lenient);
+                enriched |= self.record(log_context, outcome, index, true);
             }
         }
 
@@ -66,14 +274,114 @@ impl IdlRegistry {
             if let Some(code) = log_context.error_code {
                 if let Some(idl_error) = idl.lookup_error(code) {
                     log_context.error_name = Some(idl_error.name.clone());
+                    enriched = true;
                 }
             }
         }
+
+        if enriched {
+            log_context.idl_version = Some(versioned.version());
+        }
     }
 
-    /// Enriches every LogContext in the slice. Cheap no-op when the registry is empty.
+    /// Adds one payload's outcome to the LogContext under the registry's decode mode.
+    /// Returns whether the IDL matched the payload.
+    fn record(
+        &self,
+        log_context: &mut LogContext,
+        outcome: Result<Option<DecodedEvent>, DecodeError>,
+        index: usize,
+        from_log_message: bool,
+    ) -> bool {
+        let event = match outcome {
+            Ok(Some(event)) => event,
+            Ok(None) => return false,
+            // Ordinary log text can pass for base64; only real data logs are reported
+            Err(err) if from_log_message => {
+                log::debug!(
+                    "failed to decode log message for program {}: {}",
+                    log_context.program_id,
+                    err
+                );
+                return false;
+            }
+            Err(err) => {
+                log_context.decode_errors.push(LogDecodeError {
+                    program: log_context.program_id.clone(),
+                    event: None,
+                    data_log_index: index,
+                    from_log_message,
+                    offset: None,
+                    field: None,
+                    message: err.to_string(),
+                });
+                return false;
+            }
+        };
+
+        let keep_event = match &event.failure {
+            None => true,
+            Some(failure) if failure.is_trailing_bytes() => self.decode_mode != DecodeMode::Strict,
+            Some(_) => self.decode_mode == DecodeMode::Lenient,
+        };
+        let ignored = self.decode_mode == DecodeMode::Standard
+            && event
+                .failure
+                .as_ref()
+                .is_some_and(DecodeFailure::is_trailing_bytes);
+        if let Some(failure) = event.failure.as_ref().filter(|_| !ignored) {
+            log_context.decode_errors.push(LogDecodeError {
+                program: log_context.program_id.clone(),
+                event: Some(event.name.clone()),
+                data_log_index: index,
+                from_log_message,
+                offset: Some(failure.offset),
+                field: failure.field.clone(),
+                message: failure.error.to_string(),
+            });
+        }
+        if keep_event {
+            let mut event = event.into_log_event(&log_context.program_id, index);
+            event.from_log_message = from_log_message;
+            log_context.decoded_events.push(event);
+        }
+        true
+    }
+
+    fn decode_native(&self, log_context: &mut LogContext) {
+        let Some(data) = &log_context.instruction_data else {
+            return;
+        };
+        let bytes = match bs58::decode(data).into_vec() {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                log::debug!(
+                    "invalid base58 instruction data for program {}: {}",
+                    log_context.program_id,
+                    err
+                );
+                return;
+            }
+        };
+        match decode_native_instruction(
+            &log_context.program_id,
+            &bytes,
+            &log_context.instruction_accounts,
+        ) {
+            Ok(Some(instruction)) => log_context.decoded_instruction = Some(instruction.to_json()),
+            Ok(None) => {}
+            Err(err) => log::debug!(
+                "failed to decode instruction for program {}: {}",
+                log_context.program_id,
+                err
+            ),
+        }
+    }
+
+    /// Enriches every LogContext in the slice. Cheap no-op when the registry is empty
+    /// and native decoders are disabled.
     pub fn enrich_all(&self, log_contexts: &mut [LogContext]) {
-        if self.idls.is_empty() {
+        if self.idls.is_empty() && !self.native_decoders {
             return;
         }
         for log_context in log_contexts {
@@ -81,3 +389,14 @@ impl IdlRegistry {
         }
     }
 }
+
+/// Whether a log message could be a base64 event payload: padded base64 of at least a
+/// discriminator (8 bytes, 12 chars). Filters out ordinary `msg!` text before decoding.
+fn looks_like_event_payload(message: &str) -> bool {
+    message.len() >= 12
+        && message.len().is_multiple_of(4)
+        && message
+            .trim_end_matches('=')
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'/')
+}
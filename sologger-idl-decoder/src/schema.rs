@@ -0,0 +1,424 @@
+//! Table shapes for an IDL's events, derived from the same rendering rules the decoder
+//! applies (u128/i128 as decimal strings, pubkeys as base58, `bytes` as base64), so
+//! downstream tables always accept what `decoded_events` carries.
+//!
+//! - [`events_json_schema`]: a JSON Schema for one `decoded_events` entry
+//! - [`events_ddl`]: one `CREATE TABLE` per event, for ClickHouse or Postgres. Scalar
+//!   fields, options and vectors of scalars become typed columns; nested structs, enums
+//!   with fields and other compound values become a JSON column. [`event_metadata`]
+//!   gives a decoded event's metadata column values.
+
+use serde_json::{json, Map, Value};
+use sologger_log_context::sologger_log_context::{DecodedLogEvent, LogContext};
+
+use crate::decoder::snake_case;
+use crate::idl::{Idl, IdlDefinedFields, IdlField, IdlType, IdlTypeDef, IdlTypeDefTy};
+
+/// The SQL dialect emitted by [`events_ddl`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SqlDialect {
+    ClickHouse,
+    Postgres,
+}
+
+/// Ingestion metadata columns that precede every event's own fields. Prefixed so they
+/// cannot collide with IDL field names. [`event_metadata`] gives their values.
+fn metadata_columns(dialect: SqlDialect) -> [(&'static str, &'static str); 8] {
+    match dialect {
+        SqlDialect::ClickHouse => [
+            ("_signature", "String"),
+            ("_slot", "UInt64"),
+            ("_program_id", "LowCardinality(String)"),
+            ("_instruction_index", "UInt32"),
+            ("_depth", "UInt32"),
+            ("_invocation_index", "UInt32"),
+            ("_from_log_message", "Bool"),
+            ("_data_log_index", "UInt32"),
+        ],
+        // Slots stay far below i64::MAX, so BIGINT rather than NUMERIC
+        SqlDialect::Postgres => [
+            ("_signature", "VARCHAR(88)"),
+            ("_slot", "BIGINT"),
+            ("_program_id", "VARCHAR(44)"),
+            ("_instruction_index", "INTEGER"),
+            ("_depth", "INTEGER"),
+            ("_invocation_index", "INTEGER"),
+            ("_from_log_message", "BOOLEAN"),
+            ("_data_log_index", "INTEGER"),
+        ],
+    }
+}
+
+/// The columns that identify one event row: `_data_log_index` only indexes the logs of
+/// one invocation, of `data_logs` or (`_from_log_message`) of `log_messages`.
+const EVENT_KEY: &str = "_signature, _invocation_index, _from_log_message, _data_log_index";
+
+/// The metadata column values of the row for `event`, decoded from `log_context`.
+/// `_invocation_index` is the invocation's position among the transaction's parsed
+/// invocations, the last component of the LogContext's `id`.
+pub fn event_metadata(log_context: &LogContext, event: &DecodedLogEvent) -> Map<String, Value> {
+    let invocation_index = log_context
+        .id
+        .rsplit('-')
+        .next()
+        .and_then(|index| index.parse::<u64>().ok())
+        .unwrap_or(0);
+    let mut metadata = Map::new();
+    metadata.insert("_signature".to_string(), json!(log_context.signature));
+    metadata.insert("_slot".to_string(), json!(log_context.slot));
+    metadata.insert("_program_id".to_string(), json!(event.program));
+    metadata.insert(
+        "_instruction_index".to_string(),
+        json!(log_context.instruction_index),
+    );
+    metadata.insert("_depth".to_string(), json!(log_context.depth));
+    metadata.insert("_invocation_index".to_string(), json!(invocation_index));
+    metadata.insert(
+        "_from_log_message".to_string(),
+        json!(event.from_log_message),
+    );
+    metadata.insert("_data_log_index".to_string(), json!(event.data_log_index));
+    metadata
+}
+
+/// A JSON Schema (draft-07) describing one `decoded_events` entry for this IDL: a
+/// `oneOf` over its events, each `{"name": <event>, "data": {...}}`. Every defined type
+/// the events reference is emitted under `definitions`.
+pub fn events_json_schema(idl: &Idl) -> Value {
+    let mut definitions = Map::new();
+    for type_def in &idl.types {
+        definitions
+            .entry(type_def.name.clone())
+            .or_insert_with(|| type_def_schema(type_def));
+    }
+
+    let mut events = Vec::new();
+    for event in &idl.events {
+        let data = match &event.fields {
+            // Legacy: inline fields
+            Some(fields) => named_fields_schema(fields),
+            // 0.30+: the struct of the same name
+            None => json!({ "$ref": format!("#/definitions/{}", event.name) }),
+        };
+        events.push(json!({
+            "type": "object",
+            "properties": {
+                "name": { "const": event.name },
+                "data": data,
+            },
+            "required": ["name", "data"],
+        }));
+    }
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": format!("{} events", idl.program_name()),
+        "description": "One entry of LogContext.decoded_events. u128/i128 are decimal strings, pubkeys base58 strings, bytes base64 strings.",
+        "oneOf": events,
+        "definitions": definitions,
+    })
+}
+
+fn type_def_schema(type_def: &IdlTypeDef) -> Value {
+    match &type_def.ty {
+        IdlTypeDefTy::Struct { fields } => match fields {
+            None => json!({ "type": "object" }),
+            Some(fields) => defined_fields_schema(fields),
+        },
+        IdlTypeDefTy::Enum { variants } => {
+            // Unit variants decode to their name, variants with fields to {name: fields}
+            let units: Vec<&str> = variants
+                .iter()
+                .filter(|variant| variant.fields.is_none())
+                .map(|variant| variant.name.as_str())
+                .collect();
+            let mut options = Vec::new();
+            if !units.is_empty() {
+                options.push(json!({ "type": "string", "enum": units }));
+            }
+            for variant in variants {
+                if let Some(fields) = &variant.fields {
+                    options.push(json!({
+                        "type": "object",
+                        "properties": { variant.name.clone(): defined_fields_schema(fields) },
+                        "required": [variant.name],
+                        "additionalProperties": false,
+                    }));
+                }
+            }
+            json!({ "oneOf": options })
+        }
+        IdlTypeDefTy::Alias { value } => type_schema(value),
+    }
+}
+
+fn defined_fields_schema(fields: &IdlDefinedFields) -> Value {
+    match fields {
+        IdlDefinedFields::Named(fields) => named_fields_schema(fields),
+        IdlDefinedFields::Tuple(types) => json!({
+            "type": "array",
+            "items": types.iter().map(type_schema).collect::<Vec<_>>(),
+            "minItems": types.len(),
+            "maxItems": types.len(),
+        }),
+    }
+}
+
+fn named_fields_schema(fields: &[IdlField]) -> Value {
+    let mut properties = Map::new();
+    for field in fields {
+        properties.insert(field.name.clone(), type_schema(&field.ty));
+    }
+    let required: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn integer_schema(min: i128, max: i128) -> Value {
+    json!({ "type": "integer", "minimum": min, "maximum": max })
+}
+
+fn type_schema(ty: &IdlType) -> Value {
+    match ty {
+        IdlType::Bool => json!({ "type": "boolean" }),
+        IdlType::U8 => integer_schema(0, u8::MAX as i128),
+        IdlType::I8 => integer_schema(i8::MIN as i128, i8::MAX as i128),
+        IdlType::U16 => integer_schema(0, u16::MAX as i128),
+        IdlType::I16 => integer_schema(i16::MIN as i128, i16::MAX as i128),
+        IdlType::U32 => integer_schema(0, u32::MAX as i128),
+        IdlType::I32 => integer_schema(i32::MIN as i128, i32::MAX as i128),
+        IdlType::U64 => integer_schema(0, u64::MAX as i128),
+        IdlType::I64 => integer_schema(i64::MIN as i128, i64::MAX as i128),
+        IdlType::U128 => json!({ "type": "string", "pattern": "^[0-9]+$" }),
+        IdlType::I128 => json!({ "type": "string", "pattern": "^-?[0-9]+$" }),
+        IdlType::F32 | IdlType::F64 => json!({ "type": "number" }),
+        IdlType::Bytes => json!({ "type": "string", "contentEncoding": "base64" }),
+        IdlType::String => json!({ "type": "string" }),
+        IdlType::Pubkey => json!({ "type": "string", "pattern": "^[1-9A-HJ-NP-Za-km-z]{32,44}$" }),
+        IdlType::Vec(elem) => json!({ "type": "array", "items": type_schema(elem) }),
+        IdlType::Option(inner) => json!({ "anyOf": [{ "type": "null" }, type_schema(inner)] }),
+        IdlType::Array(elem, len) => json!({
+            "type": "array",
+            "items": type_schema(elem),
+            "minItems": len,
+            "maxItems": len,
+        }),
+        IdlType::Defined(name) => json!({ "$ref": format!("#/definitions/{}", name) }),
+        // Payloads reaching these fail to decode, so no value is ever produced
+        IdlType::Unsupported(desc) => json!({ "description": format!("unsupported: {}", desc) }),
+    }
+}
+
+/// The column shape of one field, resolved through aliases.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ColumnType {
+    Bool,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    U128,
+    I128,
+    F32,
+    F64,
+    /// string, bytes (base64) and unit-only enums (variant name)
+    Text,
+    Pubkey,
+    Nullable(Box<ColumnType>),
+    Array(Box<ColumnType>),
+    /// Structs, enums with fields and anything nested deeper: stored as JSON
+    Json,
+}
+
+impl ColumnType {
+    fn is_scalar(&self) -> bool {
+        !matches!(
+            self,
+            ColumnType::Nullable(_) | ColumnType::Array(_) | ColumnType::Json
+        )
+    }
+
+    fn sql(&self, dialect: SqlDialect) -> String {
+        use ColumnType::*;
+        match dialect {
+            SqlDialect::ClickHouse => match self {
+                Bool => "Bool".into(),
+                U8 => "UInt8".into(),
+                I8 => "Int8".into(),
+                U16 => "UInt16".into(),
+                I16 => "Int16".into(),
+                U32 => "UInt32".into(),
+                I32 => "Int32".into(),
+                U64 => "UInt64".into(),
+                I64 => "Int64".into(),
+                // Parsed from the decimal strings the decoder renders
+                U128 => "UInt128".into(),
+                I128 => "Int128".into(),
+                F32 => "Float32".into(),
+                F64 => "Float64".into(),
+                Text | Pubkey | Json => "String".into(),
+                Nullable(inner) => format!("Nullable({})", inner.sql(dialect)),
+                Array(inner) => format!("Array({})", inner.sql(dialect)),
+            },
+            SqlDialect::Postgres => match self {
+                Bool => "BOOLEAN".into(),
+                U8 | I8 | I16 => "SMALLINT".into(),
+                U16 | I32 => "INTEGER".into(),
+                U32 | I64 => "BIGINT".into(),
+                U64 => "NUMERIC(20, 0)".into(),
+                U128 | I128 => "NUMERIC(39, 0)".into(),
+                F32 => "REAL".into(),
+                F64 => "DOUBLE PRECISION".into(),
+                Text => "TEXT".into(),
+                Pubkey => "VARCHAR(44)".into(),
+                Json => "JSONB".into(),
+                // Postgres columns are nullable unless NOT NULL; see `column_sql`
+                Nullable(inner) => inner.sql(dialect),
+                Array(inner) => format!("{}[]", inner.sql(dialect)),
+            },
+        }
+    }
+}
+
+/// Resolves field types into column types against the IDL's `types`.
+struct ColumnResolver<'a> {
+    idl: &'a Idl,
+}
+
+impl ColumnResolver<'_> {
+    fn column_type(&self, ty: &IdlType, depth: usize) -> ColumnType {
+        // Aliases of aliases are resolved; anything deeper is stored as JSON
+        if depth > 8 {
+            return ColumnType::Json;
+        }
+        match ty {
+            IdlType::Bool => ColumnType::Bool,
+            IdlType::U8 => ColumnType::U8,
+            IdlType::I8 => ColumnType::I8,
+            IdlType::U16 => ColumnType::U16,
+            IdlType::I16 => ColumnType::I16,
+            IdlType::U32 => ColumnType::U32,
+            IdlType::I32 => ColumnType::I32,
+            IdlType::U64 => ColumnType::U64,
+            IdlType::I64 => ColumnType::I64,
+            IdlType::U128 => ColumnType::U128,
+            IdlType::I128 => ColumnType::I128,
+            IdlType::F32 => ColumnType::F32,
+            IdlType::F64 => ColumnType::F64,
+            IdlType::Bytes | IdlType::String => ColumnType::Text,
+            IdlType::Pubkey => ColumnType::Pubkey,
+            IdlType::Option(inner) => match self.column_type(inner, depth + 1) {
+                scalar if scalar.is_scalar() => ColumnType::Nullable(Box::new(scalar)),
+                _ => ColumnType::Json,
+            },
+            IdlType::Vec(elem) | IdlType::Array(elem, _) => {
+                match self.column_type(elem, depth + 1) {
+                    scalar if scalar.is_scalar() => ColumnType::Array(Box::new(scalar)),
+                    _ => ColumnType::Json,
+                }
+            }
+            IdlType::Defined(name) => match self.idl.find_type(name).map(|def| &def.ty) {
+                Some(IdlTypeDefTy::Alias { value }) => self.column_type(value, depth + 1),
+                Some(IdlTypeDefTy::Enum { variants })
+                    if variants.iter().all(|variant| variant.fields.is_none()) =>
+                {
+                    ColumnType::Text
+                }
+                _ => ColumnType::Json,
+            },
+            IdlType::Unsupported(_) => ColumnType::Json,
+        }
+    }
+
+    /// The top-level fields of an event, in payload order.
+    fn event_fields(&self, event_name: &str, inline: Option<&Vec<IdlField>>) -> Vec<IdlField> {
+        if let Some(fields) = inline {
+            return fields.clone();
+        }
+        match self.idl.find_type(event_name).map(|def| &def.ty) {
+            Some(IdlTypeDefTy::Struct {
+                fields: Some(IdlDefinedFields::Named(fields)),
+            }) => fields.clone(),
+            Some(IdlTypeDefTy::Struct {
+                fields: Some(IdlDefinedFields::Tuple(types)),
+            }) => types
+                .iter()
+                .enumerate()
+                .map(|(index, ty)| IdlField {
+                    name: format!("_{}", index),
+                    ty: ty.clone(),
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// `CREATE TABLE` statements, one per event, named `<table_prefix><event_in_snake_case>`.
+/// Each table starts with the metadata columns (signature, slot, program, the emitting
+/// invocation and the event's log index; see [`event_metadata`]), followed by the
+/// event's top-level fields.
+pub fn events_ddl(idl: &Idl, dialect: SqlDialect, table_prefix: &str) -> String {
+    let resolver = ColumnResolver { idl };
+    let mut statements = Vec::new();
+
+    for event in &idl.events {
+        let table = format!("{}{}", table_prefix, snake_case(&event.name));
+        let mut columns: Vec<String> = metadata_columns(dialect)
+            .iter()
+            .map(|(name, ty)| {
+                let not_null = if dialect == SqlDialect::Postgres {
+                    " NOT NULL"
+                } else {
+                    ""
+                };
+                format!("{} {}{}", quote(name, dialect), ty, not_null)
+            })
+            .collect();
+        for field in resolver.event_fields(&event.name, event.fields.as_ref()) {
+            let ty = resolver.column_type(&field.ty, 0);
+            columns.push(column_sql(&field.name, &ty, dialect));
+        }
+
+        let body = columns.join(",\n    ");
+        statements.push(match dialect {
+            SqlDialect::ClickHouse => format!(
+                "CREATE TABLE IF NOT EXISTS {} (\n    {}\n) ENGINE = MergeTree\nORDER BY (_slot, {});",
+                quote(&table, dialect),
+                body,
+                EVENT_KEY
+            ),
+            SqlDialect::Postgres => format!(
+                "CREATE TABLE IF NOT EXISTS {} (\n    {},\n    PRIMARY KEY ({})\n);",
+                quote(&table, dialect),
+                body,
+                EVENT_KEY
+            ),
+        });
+    }
+    statements.join("\n\n") + "\n"
+}
+
+fn column_sql(name: &str, ty: &ColumnType, dialect: SqlDialect) -> String {
+    let not_null = dialect == SqlDialect::Postgres && !matches!(ty, ColumnType::Nullable(_));
+    format!(
+        "{} {}{}",
+        quote(name, dialect),
+        ty.sql(dialect),
+        if not_null { " NOT NULL" } else { "" }
+    )
+}
+
+fn quote(identifier: &str, dialect: SqlDialect) -> String {
+    match dialect {
+        SqlDialect::ClickHouse => format!("`{}`", identifier.replace('`', "``")),
+        SqlDialect::Postgres => format!("\"{}\"", identifier.replace('"', "\"\"")),
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One observed write to an account of a watched program: the account's decoded state at
+/// `slot`, and what changed since the previous write sologger saw for it. Emitted through
+/// the same transports as LogContexts.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AccountChange {
+    ///The slot the write was observed at
+    pub slot: u64,
+    ///The account address
+    pub pubkey: String,
+    ///The program that owns the account
+    pub owner: String,
+    ///The account balance in lamports
+    pub lamports: u64,
+    ///The length of the raw account data in bytes
+    pub data_len: usize,
+    ///The account name from the owner's IDL, when the data matched one of its account discriminators
+    pub account_type: Option<String>,
+    ///The decoded account fields, rendered as decoded events are. None when the data could not be decoded
+    pub data: Option<Value>,
+    ///The version of the IDL used to decode the data
+    pub idl_version: Option<String>,
+    ///Why data matching an account discriminator could not be decoded, e.g. because the IDL no longer matches the program
+    pub decode_error: Option<String>,
+    ///The slot of the previous write seen for this account, if any
+    pub previous_slot: Option<u64>,
+    ///The decoded fields that changed since the previous write. Empty for the first write seen
+    pub diff: Vec<AccountFieldChange>,
+}
+
+/// One changed field between two decoded states of an account.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AccountFieldChange {
+    ///The dot-separated path of the field, e.g. "fees.trade_fee_rate". Empty when the whole state changed shape
+    pub path: String,
+    ///The previous value; null when the field was added
+    pub previous: Value,
+    ///The current value; null when the field was removed
+    pub current: Value,
+}
+
+impl AccountChange {
+    /// Returns the AccountChange in JSON format
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Lists the fields that differ between two decoded account states. Nested structs are
+/// compared field by field; arrays, enums and scalars are compared whole. Changes are
+/// ordered by path.
+pub fn diff_account_data(previous: &Value, current: &Value) -> Vec<AccountFieldChange> {
+    let mut changes = Vec::new();
+    diff_into(String::new(), previous, current, &mut changes);
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+fn diff_into(
+    path: String,
+    previous: &Value,
+    current: &Value,
+    changes: &mut Vec<AccountFieldChange>,
+) {
+    match (previous, current) {
+        (Value::Object(previous_fields), Value::Object(current_fields)) => {
+            for (name, current_value) in current_fields {
+                let previous_value = previous_fields.get(name).unwrap_or(&Value::Null);
+                diff_into(join(&path, name), previous_value, current_value, changes);
+            }
+            for (name, previous_value) in previous_fields {
+                if !current_fields.contains_key(name) {
+                    changes.push(AccountFieldChange {
+                        path: join(&path, name),
+                        previous: previous_value.clone(),
+                        current: Value::Null,
+                    });
+                }
+            }
+        }
+        _ if previous != current => changes.push(AccountFieldChange {
+            path,
+            previous: previous.clone(),
+            current: current.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_diff_account_data() {
+        let previous = json!({
+            "bump": 254,
+            "fees": {"trade_fee_rate": 2500, "protocol_fee_rate": 120000},
+            "padding": [0, 0],
+            "retired": false
+        });
+        let current = json!({
+            "bump": 254,
+            "fees": {"trade_fee_rate": 3000, "protocol_fee_rate": 120000},
+            "padding": [0, 1],
+            "creator": "11111111111111111111111111111111"
+        });
+
+        let diff = diff_account_data(&previous, &current);
+        let paths: Vec<&str> = diff.iter().map(|change| change.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["creator", "fees.trade_fee_rate", "padding", "retired"]
+        );
+        assert_eq!(diff[0].previous, Value::Null);
+        assert_eq!(diff[1].previous, json!(2500));
+        assert_eq!(diff[1].current, json!(3000));
+        assert_eq!(diff[3].current, Value::Null);
+
+        assert!(diff_account_data(&current, &current).is_empty());
+        // A state of a different shape is one whole-state change
+        let diff = diff_account_data(&json!("Closed"), &current);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].path, "");
+    }
+}
@@ -156,6 +156,10 @@
 //!  "description": "This is the structured log format for a Solana Program log message.",
 //!  "type": "object",
 //!  "properties": {
+//!    "schema_version": {
+//!      "description": "The layout version of the record. 2 since decoded_events became objects; records without it are version 1, and are upgraded to 2 when deserialized.",
+//!      "type": "integer"
+//!    },
 //!    "log_messages": {
 //!      "description": "The log messages produced by the program, via the msg! or emit! macros. These logs being with 'Program log:'",
 //!      "type": "array",
@@ -170,6 +174,75 @@
 //!        "type": "string"
 //!      }
 //!    },
+//!    "decoded_events": {
+//!      "description": "Anchor events decoded from data_logs by an IDL-aware consumer such as sologger_idl_decoder.",
+//!      "type": "array",
+//!      "items": {
+//!        "type": "object",
+//!        "properties": {
+//!          "name": { "type": "string" },
+//!          "program": { "type": "string" },
+//!          "discriminator": { "description": "Hex-encoded discriminator (8 bytes for Anchor events)", "type": "string" },
+//!          "data": { "type": "object" },
+//!          "data_log_index": { "description": "Index into data_logs of the decoded payload", "type": "integer" },
+//!          "partial": { "description": "Lenient decoding stopped early; data holds the fields decoded before the failure", "type": "boolean" }
+//!        }
+//!      }
+//!    },
+//!    "decode_errors": {
+//!      "description": "Payloads that matched an event but did not decode cleanly, e.g. because the IDL no longer matches the program.",
+//!      "type": "array",
+//!      "items": {
+//!        "type": "object",
+//!        "properties": {
+//!          "program": { "type": "string" },
+//!          "event": { "type": ["string", "null"] },
+//!          "data_log_index": { "type": "integer" },
+//!          "offset": { "description": "Byte offset into the payload where decoding stopped", "type": ["integer", "null"] },
+//!          "field": { "type": ["string", "null"] },
+//!          "message": { "type": "string" }
+//!        }
+//!      }
+//!    },
+//!    "balance_changes": {
+//!      "description": "The SOL and token balance changes of the whole transaction, on the first LogContext of each transaction. Only filled when the transaction's status meta is available (getTransaction, blockSubscribe).",
+//!      "type": "array",
+//!      "items": {
+//!        "type": "object",
+//!        "properties": {
+//!          "account": { "description": "The account for SOL, the token account for SPL tokens", "type": "string" },
+//!          "mint": { "description": "The token mint; null for SOL", "type": ["string", "null"] },
+//!          "owner": { "description": "The owner of the token account; null for SOL", "type": ["string", "null"] },
+//!          "decimals": { "type": "integer" },
+//!          "amount_change": { "description": "Signed change in base units (lamports for SOL), as a decimal string", "type": "string" },
+//!          "ui_amount_change": { "description": "Signed change in UI units, as an exact decimal string", "type": "string" }
+//!        }
+//!      }
+//!    },
+//!    "fees": {
+//!      "description": "The fee, fee payer and compute budget of the whole transaction, on the first LogContext of each transaction. Filled alongside balance_changes.",
+//!      "type": ["object", "null"],
+//!      "properties": {
+//!        "fee_payer": { "type": "string" },
+//!        "fee": { "description": "Total fee in lamports", "type": "integer" },
+//!        "priority_fee": { "description": "CU price times CU limit, in lamports", "type": "integer" },
+//!        "cu_limit": { "description": "Requested with SetComputeUnitLimit", "type": ["integer", "null"] },
+//!        "cu_price": { "description": "Micro-lamports per CU, requested with SetComputeUnitPrice", "type": ["integer", "null"] },
+//!        "consumed_cu": { "description": "Compute units consumed by the whole transaction", "type": ["integer", "null"] }
+//!      }
+//!    },
+//!    "transaction_accounts": {
+//!      "description": "The signers and account keys of the whole transaction, on every LogContext of the transaction. Filled by the transformer for full transactions.",
+//!      "type": ["object", "null"],
+//!      "properties": {
+//!        "fee_payer": { "type": "string" },
+//!        "signers": { "type": "array", "items": { "type": "string" } },
+//!        "writable": { "description": "Lookup-table addresses included", "type": "array", "items": { "type": "string" } },
+//!        "readonly": { "description": "Lookup-table addresses included", "type": "array", "items": { "type": "string" } },
+//!        "loaded_writable": { "description": "Resolved from v0 address lookup tables", "type": "array", "items": { "type": "string" } },
+//!        "loaded_readonly": { "description": "Resolved from v0 address lookup tables", "type": "array", "items": { "type": "string" } }
+//!      }
+//!    },
 //!    "raw_logs": {
 //!      "description": "This is the raw log output from the program. This will contain all logs, regardless of prefix.",
 //!      "type": "array",
@@ -188,6 +261,17 @@
 //!      "description": "The transaction error produced by the program. This value is not parsed from the raw logs, but is provided by the RPC log subscription response as to why a transaction might be rejected.",
 //!      "type": "string"
 //!    },
+//!    "transaction_error_detail": {
+//!      "description": "The transaction error parsed into its type, set only on the invocation that failed.",
+//!      "type": ["object", "null"],
+//!      "properties": {
+//!        "error": { "description": "The RPC error, e.g. {\"InstructionError\":[1,{\"Custom\":6001}]}" },
+//!        "instruction_index": { "description": "The failed top-level instruction", "type": ["integer", "null"] },
+//!        "instruction_error": { "description": "The InstructionError variant, e.g. Custom", "type": ["string", "null"] },
+//!        "custom_code": { "type": ["integer", "null"] },
+//!        "message": { "description": "e.g. Instruction 1 failed: custom program error 6001 (0x1771)", "type": "string" }
+//!      }
+//!    },
 //!    "program_id": {
 //!      "description": "The program id of the program that produced the logs.",
 //!      "type": "string"
@@ -216,6 +300,18 @@
 //!      "description": "The period of time for which each leader ingests transactions and produces a block.",
 //!      "type": "integer"
 //!    },
+//!    "block_time": {
+//!      "description": "The block's estimated production time, as Unix seconds.",
+//!      "type": ["integer", "null"]
+//!    },
+//!    "transaction_index": {
+//!      "description": "The position of the transaction within its block, for block sources.",
+//!      "type": ["integer", "null"]
+//!    },
+//!    "received_at": {
+//!      "description": "When sologger received the transaction, as Unix milliseconds.",
+//!      "type": ["integer", "null"]
+//!    },
 //!    "signature": {
 //!      "description": "The signature of the transaction that invoked the program.",
 //!      "type": "string"
@@ -228,10 +324,13 @@ use wasm_bindgen::JsValue;
 use wasm_bindgen::prelude::wasm_bindgen;
 use crate::programs_selector::ProgramsSelector;
 use crate::sologger_log_context::LogContext;
+use crate::transaction_error::{attach_transaction_error, TransactionErrorDetail};
 
+pub mod account_change;
 pub mod programs_selector;
 mod rpc_response;
 pub mod sologger_log_context;
+pub mod transaction_error;
 
 #[wasm_bindgen]
 pub struct WasmLogParser {
@@ -248,14 +347,19 @@ impl WasmLogParser {
         }
     }
 
+    /// `transaction_error` is the RPC's JSON `err`, e.g. `{"InstructionError":[1,{"Custom":6001}]}`.
     pub fn parse_logs(&self, logs: Vec<String>, transaction_error: String, slot: u64, signature: String) -> JsValue {
-        let log_contexts = LogContext::parse_logs(
+        let detail = TransactionErrorDetail::from_json(&transaction_error);
+        let mut log_contexts = LogContext::parse_logs(
             &logs,
             transaction_error,
             &self.programs_selector,
             slot,
             signature,
         );
+        if let Some(detail) = detail {
+            attach_transaction_error(&mut log_contexts, detail);
+        }
         serde_wasm_bindgen::to_value(&log_contexts).unwrap()
     }
 }
@@ -1,5 +1,6 @@
 use crate::programs_selector::ProgramsSelector;
 use crate::rpc_response::RpcResponse;
+use crate::transaction_error::{attach_transaction_error, TransactionErrorDetail};
 use lazy_static::lazy_static;
 use log::{info, trace, warn};
 use regex::{Captures, Regex};
@@ -15,18 +16,185 @@ lazy_static! {
     static ref ERROR_CODE_PARSER: Regex = Regex::new(ERROR_CODE_REGEX).unwrap();
 }
 
+/// The version of the serialized LogContext layout, recorded in every record's
+/// schema_version. Version 2 carries decoded_events as structured objects; version 1
+/// records (no schema_version field) carried them as JSON strings.
+pub const LOG_CONTEXT_SCHEMA_VERSION: u32 = 2;
+
+/// An Anchor event decoded from one of a LogContext's data_logs.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct DecodedLogEvent {
+    ///The event name as declared in the IDL
+    pub name: String,
+    ///The program ID of the program that emitted the event
+    pub program: String,
+    ///The discriminator that prefixed the payload, hex-encoded: 8 bytes for Anchor events, any length for custom discriminators and user-defined layouts. Empty for events read from version 1 records
+    pub discriminator: String,
+    ///The decoded fields. u128/i128 values are decimal strings, pubkeys base58 strings and bytes base64 strings
+    pub data: serde_json::Value,
+    ///The index into data_logs of the payload the event was decoded from, or into log_messages when from_log_message is set
+    pub data_log_index: usize,
+    ///Set when the event was decoded from a base64 'Program log:' message, the way Anchor versions before 0.24 emitted events, rather than from a 'Program data:' log
+    #[serde(default)]
+    pub from_log_message: bool,
+    ///Set when lenient decoding stopped before the end of the event: data holds the fields decoded before the failure, which is recorded in the LogContext's decode_errors
+    #[serde(default)]
+    pub partial: bool,
+}
+
+/// A payload an IDL-aware consumer matched to an event (or could not read at all) but failed to decode, e.g. because the IDL no longer matches the program.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct LogDecodeError {
+    ///The program ID of the program that emitted the payload
+    pub program: String,
+    ///The event the payload's discriminator matched, if decoding got that far
+    pub event: Option<String>,
+    ///The index into data_logs of the payload, or into log_messages when from_log_message is set
+    pub data_log_index: usize,
+    ///Set when the payload was a base64 'Program log:' message rather than a 'Program data:' log
+    #[serde(default)]
+    pub from_log_message: bool,
+    ///The byte offset into the payload, discriminator included, where decoding stopped
+    pub offset: Option<usize>,
+    ///The top-level event field that failed to decode
+    pub field: Option<String>,
+    ///Why decoding failed
+    pub message: String,
+}
+
+/// A native SOL or SPL token balance changed by a transaction, from the pre/post balances in the transaction's status meta.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct BalanceChange {
+    ///The account whose balance changed: the account itself for SOL, the token account for SPL tokens
+    pub account: String,
+    ///The token mint, or None for native SOL
+    pub mint: Option<String>,
+    ///The owner of the token account, when the RPC reports it. None for SOL
+    pub owner: Option<String>,
+    ///The decimals of the token; 9 for SOL
+    pub decimals: u8,
+    ///The signed change in base units (lamports for SOL), as a decimal string
+    pub amount_change: String,
+    ///The signed change in UI units, as an exact decimal string such as "-1.5"
+    pub ui_amount_change: String,
+}
+
+impl BalanceChange {
+    /// The change from `pre` to `post` base units, or None when the balance is unchanged.
+    pub fn new(
+        account: String,
+        mint: Option<String>,
+        owner: Option<String>,
+        decimals: u8,
+        pre: u64,
+        post: u64,
+    ) -> Option<Self> {
+        if pre == post {
+            return None;
+        }
+        let change = post as i128 - pre as i128;
+        Some(Self {
+            account,
+            mint,
+            owner,
+            decimals,
+            amount_change: change.to_string(),
+            ui_amount_change: ui_amount(change, decimals),
+        })
+    }
+}
+
+/// What a transaction paid and asked for, from its status meta and Compute Budget instructions.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct TransactionFees {
+    ///The account that paid the fee: the transaction's first account key
+    pub fee_payer: String,
+    ///The total fee in lamports, base and priority fee included
+    pub fee: u64,
+    ///The priority fee in lamports: the CU price times the CU limit (the requested one, else 200,000 per non-Compute-Budget instruction, capped at 1,400,000), rounded up
+    pub priority_fee: u64,
+    ///The compute unit limit requested with SetComputeUnitLimit
+    pub cu_limit: Option<u32>,
+    ///The compute unit price in micro-lamports requested with SetComputeUnitPrice
+    pub cu_price: Option<u64>,
+    ///The compute units consumed by the whole transaction, when the RPC reports them
+    pub consumed_cu: Option<u64>,
+}
+
+/// The accounts a transaction touched, classified by how its message uses them.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct TransactionAccounts {
+    ///The account that paid the fee: the transaction's first account key
+    pub fee_payer: String,
+    ///The accounts that signed the transaction, fee payer first
+    pub signers: Vec<String>,
+    ///The accounts the message may write, lookup-table addresses included
+    pub writable: Vec<String>,
+    ///The accounts the message only reads, lookup-table addresses included
+    pub readonly: Vec<String>,
+    ///The writable addresses resolved from v0 address lookup tables
+    pub loaded_writable: Vec<String>,
+    ///The readonly addresses resolved from v0 address lookup tables
+    pub loaded_readonly: Vec<String>,
+}
+
+/// Renders base units as an exact decimal string with `decimals` places, trailing zeros trimmed.
+fn ui_amount(amount: i128, decimals: u8) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    let sign = if amount < 0 { "-" } else { "" };
+    if fraction.is_empty() {
+        format!("{}{}", sign, whole)
+    } else {
+        format!("{}{}.{}", sign, whole, fraction)
+    }
+}
+
 /// A LogContext is a structured log format that represents the logs of a single program invocation, per processed slot, transaction or block.
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
 pub struct LogContext {
+    ///The layout version of this record, see LOG_CONTEXT_SCHEMA_VERSION. Records without the field are version 1, and are upgraded to the current version when deserialized
+    #[serde(
+        default = "current_schema_version",
+        deserialize_with = "deserialize_schema_version"
+    )]
+    pub schema_version: u32,
     ///The log messages produced by the program, via the msg! or emit! macros. These logs being with 'Program log:'
     pub log_messages: Vec<String>,
     ///The data messages containing serialized data produced by the program, usually via the emit! or emit_cpi! macros provided by Anchor. These logs begin with 'Program data:'
     pub data_logs: Vec<String>,
-    ///Anchor events decoded from data_logs by an IDL-aware consumer such as sologger_idl_decoder. Each entry is a JSON string of the form {"name":"EventName","data":{...}}. Empty unless enrichment ran with an IDL registered for this program
+    ///Anchor events decoded from data_logs by an IDL-aware consumer such as sologger_idl_decoder. Empty unless enrichment ran with an IDL registered for this program. Version 1 records held JSON strings of the form {"name":"EventName","data":{...}}; those still deserialize
+    #[serde(default, deserialize_with = "deserialize_decoded_events")]
+    pub decoded_events: Vec<DecodedLogEvent>,
+    ///Payloads that matched an event but did not decode cleanly: malformed data, or (in strict and lenient modes) bytes left after the last field. Filled by IDL-aware consumers such as sologger_idl_decoder
     #[serde(default)]
-    pub decoded_events: Vec<String>,
+    pub decode_errors: Vec<LogDecodeError>,
+    ///Which IDL version decoded decoded_events and error_name, when the consumer holds several IDLs for this program over its upgrade history (selected by slot). The configured label, else the IDL's own version
+    #[serde(default)]
+    pub idl_version: Option<String>,
     ///The raw logs produced by the program, including all logs that do not match the other log types. These logs are not parsed and are provided as-is.
     pub raw_logs: Vec<String>,
+    ///The instruction data passed to this invocation, base58-encoded as the RPC returns it. Only filled by transformer paths that see the full transaction (getTransaction, blockSubscribe); log notifications do not carry it
+    #[serde(default)]
+    pub instruction_data: Option<String>,
+    ///The account keys passed to this invocation, in instruction order. Filled alongside instruction_data
+    #[serde(default)]
+    pub instruction_accounts: Vec<String>,
+    ///The instruction decoded from instruction_data by an IDL-aware consumer such as sologger_idl_decoder (including its native program decoders), as a JSON string of the form {"program":"spl-token","name":"transfer","args":{...}}
+    #[serde(default)]
+    pub decoded_instruction: Option<String>,
+    ///The SOL and token balance changes of the whole transaction. Only filled by transformer paths that see the transaction's status meta (getTransaction, blockSubscribe), and only on the first LogContext of each transaction
+    #[serde(default)]
+    pub balance_changes: Vec<BalanceChange>,
+    ///The fee, fee payer and compute budget of the whole transaction. Filled alongside balance_changes, on the first LogContext of each transaction
+    #[serde(default)]
+    pub fees: Option<TransactionFees>,
+    ///The signers and account keys of the whole transaction. Set on every LogContext of the transaction, so a search for failures signed by a wallet matches the failing invocation itself
+    #[serde(default)]
+    pub transaction_accounts: Option<TransactionAccounts>,
     ///The errors produced by the program. These include logs beginning with 'Program failed to complete:' (for example 'Program failed to complete: Invoked an instruction with data that is too large (12178014311288245306 > 10240)'), the error portion of 'Program <id> failed: <error>' logs, and system-program diagnostics such as 'Transfer: insufficient lamports 5628503, need 6799920'
     pub errors: Vec<String>,
     ///The numeric code parsed from a 'custom program error: 0x…' failure, if this program invocation failed with one. Anchor error codes start at 6000 (0x1770)
@@ -37,6 +205,9 @@ pub struct LogContext {
     pub error_name: Option<String>,
     ///The transaction error produced by the program. This value is not parsed from the raw logs, but is provided by the RPC log subscription response as to why a transaction might be rejected.
     pub transaction_error: String,
+    ///The transaction error parsed into its type, with the failing instruction and custom code. Set only on the invocation that failed, see transaction_error::attach_transaction_error
+    #[serde(default)]
+    pub transaction_error_detail: Option<TransactionErrorDetail>,
     ///The program ID of the program that produced the logs
     pub program_id: String,
     ///The program ID of the parent program that invoked the program that produced the logs
@@ -54,12 +225,71 @@ pub struct LogContext {
     pub invoke_result: String,
     ///The slot of the program invocation
     pub slot: usize,
+    ///The block's estimated production time, as Unix seconds. From the block for block sources and getTransaction, from a getBlockTime lookup for logsSubscribe when resolveBlockTime is enabled
+    #[serde(default)]
+    pub block_time: Option<i64>,
+    ///The position of the transaction within its block, for block sources
+    #[serde(default)]
+    pub transaction_index: Option<usize>,
+    ///When sologger received the transaction, as Unix milliseconds. Stamped as the notification arrives, before queueing or backfill, so received_at - block_time * 1000 is the ingest latency; replays keep the recorded time
+    #[serde(default)]
+    pub received_at: Option<u64>,
     ///The signature of the transaction that invoked the program that produced the logs
     pub signature: String,
     pub consumed_cu: u64,
     pub max_cu: u64,
 }
 
+fn current_schema_version() -> u32 {
+    LOG_CONTEXT_SCHEMA_VERSION
+}
+
+/// Older records only deserialize once deserialize_decoded_events has upgraded them, so
+/// what ends up in memory is the current layout. Newer versions are kept as they are.
+fn deserialize_schema_version<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    u32::deserialize(deserializer).map(|version| version.max(LOG_CONTEXT_SCHEMA_VERSION))
+}
+
+/// Accepts both the structured events of version 2 records and the JSON strings of
+/// version 1 records. Version 1 strings carry no program or discriminator, and their
+/// data_log_index is their position in the list.
+fn deserialize_decoded_events<'de, D>(deserializer: D) -> Result<Vec<DecodedLogEvent>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum AnyVersion {
+        Structured(DecodedLogEvent),
+        Legacy(String),
+    }
+
+    let events = Vec::<AnyVersion>::deserialize(deserializer)?;
+    events
+        .into_iter()
+        .enumerate()
+        .map(|(index, event)| match event {
+            AnyVersion::Structured(event) => Ok(event),
+            AnyVersion::Legacy(json) => {
+                let value: serde_json::Value =
+                    serde_json::from_str(&json).map_err(serde::de::Error::custom)?;
+                Ok(DecodedLogEvent {
+                    name: value["name"].as_str().unwrap_or_default().to_string(),
+                    program: "".to_string(),
+                    discriminator: "".to_string(),
+                    data: value["data"].clone(),
+                    data_log_index: index,
+                    from_log_message: false,
+                    partial: false,
+                })
+            }
+        })
+        .collect()
+}
+
 impl LogContext {
     /// Creates a new, empty LogContext
     pub fn new(
@@ -71,14 +301,27 @@ impl LogContext {
         signature: String,
     ) -> Self {
         Self {
+            schema_version: LOG_CONTEXT_SCHEMA_VERSION,
             log_messages: vec![],
             data_logs: vec![],
             decoded_events: vec![],
+            decode_errors: vec![],
+            idl_version: None,
             raw_logs: vec![],
+            instruction_data: None,
+            instruction_accounts: vec![],
+            decoded_instruction: None,
+            balance_changes: vec![],
+            fees: None,
+            transaction_accounts: None,
             errors: vec![],
             error_code: None,
             error_name: None,
             transaction_error: "".to_string(),
+            transaction_error_detail: None,
+            block_time: None,
+            transaction_index: None,
+            received_at: None,
             program_id,
             parent_program_id: "".to_string(),
             depth,
@@ -167,20 +410,7 @@ impl LogContext {
         programs_selector: &ProgramsSelector,
     ) -> Vec<LogContext> {
         let response: RpcResponse = serde_json::from_str(payload).unwrap();
-
-        Self::parse_logs(
-            &response.params.result.value.logs,
-            response
-                .params
-                .result
-                .value
-                .err
-                .unwrap_or(serde_json::Value::Null)
-                .to_string(),
-            programs_selector,
-            response.params.result.context.slot,
-            response.params.result.value.signature,
-        )
+        Self::from_rpc_response(response, programs_selector)
     }
 
     /// Parses the provided payload and returns a vector of LogContexts. The payload in this case is the raw JSON response as bytes from the Solana RPC log_subscription endpoint.
@@ -189,20 +419,23 @@ impl LogContext {
         programs_selector: &ProgramsSelector,
     ) -> Vec<LogContext> {
         let response: RpcResponse = serde_json::from_slice(payload).unwrap();
+        Self::from_rpc_response(response, programs_selector)
+    }
 
-        Self::parse_logs(
-            &response.params.result.value.logs,
-            response
-                .params
-                .result
-                .value
-                .err
-                .unwrap_or(serde_json::Value::Null)
-                .to_string(),
+    fn from_rpc_response(response: RpcResponse, programs_selector: &ProgramsSelector) -> Vec<LogContext> {
+        let value = response.params.result.value;
+        let transaction_error = value.err.unwrap_or(serde_json::Value::Null).to_string();
+        let mut log_contexts = Self::parse_logs(
+            &value.logs,
+            transaction_error.clone(),
             programs_selector,
             response.params.result.context.slot,
-            response.params.result.value.signature,
-        )
+            value.signature,
+        );
+        if let Some(detail) = TransactionErrorDetail::from_json(&transaction_error) {
+            attach_transaction_error(&mut log_contexts, detail);
+        }
+        log_contexts
     }
 
     /// Parses the provided logs and returns a vector of LogContexts.
@@ -214,6 +447,7 @@ impl LogContext {
     }
 
     /// Parses the provided logs and returns a vector of LogContexts. It may be provided with additional information from the Solana RPC response.
+    /// `transaction_error` is copied as is; callers holding the typed error set `transaction_error_detail` with `attach_transaction_error`.
     pub fn parse_logs(
         logs: &Vec<String>,
         transaction_error: String,
@@ -548,7 +782,10 @@ fn extract_error_code(err_str: &str) -> Option<u32> {
 #[cfg(test)]
 mod tests {
     use crate::programs_selector::ProgramsSelector;
-    use crate::sologger_log_context::{extract_compute_numbers, extract_error_code, LogContext};
+    use crate::sologger_log_context::{
+        extract_compute_numbers, extract_error_code, BalanceChange, DecodedLogEvent, LogContext,
+        LOG_CONTEXT_SCHEMA_VERSION,
+    };
     use std::time::SystemTime;
 
     //TODO fix test for ID
@@ -1661,4 +1898,84 @@ mod tests {
         let log = "consumed 100 of";
         assert_eq!(extract_compute_numbers(log), None);
     }
+
+    #[test]
+    fn test_balance_change_amounts() {
+        let sol = BalanceChange::new("Payer".to_string(), None, None, 9, 2_000_005_000, 500_000_000)
+            .unwrap();
+        assert_eq!(sol.amount_change, "-1500005000");
+        assert_eq!(sol.ui_amount_change, "-1.500005");
+
+        let usdc = BalanceChange::new(
+            "TokenAccount".to_string(),
+            Some("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string()),
+            Some("Owner".to_string()),
+            6,
+            0,
+            42,
+        )
+        .unwrap();
+        assert_eq!(usdc.ui_amount_change, "0.000042");
+        let whole = BalanceChange::new("A".to_string(), None, None, 0, 1, u64::MAX).unwrap();
+        assert_eq!(whole.ui_amount_change, "18446744073709551614");
+        assert_eq!(
+            BalanceChange::new("A".to_string(), None, None, 6, 3_000_000, 1_000_000)
+                .unwrap()
+                .ui_amount_change,
+            "-2"
+        );
+
+        assert!(BalanceChange::new("A".to_string(), None, None, 9, 7, 7).is_none());
+    }
+
+    #[test]
+    fn test_decoded_events_schema_versions() {
+        let mut log_context = LogContext::new(
+            "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C".to_string(),
+            1,
+            "id".to_string(),
+            0,
+            1,
+            "sig".to_string(),
+        );
+        log_context.decoded_events.push(DecodedLogEvent {
+            name: "SwapEvent".to_string(),
+            program: log_context.program_id.clone(),
+            discriminator: "40c6cde8260871e2".to_string(),
+            data: serde_json::json!({"input_amount": 1000}),
+            data_log_index: 0,
+            from_log_message: false,
+            partial: false,
+        });
+
+        // Version 2 records carry the events as objects and round-trip
+        let value: serde_json::Value = serde_json::from_str(&log_context.to_json()).unwrap();
+        assert_eq!(value["schema_version"], LOG_CONTEXT_SCHEMA_VERSION);
+        assert_eq!(value["decoded_events"][0]["data"]["input_amount"], 1000);
+        let round_trip: LogContext = serde_json::from_str(&log_context.to_json()).unwrap();
+        assert_eq!(round_trip, log_context);
+
+        // Version 1 records (no schema_version, events as JSON strings) still load, upgraded
+        let mut legacy = value;
+        legacy.as_object_mut().unwrap().remove("schema_version");
+        legacy["decoded_events"] = serde_json::json!([
+            r#"{"name":"SwapEvent","data":{"input_amount":1000}}"#
+        ]);
+        let mut explicit_v1 = legacy.clone();
+        explicit_v1["schema_version"] = serde_json::json!(1);
+        let legacy: LogContext = serde_json::from_value(legacy).unwrap();
+        assert_eq!(legacy.schema_version, LOG_CONTEXT_SCHEMA_VERSION);
+        assert_eq!(legacy.decoded_events[0].name, "SwapEvent");
+        assert_eq!(legacy.decoded_events[0].data, serde_json::json!({"input_amount": 1000}));
+        assert!(legacy.decoded_events[0].discriminator.is_empty());
+        let explicit_v1: LogContext = serde_json::from_value(explicit_v1).unwrap();
+        assert_eq!(explicit_v1, legacy);
+
+        // The upgraded record serializes as the current version and round-trips
+        let upgraded: serde_json::Value = serde_json::from_str(&legacy.to_json()).unwrap();
+        assert_eq!(upgraded["schema_version"], LOG_CONTEXT_SCHEMA_VERSION);
+        assert!(upgraded["decoded_events"][0].is_object());
+        let round_trip: LogContext = serde_json::from_str(&legacy.to_json()).unwrap();
+        assert_eq!(round_trip, legacy);
+    }
 }
@@ -62,7 +62,7 @@ pub mod log_context_transformer_wasm;
 use crate::log_context_transformer_wasm::{
     from_rpc_logs_response, from_rpc_response, Response, RpcLogsResponse,
 };
-use sologger_idl_decoder::{decode_event, Idl, IdlRegistry};
+use sologger_idl_decoder::{decode_event, DecodeMode, Idl, IdlRegistry, LayoutSchema};
 use sologger_log_context::programs_selector::ProgramsSelector;
 use wasm_bindgen::prelude::*;
 
@@ -93,13 +93,40 @@ impl WasmLogContextTransformer {
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// Registers a user-defined borsh layout (JSON) for a program without an Anchor IDL.
+    #[wasm_bindgen]
+    pub fn add_layout(&mut self, program_id: String, layout_json: String) -> Result<(), JsValue> {
+        let layout =
+            LayoutSchema::from_json(&layout_json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.idl_registry.insert_layout(program_id, layout);
+        Ok(())
+    }
+
+    /// Also decodes events that Anchor versions before 0.24 emitted as base64
+    /// 'Program log:' messages. Off by default.
+    #[wasm_bindgen]
+    pub fn set_decode_log_message_events(&mut self, enabled: bool) {
+        self.idl_registry.set_log_message_events(enabled);
+    }
+
+    /// How payloads that match an event but don't fit its layout are handled:
+    /// "standard" (default), "strict" or "lenient". Failures are reported in each
+    /// LogContext's decode_errors.
+    #[wasm_bindgen]
+    pub fn set_decode_mode(&mut self, mode: String) -> Result<(), JsValue> {
+        let mode: DecodeMode = serde_json::from_value(serde_json::Value::String(mode))
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.idl_registry.set_decode_mode(mode);
+        Ok(())
+    }
+
     #[wasm_bindgen]
     pub fn from_rpc_response(&self, response: JsValue) -> Result<JsValue, JsValue> {
         let response: Response<RpcLogsResponse> = serde_wasm_bindgen::from_value(response)?;
         let mut result = from_rpc_response(&response, &self.program_selector)
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
         self.idl_registry.enrich_all(&mut result);
-        Ok(serde_wasm_bindgen::to_value(&result)?)
+        to_js(&result)
     }
 
     #[wasm_bindgen]
@@ -112,28 +139,30 @@ impl WasmLogContextTransformer {
         let mut result = from_rpc_logs_response(&rpc_logs_response, slot, &self.program_selector)
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
         self.idl_registry.enrich_all(&mut result);
-        Ok(serde_wasm_bindgen::to_value(&result)?)
+        to_js(&result)
     }
 }
 
+/// Serializes LogContexts for JS. json_compatible so decoded event data (serde_json
+/// objects) become plain JS objects rather than JS Maps.
+fn to_js<T: serde::Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(JsValue::from)
+}
+
 /// Decodes a single base64 'Program data:' payload against an IDL, without constructing
 /// a transformer. Returns {name, data} for a recognized event, or null when the payload
 /// matches no event in the IDL. Throws on malformed IDL JSON or a corrupt payload.
 #[wasm_bindgen]
 pub fn decode_program_data(idl_json: String, data_base64: String) -> Result<JsValue, JsValue> {
-    use serde::Serialize;
-
     let idl = Idl::from_json(&idl_json).map_err(|e| JsValue::from_str(&e.to_string()))?;
     let decoded =
         decode_event(&idl, &data_base64).map_err(|e| JsValue::from_str(&e.to_string()))?;
     match decoded {
         None => Ok(JsValue::NULL),
         Some(event) => {
-            let value = serde_json::json!({ "name": event.name, "data": event.data });
-            // json_compatible: JSON objects become plain JS objects rather than JS Maps
-            value
-                .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
-                .map_err(JsValue::from)
+            to_js(&serde_json::json!({ "name": event.name, "data": event.data }))
         }
     }
 }
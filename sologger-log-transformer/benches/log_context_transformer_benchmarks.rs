@@ -1,4 +1,5 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
 use solana_rpc_client_api::response::{Response, RpcLogsResponse, RpcResponseContext};
 use solana_sdk::signature::Signature;
 use solana_transaction_status::{
@@ -8,7 +9,7 @@ use solana_transaction_status::{
 };
 use solana_transaction_status::option_serializer::OptionSerializer;
 use sologger_log_context::programs_selector::ProgramsSelector;
-use sologger_log_transformer::log_context_transformer::{from_encoded_confirmed_block, from_rpc_logs_response, from_rpc_response, from_ui_confirmed_block};
+use sologger_log_transformer::log_context_transformer::{from_encoded_confirmed_block, from_encoded_transaction, from_rpc_logs_response, from_rpc_response, from_ui_confirmed_block};
 
 fn create_mock_ui_confirmed_block() -> UiConfirmedBlock {
     let signature = Signature::new_unique();
@@ -113,6 +114,75 @@ fn create_mock_encoded_confirmed_block() -> EncodedConfirmedBlock {
     }
 }
 
+/// A transaction with a typical mix of CPIs and program logs, for the large-block benchmark.
+fn create_mock_busy_transaction() -> EncodedTransactionWithStatusMeta {
+    let ui_transaction = UiTransaction {
+        signatures: vec![Signature::new_unique().to_string()],
+        message: UiMessage::Parsed(UiParsedMessage {
+            account_keys: vec![],
+            recent_blockhash: "".to_string(),
+            instructions: vec![],
+            address_table_lookups: None,
+        }),
+    };
+    let transaction_status_meta = UiTransactionStatusMeta {
+        err: None,
+        status: Ok(()),
+        fee: 5000,
+        pre_balances: vec![],
+        post_balances: vec![],
+        inner_instructions: OptionSerializer::None,
+        log_messages: OptionSerializer::Some(vec![
+            "Program ComputeBudget111111111111111111111111111111 invoke [1]".to_string(),
+            "Program ComputeBudget111111111111111111111111111111 success".to_string(),
+            "Program CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C invoke [1]".to_string(),
+            "Program log: Instruction: SwapBaseInput".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]".to_string(),
+            "Program log: Instruction: TransferChecked".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA consumed 6147 of 180000 compute units".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [2]".to_string(),
+            "Program log: Instruction: TransferChecked".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA consumed 6238 of 170000 compute units".to_string(),
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA success".to_string(),
+            "Program data: QMbN6CYIceIFjsjRmRFv1/c3ONcMfohbhIpavzPVz8bSo3P0rcESk2Q=".to_string(),
+            "Program CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C consumed 41266 of 200000 compute units".to_string(),
+            "Program CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C success".to_string(),
+        ]),
+        pre_token_balances: OptionSerializer::None,
+        post_token_balances: OptionSerializer::None,
+        rewards: OptionSerializer::None,
+        loaded_addresses: OptionSerializer::None,
+        return_data: OptionSerializer::None,
+        compute_units_consumed: OptionSerializer::Some(47413),
+        cost_units: OptionSerializer::None,
+    };
+    EncodedTransactionWithStatusMeta {
+        transaction: EncodedTransaction::Json(ui_transaction),
+        meta: Some(transaction_status_meta),
+        version: None,
+    }
+}
+
+/// A block as delivered by blockSubscribe with `RpcBlockSubscribeFilter::All`.
+fn create_mock_large_ui_confirmed_block(transaction_count: usize) -> UiConfirmedBlock {
+    UiConfirmedBlock {
+        previous_blockhash: "".to_string(),
+        blockhash: "".to_string(),
+        parent_slot: 0,
+        transactions: Some(
+            (0..transaction_count)
+                .map(|_| create_mock_busy_transaction())
+                .collect(),
+        ),
+        signatures: None,
+        rewards: None,
+        num_reward_partitions: Some(1),
+        block_time: None,
+        block_height: None,
+    }
+}
+
 fn create_mock_rpc_logs_response() -> RpcLogsResponse {
     RpcLogsResponse {
         signature: "pF5oPR8R4vJwU2KeQm8BAAGYcTiikZkpJAmP8TuuVztkL2K6wZhxVKy9t6jSCMSpMMD3VE6Qek1YL5JAFvuBLQw".to_string(),
@@ -136,6 +206,49 @@ fn bench_from_ui_confirmed_block(c: &mut Criterion) {
     });
 }
 
+/// Large blocks: the parallel `from_ui_confirmed_block` against parsing the same
+/// transactions one after another, as it did before.
+fn bench_large_ui_confirmed_block(c: &mut Criterion) {
+    let program_selector = ProgramsSelector::new_all_programs();
+    let mut group = c.benchmark_group("large_ui_confirmed_block");
+    for transaction_count in [500, 3000] {
+        let block = create_mock_large_ui_confirmed_block(transaction_count);
+        group.throughput(Throughput::Elements(transaction_count as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("sequential", transaction_count),
+            &block,
+            |b, block| {
+                b.iter(|| {
+                    let block = black_box(block.clone());
+                    let mut log_contexts = Vec::new();
+                    for tx in block.transactions.unwrap().iter() {
+                        log_contexts.extend(
+                            from_encoded_transaction(tx, black_box(219907401), &program_selector)
+                                .unwrap(),
+                        );
+                    }
+                    log_contexts
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("from_ui_confirmed_block", transaction_count),
+            &block,
+            |b, block| {
+                b.iter(|| {
+                    from_ui_confirmed_block(
+                        black_box(block.clone()),
+                        black_box(219907401),
+                        black_box(&program_selector),
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
 fn bench_from_rpc_logs_response(c: &mut Criterion) {
     let rpc_logs_response = create_mock_rpc_logs_response();
     let program_selector = ProgramsSelector::new_all_programs();
@@ -170,6 +283,7 @@ fn bench_from_rpc_response(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_from_ui_confirmed_block,
+    bench_large_ui_confirmed_block,
     bench_from_rpc_logs_response,
     bench_from_rpc_response
 );
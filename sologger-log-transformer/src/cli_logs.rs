@@ -0,0 +1,191 @@
+//! The text printed by `solana logs`, grouped back into transactions:
+//!
+//! ```text
+//! Streaming transaction logs. Confirmed commitment
+//! Transaction executed in slot 5:
+//!   Signature: 5h6xBEauJ3PK6SWCZ1PGjBvj8vDdWG3KpwATGy1ARAXFSDwt8GFXM7W5Ncn16wmqokgpiKRLuS83KUxyZyv2sUYv
+//!   Status: Error processing Instruction 0: custom program error: 0x1
+//!   Log Messages:
+//!     Program 11111111111111111111111111111111 invoke [1]
+//!     Program 11111111111111111111111111111111 failed: custom program error: 0x1
+//! ```
+//!
+//! The parser is fed one line at a time, so the output can be tailed from a pipe. A
+//! transaction is complete when the next one starts, or when the caller decides the
+//! stream has gone quiet and calls [`CliLogsParser::finish`].
+
+use sologger_log_context::transaction_error::{
+    InstructionError, TransactionError, TransactionErrorDetail,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One transaction as printed by `solana logs`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CliTransaction {
+    pub slot: u64,
+    pub signature: String,
+    /// The printed status; empty for `Ok`
+    pub status: String,
+    pub logs: Vec<String>,
+    /// When the parser read the transaction's first line, as Unix milliseconds
+    pub received_at: Option<u64>,
+}
+
+impl CliTransaction {
+    /// The status as a typed error, for the one shape whose text maps back unambiguously:
+    /// a custom program error, e.g. `Error processing Instruction 0: custom program
+    /// error: 0x1`. Other errors are only available as text.
+    pub fn error_detail(&self) -> Option<TransactionErrorDetail> {
+        let (index, reason) = self
+            .status
+            .strip_prefix("Error processing Instruction ")?
+            .split_once(": ")?;
+        let code = reason.strip_prefix("custom program error: 0x")?;
+        let error = TransactionError::InstructionError(
+            index.parse().ok()?,
+            InstructionError::Custom(u32::from_str_radix(code, 16).ok()?),
+        );
+        Some(error.into())
+    }
+}
+
+/// Groups `solana logs` output into [`CliTransaction`]s, one line at a time.
+#[derive(Debug, Default)]
+pub struct CliLogsParser {
+    current: Option<CliTransaction>,
+    /// Past the `Log Messages:` line of the current transaction
+    in_logs: bool,
+}
+
+impl CliLogsParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds one line. Returns the previous transaction when this line starts the next.
+    /// Lines outside a transaction, like the `Streaming transaction logs` banner, are
+    /// ignored.
+    pub fn push_line(&mut self, line: &str) -> Option<CliTransaction> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if let Some(slot) = header_slot(line) {
+            self.in_logs = false;
+            let received_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|elapsed| elapsed.as_millis() as u64);
+            return self.current.replace(CliTransaction {
+                slot,
+                received_at,
+                ..Default::default()
+            });
+        }
+        let current = self.current.as_mut()?;
+        let field = line.trim_start();
+        if self.in_logs && line.starts_with("    ") {
+            current.logs.push(line[4..].to_string());
+        } else if let Some(signature) = field.strip_prefix("Signature: ") {
+            current.signature = signature.trim().to_string();
+        } else if let Some(status) = field.strip_prefix("Status: ") {
+            current.status = match status.trim() {
+                "Ok" => String::new(),
+                error => error.to_string(),
+            };
+        } else if field == "Log Messages:" {
+            self.in_logs = true;
+        }
+        None
+    }
+
+    /// Takes the transaction in progress, at the end of the input or when it goes quiet.
+    pub fn finish(&mut self) -> Option<CliTransaction> {
+        self.in_logs = false;
+        self.current.take()
+    }
+}
+
+/// The slot of a `Transaction executed in slot <slot>:` line.
+fn header_slot(line: &str) -> Option<u64> {
+    line.strip_prefix("Transaction executed in slot ")?
+        .strip_suffix(':')?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = "Streaming transaction logs. Confirmed commitment
+Transaction executed in slot 5:
+  Signature: 5h6xBEauJ3PK6SWCZ1PGjBvj8vDdWG3KpwATGy1ARAXFSDwt8GFXM7W5Ncn16wmqokgpiKRLuS83KUxyZyv2sUYv
+  Status: Ok
+  Log Messages:
+    Program 11111111111111111111111111111111 invoke [1]
+    Program 11111111111111111111111111111111 success
+Transaction executed in slot 6:
+  Signature: 4eSszcNtqqdZq59S7WaxHsq4E4bNmBh9Dcrf9DT6ta1C1d3mN6QeUW3f5Qp7f4n8yG7wXiJdVe8e2tbPyX7hKqGE
+  Status: Error processing Instruction 0: custom program error: 0x1771
+  Log Messages:
+    Program CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C invoke [1]
+    Program log: AnchorError occurred. Error Code: Slippage. Error Number: 6001.
+    Program CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C failed: custom program error: 0x1771
+";
+
+    fn parse(output: &str) -> Vec<CliTransaction> {
+        let mut parser = CliLogsParser::new();
+        let mut transactions: Vec<CliTransaction> = output
+            .lines()
+            .filter_map(|line| parser.push_line(line))
+            .collect();
+        transactions.extend(parser.finish());
+        transactions
+    }
+
+    #[test]
+    fn test_groups_output_per_transaction() {
+        let transactions = parse(OUTPUT);
+        assert_eq!(transactions.len(), 2);
+
+        assert_eq!(transactions[0].slot, 5);
+        assert!(transactions[0].signature.starts_with("5h6xBEau"));
+        assert_eq!(transactions[0].status, "");
+        assert_eq!(
+            transactions[0].logs,
+            vec![
+                "Program 11111111111111111111111111111111 invoke [1]",
+                "Program 11111111111111111111111111111111 success"
+            ]
+        );
+        assert_eq!(transactions[0].error_detail(), None);
+        // Stamped when the header was read
+        assert!(transactions[0].received_at.is_some_and(|ms| ms > 1_700_000_000_000));
+
+        assert_eq!(transactions[1].slot, 6);
+        assert_eq!(
+            transactions[1].status,
+            "Error processing Instruction 0: custom program error: 0x1771"
+        );
+        assert_eq!(transactions[1].logs.len(), 3);
+        let detail = transactions[1].error_detail().unwrap();
+        assert_eq!(detail.instruction_index, Some(0));
+        assert_eq!(detail.custom_code, Some(6001));
+    }
+
+    #[test]
+    fn test_untyped_errors_and_stray_lines() {
+        let output = "Transaction executed in slot 9:\r
+  Signature: sig\r
+  Status: Blockhash not found\r
+  Log Messages:\r
+not part of the transaction\r
+";
+        let transactions = parse(output);
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].status, "Blockhash not found");
+        assert!(transactions[0].logs.is_empty());
+        assert_eq!(transactions[0].error_detail(), None);
+
+        // Lines before the first header have no transaction to go to
+        assert!(parse("  Signature: orphan\n").is_empty());
+    }
+}
@@ -13,6 +13,9 @@
 //!
 //!Please see the sologger-log-context crate for more information regarding LogContext.
 
+pub mod cli_logs;
 pub mod log_context_transformer;
+#[cfg(feature = "yellowstone")]
+pub mod yellowstone_proto;
 
 //TODO provide error mapping
@@ -1,15 +1,25 @@
 use solana_rpc_client_api::response::{Response, RpcLogsResponse};
-
+use solana_sdk::message::compiled_instruction::CompiledInstruction;
+use solana_sdk::message::{MessageHeader, VersionedMessage};
 
 use solana_transaction_status::{
     ConfirmedBlock, EncodedConfirmedBlock,
     EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction,
     EncodedTransactionWithStatusMeta, TransactionWithStatusMeta, UiConfirmedBlock,
-    VersionedConfirmedBlock, VersionedTransactionWithStatusMeta,
+    TransactionStatusMeta, UiCompiledInstruction, UiInstruction, UiMessage, UiParsedInstruction,
+    UiTransactionStatusMeta, VersionedConfirmedBlock, VersionedTransactionWithStatusMeta,
 };
 
+use rayon::prelude::*;
 use sologger_log_context::programs_selector::ProgramsSelector;
-use sologger_log_context::sologger_log_context::LogContext;
+use crate::cli_logs::CliTransaction;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::parse_accounts::{ParsedAccount, ParsedAccountSource};
+use solana_transaction_status::{TransactionTokenBalance, UiTransactionTokenBalance};
+use sologger_log_context::sologger_log_context::{
+    BalanceChange, LogContext, TransactionAccounts, TransactionFees,
+};
+use sologger_log_context::transaction_error::{attach_transaction_error, TransactionErrorDetail};
 
 /// Extracts log messages from a VersionedConfirmedBlock and returns a vector of LogContexts
 pub fn from_version_confirmed_block(
@@ -17,15 +27,9 @@ pub fn from_version_confirmed_block(
     slot: u64,
     program_selector: &ProgramsSelector,
 ) -> anyhow::Result<Vec<LogContext>> {
-    let mut block_log_contexts = Vec::new();
-    block.transactions.iter().for_each(|tx| {
-        let result = from_versioned_transaction(tx, slot, program_selector)
-            .expect("Error processing logs for block");
-        for log in result {
-            block_log_contexts.push(log);
-        }
-    });
-    Ok(block_log_contexts)
+    parse_block_transactions(&block.transactions, block.block_time, |tx| {
+        from_versioned_transaction(tx, slot, program_selector)
+    })
 }
 
 /// Extracts log messages from a ConfirmedBlock and returns a vector of LogContexts
@@ -34,15 +38,9 @@ pub fn from_confirmed_block(
     slot: u64,
     program_selector: &ProgramsSelector,
 ) -> anyhow::Result<Vec<LogContext>> {
-    let mut block_log_contexts = Vec::new();
-    block.transactions.iter().for_each(|tx| {
-        let result =
-            from_transaction(tx, slot, program_selector).expect("Error processing logs for block");
-        for log in result {
-            block_log_contexts.push(log);
-        }
-    });
-    Ok(block_log_contexts)
+    parse_block_transactions(&block.transactions, block.block_time, |tx| {
+        from_transaction(tx, slot, program_selector)
+    })
 }
 
 /// Extracts log messages from a EncodedConfirmedBlock and returns a vector of LogContexts
@@ -51,32 +49,22 @@ pub fn from_encoded_confirmed_block(
     slot: u64,
     program_selector: &ProgramsSelector,
 ) -> anyhow::Result<Vec<LogContext>> {
-    let mut block_log_contexts = Vec::new();
-    block.transactions.iter().for_each(|tx| {
-        let result = from_encoded_transaction(tx, slot, program_selector)
-            .expect("Error processing logs for block");
-        for log in result {
-            block_log_contexts.push(log);
-        }
-    });
-    Ok(block_log_contexts)
+    parse_block_transactions(&block.transactions, block.block_time, |tx| {
+        from_encoded_transaction(tx, slot, program_selector)
+    })
 }
 
-/// Extracts log messages from a UiConfirmedBlock and returns a vector of LogContexts
+/// Extracts log messages from a UiConfirmedBlock and returns a vector of LogContexts.
+/// Large blocks are parsed in parallel; the output keeps transaction order.
 pub fn from_ui_confirmed_block(
     block: UiConfirmedBlock,
     slot: u64,
     program_selector: &ProgramsSelector,
 ) -> anyhow::Result<Vec<LogContext>> {
-    let mut block_log_contexts = Vec::new();
-    block.transactions.unwrap().iter().for_each(|tx| {
-        let result = from_encoded_transaction(tx, slot, program_selector)
-            .expect("Error processing logs for block");
-        for log in result {
-            block_log_contexts.push(log);
-        }
-    });
-    Ok(block_log_contexts)
+    let transactions = block.transactions.unwrap();
+    parse_block_transactions(&transactions, block.block_time, |tx| {
+        from_encoded_transaction(tx, slot, program_selector)
+    })
 }
 
 /// Extracts log messages from a EncodedTransactionWithStatusMeta and returns a vector of LogContexts
@@ -89,7 +77,7 @@ pub fn from_encoded_transaction(
     let logs: Option<Vec<String>> = tx.meta.to_owned().unwrap().log_messages.into();
     let logs = logs.unwrap_or(vec![]);
     let te = tx.meta.to_owned().unwrap().err;
-    let transaction_error = match te {
+    let transaction_error = match &te {
         None => "".to_string(),
         Some(err) => {
             format!("{}", err)
@@ -106,13 +94,24 @@ pub fn from_encoded_transaction(
     };
 
     // let signature = tx.transaction.decode().unwrap().signatures[0];
-    let log_contexts = LogContext::parse_logs(
+    let mut log_contexts = LogContext::parse_logs(
         &logs,
         transaction_error,
         program_selector,
         slot,
         signature.to_string(),
     );
+    attach_error_detail(&mut log_contexts, te.as_ref());
+    if let Some(meta) = &tx.meta {
+        let (account_keys, instructions) = encoded_message(inner_tx, meta);
+        attach_invocations(
+            &mut log_contexts,
+            invocations_from_encoded(&account_keys, &instructions, meta),
+        );
+        attach_balance_changes(&mut log_contexts, balance_changes_from_ui(&account_keys, meta));
+        attach_fees(&mut log_contexts, fees_from_ui(&account_keys, &instructions, meta));
+        attach_transaction_accounts(&mut log_contexts, accounts_from_encoded(inner_tx, meta));
+    }
     for sologger_log_context in log_contexts {
         block_log_contexts.push(sologger_log_context);
     }
@@ -129,7 +128,7 @@ pub fn from_encoded_confirmed_transaction(
     let logs: Option<Vec<String>> = tx.transaction.meta.to_owned().unwrap().log_messages.into();
     let logs = logs.unwrap_or(vec![]);
     let te = tx.transaction.to_owned().meta.unwrap().err;
-    let transaction_error = match te {
+    let transaction_error = match &te {
         None => "".to_string(),
         Some(err) => {
             format!("{}", err)
@@ -145,8 +144,20 @@ pub fn from_encoded_confirmed_transaction(
         _ => "".to_string(),
     };
 
-    let log_contexts =
+    let mut log_contexts =
         LogContext::parse_logs(&logs, transaction_error, program_selector, slot, signature);
+    attach_error_detail(&mut log_contexts, te.as_ref());
+    if let Some(meta) = &tx.transaction.meta {
+        let (account_keys, instructions) = encoded_message(inner_tx, meta);
+        attach_invocations(
+            &mut log_contexts,
+            invocations_from_encoded(&account_keys, &instructions, meta),
+        );
+        attach_balance_changes(&mut log_contexts, balance_changes_from_ui(&account_keys, meta));
+        attach_fees(&mut log_contexts, fees_from_ui(&account_keys, &instructions, meta));
+        attach_transaction_accounts(&mut log_contexts, accounts_from_encoded(inner_tx, meta));
+    }
+    attach_block_position(&mut log_contexts, tx.block_time, None);
     for sologger_log_context in log_contexts {
         block_log_contexts.push(sologger_log_context);
     }
@@ -163,20 +174,30 @@ pub fn from_transaction(
     let logs: Option<Vec<String>> = tx.get_status_meta().unwrap().log_messages;
     let logs = logs.unwrap_or(vec![]);
     let te = tx.get_status_meta().to_owned().unwrap().status.err();
-    let transaction_error = match te {
+    let transaction_error = match &te {
         None => "".to_string(),
         Some(err) => {
             format!("{}", err)
         }
     };
     let signature = tx.transaction_signature().to_string();
-    let log_contexts = LogContext::parse_logs(
+    let mut log_contexts = LogContext::parse_logs(
         &logs,
         transaction_error,
         program_selector,
         slot,
         signature.to_string(),
     );
+    attach_error_detail(&mut log_contexts, te.as_ref());
+    if let Some(meta) = tx.get_status_meta() {
+        let message = &tx.get_transaction().message;
+        let account_keys = account_keys_from_message(message, &meta);
+        let invocations = invocations_from_message(message, &account_keys, &meta);
+        attach_invocations(&mut log_contexts, invocations);
+        attach_balance_changes(&mut log_contexts, balance_changes_from_meta(&account_keys, &meta));
+        attach_fees(&mut log_contexts, fees_from_message(message, &account_keys, &meta));
+        attach_transaction_accounts(&mut log_contexts, accounts_from_message(message, &meta));
+    }
     for sologger_log_context in log_contexts {
         block_log_contexts.push(sologger_log_context);
     }
@@ -193,26 +214,52 @@ pub fn from_versioned_transaction(
     let logs: Option<Vec<String>> = tx.meta.to_owned().log_messages;
     let logs = logs.unwrap_or(vec![]);
     let te = tx.meta.to_owned().status.err();
-    let transaction_error = match te {
+    let transaction_error = match &te {
         None => "".to_string(),
         Some(err) => {
             format!("{}", err)
         }
     };
     let signature = tx.transaction.signatures[0];
-    let log_contexts = LogContext::parse_logs(
+    let mut log_contexts = LogContext::parse_logs(
         &logs,
         transaction_error,
         program_selector,
         slot,
         signature.to_string(),
     );
+    attach_error_detail(&mut log_contexts, te.as_ref());
+    let (message, meta) = (&tx.transaction.message, &tx.meta);
+    let account_keys = account_keys_from_message(message, meta);
+    attach_invocations(
+        &mut log_contexts,
+        invocations_from_message(message, &account_keys, meta),
+    );
+    attach_balance_changes(&mut log_contexts, balance_changes_from_meta(&account_keys, meta));
+    attach_fees(&mut log_contexts, fees_from_message(message, &account_keys, meta));
+    attach_transaction_accounts(&mut log_contexts, accounts_from_message(message, meta));
     for sologger_log_context in log_contexts {
         block_log_contexts.push(sologger_log_context);
     }
     Ok(block_log_contexts)
 }
 
+/// Extracts log messages from a Yellowstone gRPC transaction update and returns a vector of LogContexts
+#[cfg(feature = "yellowstone")]
+pub fn from_yellowstone_transaction(
+    update: &crate::yellowstone_proto::SubscribeUpdateTransaction,
+    program_selector: &ProgramsSelector,
+) -> anyhow::Result<Vec<LogContext>> {
+    let info = update
+        .transaction
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("transaction update without a transaction"))?;
+    let tx = crate::yellowstone_proto::versioned_transaction_with_status_meta(info)?;
+    let mut log_contexts = from_versioned_transaction(&tx, update.slot, program_selector)?;
+    attach_block_position(&mut log_contexts, None, Some(info.index as usize));
+    Ok(log_contexts)
+}
+
 /// Extracts log messages from a Response<RpcLogsResponse> and returns a vector of LogContexts
 pub fn from_rpc_response(
     response: &Response<RpcLogsResponse>,
@@ -226,13 +273,14 @@ pub fn from_rpc_response(
     };
 
     let sig = response.value.signature.to_string();
-    let log_contexts = LogContext::parse_logs(
+    let mut log_contexts = LogContext::parse_logs(
         &response.value.logs,
         transaction_error,
         program_selector,
         response.context.slot,
         sig,
     );
+    attach_error_detail(&mut log_contexts, response.value.err.as_ref());
 
     Ok(log_contexts)
 }
@@ -251,17 +299,662 @@ pub fn from_rpc_logs_response(
     };
 
     let sig = rpc_logs_response.signature.to_string();
-    let log_contexts = LogContext::parse_logs(
+    let mut log_contexts = LogContext::parse_logs(
         &rpc_logs_response.logs,
         transaction_error,
         program_selector,
         slot,
         sig,
     );
+    attach_error_detail(&mut log_contexts, rpc_logs_response.err.as_ref());
+
+    Ok(log_contexts)
+}
+
+/// Extracts log messages from a transaction printed by `solana logs` and returns a vector of
+/// LogContexts. Custom program errors are typed; other errors are kept as text.
+pub fn from_cli_transaction(
+    transaction: &CliTransaction,
+    program_selector: &ProgramsSelector,
+) -> anyhow::Result<Vec<LogContext>> {
+    let mut log_contexts = LogContext::parse_logs(
+        &transaction.logs,
+        transaction.status.clone(),
+        program_selector,
+        transaction.slot,
+        transaction.signature.clone(),
+    );
+    if let Some(detail) = transaction.error_detail() {
+        attach_transaction_error(&mut log_contexts, detail);
+    }
+    for log_context in log_contexts.iter_mut() {
+        log_context.received_at = transaction.received_at;
+    }
 
     Ok(log_contexts)
 }
 
+/// Blocks with fewer transactions are parsed on the calling thread: handing them to the
+/// thread pool costs more than it saves.
+const PARALLEL_BLOCK_THRESHOLD: usize = 64;
+
+/// Parses every transaction of a block, spread over the rayon thread pool for large
+/// blocks, and returns the LogContexts in transaction order, or the error of the first
+/// transaction that fails to parse.
+fn parse_block_transactions<T: Sync>(
+    transactions: &[T],
+    block_time: Option<i64>,
+    parse: impl Fn(&T) -> anyhow::Result<Vec<LogContext>> + Sync,
+) -> anyhow::Result<Vec<LogContext>> {
+    let parse_at = |(index, tx): (usize, &T)| {
+        let mut result = parse(tx).map_err(|err| {
+            err.context(format!("Error processing logs for block transaction {}", index))
+        })?;
+        attach_block_position(&mut result, block_time, Some(index));
+        Ok(result)
+    };
+    let per_transaction: Vec<Vec<LogContext>> = if transactions.len() < PARALLEL_BLOCK_THRESHOLD {
+        transactions.iter().enumerate().map(parse_at).collect::<anyhow::Result<_>>()?
+    } else {
+        transactions.par_iter().enumerate().map(parse_at).collect::<anyhow::Result<_>>()?
+    };
+    Ok(per_transaction.into_iter().flatten().collect())
+}
+
+/// Sets when and where in its block a transaction landed on all of its LogContexts.
+fn attach_block_position(
+    log_contexts: &mut [LogContext],
+    block_time: Option<i64>,
+    transaction_index: Option<usize>,
+) {
+    for log_context in log_contexts.iter_mut() {
+        log_context.block_time = block_time;
+        log_context.transaction_index = transaction_index;
+    }
+}
+
+/// Sets the typed error on the invocation that failed. The Solana error types serialize to
+/// the RPC's JSON form, which sologger_log_context parses into its own mirror of them.
+fn attach_error_detail<E: serde::Serialize>(log_contexts: &mut [LogContext], err: Option<&E>) {
+    let detail = err
+        .and_then(|err| serde_json::to_string(err).ok())
+        .and_then(|err| TransactionErrorDetail::from_json(&err));
+    if let Some(detail) = detail {
+        attach_transaction_error(log_contexts, detail);
+    }
+}
+
+/// A program invocation recovered from a full transaction.
+struct Invocation {
+    program_id: String,
+    accounts: Vec<String>,
+    ///Base58 instruction data. None for instructions the RPC returned jsonParsed
+    data: Option<String>,
+}
+
+/// Copies instruction data and accounts onto the LogContexts they belong to. Both lists are
+/// in execution order, so each LogContext takes the next invocation of its program; the
+/// invocations of programs filtered out by the ProgramsSelector are skipped over.
+fn attach_invocations(log_contexts: &mut [LogContext], invocations: Vec<Invocation>) {
+    let mut invocations = invocations.into_iter();
+    for log_context in log_contexts.iter_mut() {
+        let Some(invocation) = invocations
+            .by_ref()
+            .find(|invocation| invocation.program_id == log_context.program_id)
+        else {
+            break;
+        };
+        log_context.instruction_data = invocation.data;
+        log_context.instruction_accounts = invocation.accounts;
+    }
+}
+
+/// The account keys (lookup-table addresses included) and top-level instructions of an
+/// encoded transaction. An `accounts`-detail transaction has keys but no instructions.
+fn encoded_message(
+    transaction: &EncodedTransaction,
+    meta: &UiTransactionStatusMeta,
+) -> (Vec<String>, Vec<UiInstruction>) {
+    match transaction {
+        EncodedTransaction::Json(ui_transaction) => match &ui_transaction.message {
+            UiMessage::Raw(message) => {
+                let mut account_keys = message.account_keys.clone();
+                if let Some(loaded) = meta.loaded_addresses.as_ref().map(|loaded| loaded.clone()) {
+                    account_keys.extend(loaded.writable);
+                    account_keys.extend(loaded.readonly);
+                }
+                let instructions = message
+                    .instructions
+                    .iter()
+                    .cloned()
+                    .map(UiInstruction::Compiled)
+                    .collect();
+                (account_keys, instructions)
+            }
+            // jsonParsed account keys already include the lookup-table addresses
+            UiMessage::Parsed(message) => (
+                message
+                    .account_keys
+                    .iter()
+                    .map(|account| account.pubkey.clone())
+                    .collect(),
+                message.instructions.clone(),
+            ),
+        },
+        EncodedTransaction::Accounts(accounts) => (
+            accounts
+                .account_keys
+                .iter()
+                .map(|account| account.pubkey.clone())
+                .collect(),
+            vec![],
+        ),
+        _ => {
+            let Some(versioned) = transaction.decode() else {
+                return (vec![], vec![]);
+            };
+            let mut account_keys: Vec<String> = versioned
+                .message
+                .static_account_keys()
+                .iter()
+                .map(|key| key.to_string())
+                .collect();
+            if let Some(loaded) = meta.loaded_addresses.as_ref().map(|loaded| loaded.clone()) {
+                account_keys.extend(loaded.writable);
+                account_keys.extend(loaded.readonly);
+            }
+            let instructions = versioned
+                .message
+                .instructions()
+                .iter()
+                .map(|instruction| {
+                    UiInstruction::Compiled(UiCompiledInstruction {
+                        program_id_index: instruction.program_id_index,
+                        accounts: instruction.accounts.clone(),
+                        data: bs58::encode(&instruction.data).into_string(),
+                        stack_height: None,
+                    })
+                })
+                .collect();
+            (account_keys, instructions)
+        }
+    }
+}
+
+/// Lists the invocations of an encoded transaction in execution order: each top-level
+/// instruction followed by its inner (CPI) instructions.
+fn invocations_from_encoded(
+    account_keys: &[String],
+    top_level: &[UiInstruction],
+    meta: &UiTransactionStatusMeta,
+) -> Vec<Invocation> {
+    let inner_instructions: Option<Vec<_>> = meta.inner_instructions.clone().into();
+    let inner_instructions = inner_instructions.unwrap_or_default();
+
+    let mut invocations = Vec::new();
+    for (index, instruction) in top_level.iter().enumerate() {
+        invocations.extend(invocation_from_ui(instruction, account_keys));
+        for inner in inner_instructions
+            .iter()
+            .filter(|inner| inner.index as usize == index)
+        {
+            for instruction in &inner.instructions {
+                invocations.extend(invocation_from_ui(instruction, account_keys));
+            }
+        }
+    }
+    invocations
+}
+
+fn invocation_from_ui(instruction: &UiInstruction, account_keys: &[String]) -> Option<Invocation> {
+    match instruction {
+        UiInstruction::Compiled(compiled) => Some(Invocation {
+            program_id: account_keys
+                .get(compiled.program_id_index as usize)?
+                .clone(),
+            accounts: compiled
+                .accounts
+                .iter()
+                .filter_map(|index| account_keys.get(*index as usize).cloned())
+                .collect(),
+            data: Some(compiled.data.clone()),
+        }),
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => Some(Invocation {
+            program_id: decoded.program_id.clone(),
+            accounts: decoded.accounts.clone(),
+            data: Some(decoded.data.clone()),
+        }),
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => Some(Invocation {
+            program_id: parsed.program_id.clone(),
+            accounts: vec![],
+            data: None,
+        }),
+    }
+}
+
+/// The account keys of a decoded transaction, lookup-table addresses included.
+fn account_keys_from_message(message: &VersionedMessage, meta: &TransactionStatusMeta) -> Vec<String> {
+    let mut account_keys: Vec<String> = message
+        .static_account_keys()
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+    account_keys.extend(meta.loaded_addresses.writable.iter().map(|key| key.to_string()));
+    account_keys.extend(meta.loaded_addresses.readonly.iter().map(|key| key.to_string()));
+    account_keys
+}
+
+/// Lists the invocations of a decoded transaction in execution order.
+fn invocations_from_message(
+    message: &VersionedMessage,
+    account_keys: &[String],
+    meta: &TransactionStatusMeta,
+) -> Vec<Invocation> {
+
+    let to_invocation = |instruction: &CompiledInstruction| {
+        Some(Invocation {
+            program_id: account_keys
+                .get(instruction.program_id_index as usize)?
+                .clone(),
+            accounts: instruction
+                .accounts
+                .iter()
+                .filter_map(|index| account_keys.get(*index as usize).cloned())
+                .collect(),
+            data: Some(bs58::encode(&instruction.data).into_string()),
+        })
+    };
+
+    let inner_instructions = meta.inner_instructions.as_deref().unwrap_or_default();
+    let mut invocations = Vec::new();
+    for (index, instruction) in message.instructions().iter().enumerate() {
+        invocations.extend(to_invocation(instruction));
+        for inner in inner_instructions
+            .iter()
+            .filter(|inner| inner.index as usize == index)
+        {
+            for inner_instruction in &inner.instructions {
+                invocations.extend(to_invocation(&inner_instruction.instruction));
+            }
+        }
+    }
+    invocations
+}
+
+/// A token balance from pre/post_token_balances, whichever status meta it came from.
+struct TokenBalance {
+    account_index: usize,
+    mint: String,
+    owner: Option<String>,
+    decimals: u8,
+    amount: u64,
+}
+
+impl From<&UiTransactionTokenBalance> for TokenBalance {
+    fn from(balance: &UiTransactionTokenBalance) -> Self {
+        let owner: Option<String> = balance.owner.clone().into();
+        Self {
+            account_index: balance.account_index as usize,
+            mint: balance.mint.clone(),
+            owner,
+            decimals: balance.ui_token_amount.decimals,
+            amount: balance.ui_token_amount.amount.parse().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&TransactionTokenBalance> for TokenBalance {
+    fn from(balance: &TransactionTokenBalance) -> Self {
+        Self {
+            account_index: balance.account_index as usize,
+            mint: balance.mint.clone(),
+            owner: (!balance.owner.is_empty()).then(|| balance.owner.clone()),
+            decimals: balance.ui_token_amount.decimals,
+            amount: balance.ui_token_amount.amount.parse().unwrap_or_default(),
+        }
+    }
+}
+
+/// Puts a transaction's balance changes on its first LogContext.
+fn attach_balance_changes(log_contexts: &mut [LogContext], balance_changes: Vec<BalanceChange>) {
+    if let Some(first) = log_contexts.first_mut() {
+        first.balance_changes = balance_changes;
+    }
+}
+
+fn balance_changes_from_ui(account_keys: &[String], meta: &UiTransactionStatusMeta) -> Vec<BalanceChange> {
+    let token_balances = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>| match balances {
+        OptionSerializer::Some(balances) => balances.iter().map(TokenBalance::from).collect(),
+        _ => vec![],
+    };
+    balance_changes(
+        account_keys,
+        &meta.pre_balances,
+        &meta.post_balances,
+        token_balances(&meta.pre_token_balances),
+        token_balances(&meta.post_token_balances),
+    )
+}
+
+fn balance_changes_from_meta(account_keys: &[String], meta: &TransactionStatusMeta) -> Vec<BalanceChange> {
+    let token_balances = |balances: &Option<Vec<TransactionTokenBalance>>| {
+        balances
+            .iter()
+            .flatten()
+            .map(TokenBalance::from)
+            .collect()
+    };
+    balance_changes(
+        account_keys,
+        &meta.pre_balances,
+        &meta.post_balances,
+        token_balances(&meta.pre_token_balances),
+        token_balances(&meta.post_token_balances),
+    )
+}
+
+/// The non-zero SOL changes in account order, then the token changes. A token account
+/// missing from the pre balances was created by the transaction, one missing from the post
+/// balances was closed by it.
+fn balance_changes(
+    account_keys: &[String],
+    pre_balances: &[u64],
+    post_balances: &[u64],
+    pre_tokens: Vec<TokenBalance>,
+    post_tokens: Vec<TokenBalance>,
+) -> Vec<BalanceChange> {
+    let mut changes: Vec<BalanceChange> = pre_balances
+        .iter()
+        .zip(post_balances)
+        .zip(account_keys)
+        .filter_map(|((pre, post), account)| {
+            BalanceChange::new(account.clone(), None, None, 9, *pre, *post)
+        })
+        .collect();
+
+    let same_account =
+        |a: &TokenBalance, b: &TokenBalance| a.account_index == b.account_index && a.mint == b.mint;
+    for post in &post_tokens {
+        let pre = pre_tokens.iter().find(|pre| same_account(pre, post));
+        let Some(account) = account_keys.get(post.account_index) else {
+            continue;
+        };
+        changes.extend(BalanceChange::new(
+            account.clone(),
+            Some(post.mint.clone()),
+            post.owner.clone(),
+            post.decimals,
+            pre.map_or(0, |pre| pre.amount),
+            post.amount,
+        ));
+    }
+    for pre in &pre_tokens {
+        if post_tokens.iter().any(|post| same_account(pre, post)) {
+            continue;
+        }
+        let Some(account) = account_keys.get(pre.account_index) else {
+            continue;
+        };
+        changes.extend(BalanceChange::new(
+            account.clone(),
+            Some(pre.mint.clone()),
+            pre.owner.clone(),
+            pre.decimals,
+            pre.amount,
+            0,
+        ));
+    }
+    changes
+}
+
+const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+/// The CU limit granted per instruction when the transaction does not request one
+const DEFAULT_INSTRUCTION_CU_LIMIT: u64 = 200_000;
+const MAX_CU_LIMIT: u64 = 1_400_000;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// Puts a transaction's fees on its first LogContext, next to its balance changes.
+fn attach_fees(log_contexts: &mut [LogContext], fees: Option<TransactionFees>) {
+    if let Some(first) = log_contexts.first_mut() {
+        first.fees = fees;
+    }
+}
+
+fn fees_from_ui(
+    account_keys: &[String],
+    top_level: &[UiInstruction],
+    meta: &UiTransactionStatusMeta,
+) -> Option<TransactionFees> {
+    let instructions = top_level
+        .iter()
+        .filter_map(|instruction| invocation_from_ui(instruction, account_keys))
+        .map(|invocation| {
+            let data = invocation
+                .data
+                .and_then(|data| bs58::decode(data).into_vec().ok())
+                .unwrap_or_default();
+            (invocation.program_id, data)
+        })
+        .collect();
+    let consumed_cu = match meta.compute_units_consumed {
+        OptionSerializer::Some(consumed_cu) => Some(consumed_cu),
+        _ => None,
+    };
+    transaction_fees(account_keys, meta.fee, consumed_cu, instructions)
+}
+
+fn fees_from_message(
+    message: &VersionedMessage,
+    account_keys: &[String],
+    meta: &TransactionStatusMeta,
+) -> Option<TransactionFees> {
+    let instructions = message
+        .instructions()
+        .iter()
+        .filter_map(|instruction| {
+            let program_id = account_keys.get(instruction.program_id_index as usize)?;
+            Some((program_id.clone(), instruction.data.clone()))
+        })
+        .collect();
+    transaction_fees(account_keys, meta.fee, meta.compute_units_consumed, instructions)
+}
+
+/// Reads the requested CU limit and price from the top-level Compute Budget instructions
+/// (program id, raw data) and prices the priority fee the way the runtime does. None when
+/// the transaction has no account keys, i.e. it was fetched without transaction details.
+fn transaction_fees(
+    account_keys: &[String],
+    fee: u64,
+    consumed_cu: Option<u64>,
+    top_level: Vec<(String, Vec<u8>)>,
+) -> Option<TransactionFees> {
+    let fee_payer = account_keys.first()?.clone();
+    let mut cu_limit = None;
+    let mut cu_price = None;
+    let mut other_instructions: u64 = 0;
+    for (program_id, data) in &top_level {
+        if program_id != COMPUTE_BUDGET_PROGRAM_ID {
+            other_instructions += 1;
+            continue;
+        }
+        match data.split_first() {
+            Some((2, limit)) if limit.len() >= 4 => {
+                cu_limit = Some(u32::from_le_bytes(limit[..4].try_into().unwrap()));
+            }
+            Some((3, price)) if price.len() >= 8 => {
+                cu_price = Some(u64::from_le_bytes(price[..8].try_into().unwrap()));
+            }
+            _ => {}
+        }
+    }
+
+    let effective_limit = match cu_limit {
+        Some(cu_limit) => u64::from(cu_limit),
+        None => (other_instructions * DEFAULT_INSTRUCTION_CU_LIMIT).min(MAX_CU_LIMIT),
+    };
+    let priority_fee = (u128::from(cu_price.unwrap_or_default()) * u128::from(effective_limit))
+        .div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+
+    Some(TransactionFees {
+        fee_payer,
+        fee,
+        priority_fee: u64::try_from(priority_fee).unwrap_or(u64::MAX),
+        cu_limit,
+        cu_price,
+        consumed_cu,
+    })
+}
+
+/// One account key of a transaction and how its message uses it.
+struct AccountKey {
+    pubkey: String,
+    signer: bool,
+    writable: bool,
+    ///Resolved from an address lookup table
+    loaded: bool,
+}
+
+/// Puts the transaction's accounts on every one of its LogContexts.
+fn attach_transaction_accounts(
+    log_contexts: &mut [LogContext],
+    transaction_accounts: Option<TransactionAccounts>,
+) {
+    for log_context in log_contexts.iter_mut() {
+        log_context.transaction_accounts = transaction_accounts.clone();
+    }
+}
+
+fn accounts_from_encoded(
+    transaction: &EncodedTransaction,
+    meta: &UiTransactionStatusMeta,
+) -> Option<TransactionAccounts> {
+    let loaded = || {
+        meta.loaded_addresses
+            .as_ref()
+            .map(|loaded| loaded_account_keys(loaded.writable.clone(), loaded.readonly.clone()))
+            .unwrap_or_default()
+    };
+    let keys = match transaction {
+        EncodedTransaction::Json(ui_transaction) => match &ui_transaction.message {
+            UiMessage::Raw(message) => {
+                let mut keys = static_account_keys(message.account_keys.clone(), &message.header);
+                keys.extend(loaded());
+                keys
+            }
+            UiMessage::Parsed(message) => parsed_account_keys(&message.account_keys),
+        },
+        EncodedTransaction::Accounts(accounts) => parsed_account_keys(&accounts.account_keys),
+        _ => {
+            let versioned = transaction.decode()?;
+            let pubkeys = versioned
+                .message
+                .static_account_keys()
+                .iter()
+                .map(|key| key.to_string())
+                .collect();
+            let mut keys = static_account_keys(pubkeys, versioned.message.header());
+            keys.extend(loaded());
+            keys
+        }
+    };
+    transaction_accounts(keys)
+}
+
+fn accounts_from_message(
+    message: &VersionedMessage,
+    meta: &TransactionStatusMeta,
+) -> Option<TransactionAccounts> {
+    let pubkeys = message
+        .static_account_keys()
+        .iter()
+        .map(|key| key.to_string())
+        .collect();
+    let mut keys = static_account_keys(pubkeys, message.header());
+    keys.extend(loaded_account_keys(
+        meta.loaded_addresses.writable.iter().map(|key| key.to_string()).collect(),
+        meta.loaded_addresses.readonly.iter().map(|key| key.to_string()).collect(),
+    ));
+    transaction_accounts(keys)
+}
+
+/// Classifies the static keys by the message header: signed keys come first, then
+/// unsigned ones, each group with its readonly keys last.
+fn static_account_keys(pubkeys: Vec<String>, header: &MessageHeader) -> Vec<AccountKey> {
+    let signed = header.num_required_signatures as usize;
+    let writable_signed = signed.saturating_sub(header.num_readonly_signed_accounts as usize);
+    let writable_unsigned = pubkeys
+        .len()
+        .saturating_sub(header.num_readonly_unsigned_accounts as usize);
+    pubkeys
+        .into_iter()
+        .enumerate()
+        .map(|(index, pubkey)| AccountKey {
+            pubkey,
+            signer: index < signed,
+            writable: if index < signed {
+                index < writable_signed
+            } else {
+                index < writable_unsigned
+            },
+            loaded: false,
+        })
+        .collect()
+}
+
+fn loaded_account_keys(writable: Vec<String>, readonly: Vec<String>) -> Vec<AccountKey> {
+    let key = |writable| {
+        move |pubkey| AccountKey {
+            pubkey,
+            signer: false,
+            writable,
+            loaded: true,
+        }
+    };
+    writable
+        .into_iter()
+        .map(key(true))
+        .chain(readonly.into_iter().map(key(false)))
+        .collect()
+}
+
+/// jsonParsed and accounts-detail transactions already classify their keys.
+fn parsed_account_keys(accounts: &[ParsedAccount]) -> Vec<AccountKey> {
+    accounts
+        .iter()
+        .map(|account| AccountKey {
+            pubkey: account.pubkey.clone(),
+            signer: account.signer,
+            writable: account.writable,
+            loaded: account.source == Some(ParsedAccountSource::LookupTable),
+        })
+        .collect()
+}
+
+/// None when the transaction has no account keys, i.e. it was fetched without transaction
+/// details.
+fn transaction_accounts(keys: Vec<AccountKey>) -> Option<TransactionAccounts> {
+    let mut accounts = TransactionAccounts {
+        fee_payer: keys.first()?.pubkey.clone(),
+        ..Default::default()
+    };
+    for key in keys {
+        if key.signer {
+            accounts.signers.push(key.pubkey.clone());
+        }
+        match (key.writable, key.loaded) {
+            (true, true) => accounts.loaded_writable.push(key.pubkey.clone()),
+            (false, true) => accounts.loaded_readonly.push(key.pubkey.clone()),
+            _ => {}
+        }
+        if key.writable {
+            accounts.writable.push(key.pubkey);
+        } else {
+            accounts.readonly.push(key.pubkey);
+        }
+    }
+    Some(accounts)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -269,17 +962,18 @@ mod tests {
 
     use solana_rpc_client::rpc_client::RpcClient;
     use solana_rpc_client_api::config::{CommitmentConfig, RpcBlockConfig, RpcTransactionConfig};
-    use solana_rpc_client_api::response::{Response, RpcLogsResponse, RpcResponseContext, UiTransactionError};
+    use solana_rpc_client_api::response::{Response, RpcLogsResponse, RpcResponseContext, UiTokenAmount, UiTransactionError};
     use solana_sdk::clock::UnixTimestamp;
     use solana_sdk::instruction::InstructionError;
     use solana_sdk::message::Message;
     use solana_sdk::signature::{Keypair, Signature, Signer};
     use solana_sdk::transaction::{Transaction, TransactionError, VersionedTransaction};
     use solana_transaction_status::option_serializer::OptionSerializer;
-    use solana_transaction_status::{ConfirmedBlock, EncodedConfirmedBlock, EncodedTransaction, EncodedTransactionWithStatusMeta, TransactionDetails, TransactionStatusMeta, UiConfirmedBlock, UiMessage, UiParsedMessage, UiRawMessage, UiTransaction, UiTransactionEncoding, UiTransactionStatusMeta, VersionedConfirmedBlock, VersionedTransactionWithStatusMeta};
+    use solana_transaction_status::{EncodedConfirmedBlock, EncodedTransaction, EncodedTransactionWithStatusMeta, TransactionDetails, TransactionStatusMeta, UiConfirmedBlock, UiMessage, UiParsedMessage, UiCompiledInstruction, UiInnerInstructions, UiInstruction, UiLoadedAddresses, UiRawMessage, UiTransaction, UiTransactionEncoding, UiTransactionStatusMeta, UiTransactionTokenBalance, VersionedConfirmedBlock, VersionedTransactionWithStatusMeta};
 
-    use crate::log_context_transformer::{from_confirmed_block, from_encoded_confirmed_block, from_encoded_confirmed_transaction, from_encoded_transaction, from_rpc_logs_response, from_rpc_response, from_ui_confirmed_block, from_version_confirmed_block};
+    use crate::log_context_transformer::{from_confirmed_block, from_encoded_confirmed_block, from_encoded_confirmed_transaction, from_encoded_transaction, from_rpc_logs_response, from_rpc_response, from_ui_confirmed_block, from_version_confirmed_block, parse_block_transactions};
     use sologger_log_context::programs_selector::ProgramsSelector;
+    use sologger_log_context::sologger_log_context::LogContext;
 
     #[test]
     fn test_block() {
@@ -413,6 +1107,36 @@ mod tests {
         assert_eq!(logs_contexts.len(), 1);
     }
 
+    #[test]
+    pub fn test_parse_cli_transaction() {
+        use crate::cli_logs::CliTransaction;
+        use crate::log_context_transformer::from_cli_transaction;
+
+        let transaction = CliTransaction {
+            slot: 6,
+            signature: "pF5oPR8R4vJwU2KeQm8BAAGYcTiikZkpJAmP8TuuVztkL2K6wZhxVKy9t6jSCMSpMMD3VE6Qek1YL5JAFvuBLQw".to_string(),
+            status: "Error processing Instruction 1: custom program error: 0x1771".to_string(),
+            logs: vec![
+                "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+                "Program 11111111111111111111111111111111 success".to_string(),
+                "Program CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C invoke [1]".to_string(),
+                "Program CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C failed: custom program error: 0x1771".to_string(),
+            ],
+            received_at: Some(1_700_000_000_123),
+        };
+
+        let log_contexts =
+            from_cli_transaction(&transaction, &ProgramsSelector::new_all_programs()).unwrap();
+        assert_eq!(log_contexts.len(), 2);
+        assert_eq!(log_contexts[1].slot, 6);
+        assert_eq!(log_contexts[1].signature, transaction.signature);
+        assert_eq!(log_contexts[1].transaction_error, transaction.status);
+        assert!(log_contexts[0].transaction_error_detail.is_none());
+        let detail = log_contexts[1].transaction_error_detail.as_ref().unwrap();
+        assert_eq!(detail.custom_code, Some(6001));
+        assert_eq!(log_contexts[0].received_at, Some(1_700_000_000_123));
+    }
+
     #[test]
     pub fn test_parse_rpc_response() {
         let rpc_logs_response = RpcLogsResponse {
@@ -472,7 +1196,7 @@ mod tests {
             transactions: vec![transaction_with_status_meta],
             rewards: vec![],
             num_partitions: Some(1),
-            block_time: Some(UnixTimestamp::default()),
+            block_time: Some(1_700_000_000),
             block_height: Some(100),
         };
         let logs_contexts = from_version_confirmed_block(
@@ -482,6 +1206,10 @@ mod tests {
         )
         .unwrap();
         assert_eq!(logs_contexts.len(), 1);
+        assert_eq!(logs_contexts[0].block_time, Some(1_700_000_000));
+        assert_eq!(logs_contexts[0].transaction_index, Some(0));
+        // Stamped by the ingesting binary, not the transformer
+        assert_eq!(logs_contexts[0].received_at, None);
     }
 
     #[test]
@@ -544,6 +1272,88 @@ mod tests {
         assert_eq!(logs_contexts.len(), 1);
     }
 
+    #[test]
+    pub fn test_large_block_keeps_transaction_order() {
+        let transaction = |signature: &Signature| EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Json(UiTransaction {
+                signatures: vec![signature.to_string()],
+                message: UiMessage::Parsed(UiParsedMessage {
+                    account_keys: vec![],
+                    recent_blockhash: "".to_string(),
+                    instructions: vec![],
+                    address_table_lookups: None,
+                }),
+            }),
+            meta: Some(UiTransactionStatusMeta {
+                err: None,
+                status: Ok(()),
+                fee: 0,
+                pre_balances: vec![],
+                post_balances: vec![],
+                inner_instructions: OptionSerializer::None,
+                log_messages: OptionSerializer::Some(vec![
+                    "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+                    "Program 11111111111111111111111111111111 success".to_string(),
+                ]),
+                pre_token_balances: OptionSerializer::None,
+                post_token_balances: OptionSerializer::None,
+                rewards: OptionSerializer::None,
+                loaded_addresses: OptionSerializer::None,
+                return_data: OptionSerializer::None,
+                compute_units_consumed: OptionSerializer::None,
+                cost_units: OptionSerializer::None,
+            }),
+            version: None,
+        };
+        // Well above the threshold, so the block is parsed on the thread pool
+        let signatures: Vec<Signature> = (0..500).map(|_| Signature::new_unique()).collect();
+        let block = UiConfirmedBlock {
+            previous_blockhash: "".to_string(),
+            blockhash: "".to_string(),
+            parent_slot: 0,
+            transactions: Some(signatures.iter().map(transaction).collect()),
+            signatures: None,
+            rewards: None,
+            num_reward_partitions: None,
+            block_time: Some(1_700_000_000),
+            block_height: None,
+        };
+
+        let log_contexts =
+            from_ui_confirmed_block(block, 123, &ProgramsSelector::new_all_programs()).unwrap();
+        assert_eq!(log_contexts.len(), signatures.len());
+        for (index, (log_context, signature)) in log_contexts.iter().zip(&signatures).enumerate() {
+            assert_eq!(log_context.signature, signature.to_string());
+            assert_eq!(log_context.transaction_index, Some(index));
+            assert_eq!(log_context.block_time, Some(1_700_000_000));
+        }
+    }
+
+    #[test]
+    pub fn test_block_transaction_error_is_returned() {
+        let parse = |index: &usize| {
+            if *index == 40 {
+                anyhow::bail!("bad transaction");
+            }
+            Ok(vec![LogContext::new(
+                "11111111111111111111111111111111".to_string(),
+                1,
+                "1".to_string(),
+                0,
+                0,
+                index.to_string(),
+            )])
+        };
+        // Below and above the threshold: the error is returned, not a panic on the pool
+        for len in [50, 500] {
+            let transactions: Vec<usize> = (0..len).collect();
+            let err = parse_block_transactions(&transactions, None, parse).unwrap_err();
+            assert!(format!("{:#}", err).contains("bad transaction"));
+        }
+        let transactions: Vec<usize> = (0..30).collect();
+        assert_eq!(parse_block_transactions(&transactions, None, parse).unwrap().len(), 30);
+    }
+
     // Test for error cases in from_encoded_transaction
     #[test]
     fn test_from_encoded_transaction_error_cases() {
@@ -591,5 +1401,471 @@ mod tests {
         assert_eq!(result.len(), 0);
         // You might want to add more assertions here to check the error handling
     }
-    
+
+    #[test]
+    fn test_encoded_transaction_attaches_instructions() {
+        let signature = Signature::new_unique();
+        let ui_raw_message = UiRawMessage {
+            header: Default::default(),
+            account_keys: vec![
+                "Payer111".to_string(),
+                "Dest111".to_string(),
+                "ComputeBudget111111111111111111111111111111".to_string(),
+                "9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7".to_string(),
+            ],
+            recent_blockhash: "".to_string(),
+            instructions: vec![
+                UiCompiledInstruction {
+                    program_id_index: 2,
+                    accounts: vec![],
+                    data: "K1FDJ7".to_string(),
+                    stack_height: None,
+                },
+                UiCompiledInstruction {
+                    program_id_index: 3,
+                    accounts: vec![0, 1],
+                    data: "3Bxs".to_string(),
+                    stack_height: None,
+                },
+            ],
+            address_table_lookups: None,
+        };
+        let ui_transaction = UiTransaction {
+            signatures: vec![signature.to_string()],
+            message: UiMessage::Raw(ui_raw_message),
+        };
+        let transaction_status_meta = UiTransactionStatusMeta {
+            err: None,
+            status: Ok(()),
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            inner_instructions: OptionSerializer::Some(vec![UiInnerInstructions {
+                index: 1,
+                instructions: vec![UiInstruction::Compiled(UiCompiledInstruction {
+                    program_id_index: 4,
+                    accounts: vec![0, 1],
+                    data: "3Bxs4h24hBtQy9rw".to_string(),
+                    stack_height: Some(2),
+                })],
+            }]),
+            log_messages: OptionSerializer::Some(vec![
+                "Program ComputeBudget111111111111111111111111111111 invoke [1]".to_string(),
+                "Program ComputeBudget111111111111111111111111111111 success".to_string(),
+                "Program 9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7 invoke [1]".to_string(),
+                "Program 11111111111111111111111111111111 invoke [2]".to_string(),
+                "Program 11111111111111111111111111111111 success".to_string(),
+                "Program 9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7 success".to_string(),
+            ]),
+            pre_token_balances: OptionSerializer::None,
+            post_token_balances: OptionSerializer::None,
+            rewards: OptionSerializer::None,
+            loaded_addresses: OptionSerializer::Some(UiLoadedAddresses {
+                writable: vec![],
+                readonly: vec!["11111111111111111111111111111111".to_string()],
+            }),
+            return_data: OptionSerializer::None,
+            compute_units_consumed: OptionSerializer::None,
+            cost_units: OptionSerializer::None,
+        };
+        let transaction = EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Json(ui_transaction),
+            meta: Some(transaction_status_meta),
+            version: None,
+        };
+
+        let result =
+            from_encoded_transaction(&transaction, 123, &ProgramsSelector::new_all_programs())
+                .unwrap();
+
+        assert_eq!(result.len(), 3);
+        assert_eq!(result[0].instruction_data.as_deref(), Some("K1FDJ7"));
+        assert!(result[0].instruction_accounts.is_empty());
+        assert_eq!(result[1].program_id, "9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7");
+        assert_eq!(result[1].instruction_data.as_deref(), Some("3Bxs"));
+        assert_eq!(result[1].instruction_accounts, vec!["Payer111", "Dest111"]);
+        // The inner System instruction resolves its program through the loaded addresses
+        assert_eq!(result[2].program_id, "11111111111111111111111111111111");
+        assert_eq!(result[2].instruction_data.as_deref(), Some("3Bxs4h24hBtQy9rw"));
+        assert_eq!(result[2].instruction_accounts, vec!["Payer111", "Dest111"]);
+    }
+
+    fn token_balance(account_index: u8, mint: &str, owner: &str, decimals: u8, amount: u64) -> UiTransactionTokenBalance {
+        UiTransactionTokenBalance {
+            account_index,
+            mint: mint.to_string(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: None,
+                decimals,
+                amount: amount.to_string(),
+                ui_amount_string: "".to_string(),
+            },
+            owner: OptionSerializer::Some(owner.to_string()),
+            program_id: OptionSerializer::None,
+        }
+    }
+
+    #[test]
+    fn test_encoded_transaction_attaches_balance_changes() {
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let ui_raw_message = UiRawMessage {
+            header: Default::default(),
+            account_keys: vec![
+                "Payer111".to_string(),
+                "PayerUsdc111".to_string(),
+                "PoolUsdc111".to_string(),
+                "Closed111".to_string(),
+                "9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7".to_string(),
+            ],
+            recent_blockhash: "".to_string(),
+            instructions: vec![],
+            address_table_lookups: None,
+        };
+        let transaction_status_meta = UiTransactionStatusMeta {
+            err: None,
+            status: Ok(()),
+            fee: 5000,
+            pre_balances: vec![2_000_005_000, 2_039_280, 0, 2_039_280, 1],
+            post_balances: vec![1_002_039_280, 2_039_280, 0, 0, 1],
+            inner_instructions: OptionSerializer::None,
+            log_messages: OptionSerializer::Some(vec![
+                "Program 9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7 invoke [1]".to_string(),
+                "Program 9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7 success".to_string(),
+                "Program 9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7 invoke [1]".to_string(),
+                "Program 9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7 success".to_string(),
+            ]),
+            pre_token_balances: OptionSerializer::Some(vec![
+                token_balance(1, usdc, "Payer111", 6, 5_000_000),
+                token_balance(3, "Mint111", "Payer111", 0, 10),
+            ]),
+            post_token_balances: OptionSerializer::Some(vec![
+                token_balance(1, usdc, "Payer111", 6, 2_500_000),
+                token_balance(2, usdc, "Pool111", 6, 2_500_000),
+            ]),
+            rewards: OptionSerializer::None,
+            loaded_addresses: OptionSerializer::None,
+            return_data: OptionSerializer::None,
+            compute_units_consumed: OptionSerializer::None,
+            cost_units: OptionSerializer::None,
+        };
+        let transaction = EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Json(UiTransaction {
+                signatures: vec![Signature::new_unique().to_string()],
+                message: UiMessage::Raw(ui_raw_message),
+            }),
+            meta: Some(transaction_status_meta),
+            version: None,
+        };
+
+        let result =
+            from_encoded_transaction(&transaction, 123, &ProgramsSelector::new_all_programs())
+                .unwrap();
+        assert_eq!(result.len(), 2);
+        // Transaction-level: only on the first LogContext
+        assert!(result[1].balance_changes.is_empty());
+
+        let changes: Vec<(&str, Option<&str>, &str)> = result[0]
+            .balance_changes
+            .iter()
+            .map(|change| {
+                (
+                    change.account.as_str(),
+                    change.mint.as_deref(),
+                    change.ui_amount_change.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("Payer111", None, "-0.99796572"),
+                ("Closed111", None, "-0.00203928"),
+                ("PayerUsdc111", Some(usdc), "-2.5"),
+                ("PoolUsdc111", Some(usdc), "2.5"),
+                ("Closed111", Some("Mint111"), "-10"),
+            ]
+        );
+        assert_eq!(result[0].balance_changes[3].owner.as_deref(), Some("Pool111"));
+        assert_eq!(result[0].balance_changes[3].amount_change, "2500000");
+        assert_eq!(result[0].balance_changes[2].decimals, 6);
+    }
+
+    #[test]
+    fn test_encoded_transaction_attaches_fees() {
+        let compute_budget = "ComputeBudget111111111111111111111111111111";
+        let program = "9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7";
+        let instruction = |program_id_index: u8, data: Vec<u8>| {
+            UiCompiledInstruction {
+                program_id_index,
+                accounts: vec![],
+                data: bs58::encode(data).into_string(),
+                stack_height: None,
+            }
+        };
+        let transaction = |instructions: Vec<UiCompiledInstruction>| {
+            let ui_raw_message = UiRawMessage {
+                header: Default::default(),
+                account_keys: vec![
+                    "Payer111".to_string(),
+                    compute_budget.to_string(),
+                    program.to_string(),
+                ],
+                recent_blockhash: "".to_string(),
+                instructions,
+                address_table_lookups: None,
+            };
+            let transaction_status_meta = UiTransactionStatusMeta {
+                err: None,
+                status: Ok(()),
+                fee: 20_000,
+                pre_balances: vec![],
+                post_balances: vec![],
+                inner_instructions: OptionSerializer::None,
+                log_messages: OptionSerializer::Some(vec![
+                    format!("Program {} invoke [1]", program),
+                    format!("Program {} success", program),
+                ]),
+                pre_token_balances: OptionSerializer::None,
+                post_token_balances: OptionSerializer::None,
+                rewards: OptionSerializer::None,
+                loaded_addresses: OptionSerializer::None,
+                return_data: OptionSerializer::None,
+                compute_units_consumed: OptionSerializer::Some(42_000),
+                cost_units: OptionSerializer::None,
+            };
+            EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::Json(UiTransaction {
+                    signatures: vec![Signature::new_unique().to_string()],
+                    message: UiMessage::Raw(ui_raw_message),
+                }),
+                meta: Some(transaction_status_meta),
+                version: None,
+            }
+        };
+        let set_limit = |limit: u32| [vec![2], limit.to_le_bytes().to_vec()].concat();
+        let set_price = |price: u64| [vec![3], price.to_le_bytes().to_vec()].concat();
+
+        let requested = transaction(vec![
+            instruction(1, set_limit(300_000)),
+            instruction(1, set_price(50_000)),
+            instruction(2, vec![]),
+        ]);
+        let result =
+            from_encoded_transaction(&requested, 123, &ProgramsSelector::new_all_programs())
+                .unwrap();
+        let fees = result[0].fees.as_ref().unwrap();
+        assert_eq!(fees.fee_payer, "Payer111");
+        assert_eq!(fees.fee, 20_000);
+        assert_eq!(fees.cu_limit, Some(300_000));
+        assert_eq!(fees.cu_price, Some(50_000));
+        assert_eq!(fees.priority_fee, 15_000);
+        assert_eq!(fees.consumed_cu, Some(42_000));
+
+        // Without a requested limit, each other instruction is granted the default 200k CU
+        let default_limit = transaction(vec![
+            instruction(1, set_price(1)),
+            instruction(2, vec![]),
+            instruction(2, vec![]),
+        ]);
+        let result =
+            from_encoded_transaction(&default_limit, 123, &ProgramsSelector::new_all_programs())
+                .unwrap();
+        let fees = result[0].fees.as_ref().unwrap();
+        assert_eq!(fees.cu_limit, None);
+        assert_eq!(fees.priority_fee, 1);
+
+        let no_priority = transaction(vec![instruction(2, vec![])]);
+        let result =
+            from_encoded_transaction(&no_priority, 123, &ProgramsSelector::new_all_programs())
+                .unwrap();
+        let fees = result[0].fees.as_ref().unwrap();
+        assert_eq!((fees.cu_price, fees.priority_fee), (None, 0));
+    }
+
+    #[test]
+    fn test_encoded_transaction_attaches_transaction_accounts() {
+        let program = "9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7";
+        let ui_raw_message = UiRawMessage {
+            header: solana_sdk::message::MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![
+                "Payer111".to_string(),
+                "Cosigner111".to_string(),
+                "Vault111".to_string(),
+                program.to_string(),
+            ],
+            recent_blockhash: "".to_string(),
+            instructions: vec![],
+            address_table_lookups: None,
+        };
+        let transaction_status_meta = UiTransactionStatusMeta {
+            err: None,
+            status: Ok(()),
+            fee: 10_000,
+            pre_balances: vec![],
+            post_balances: vec![],
+            inner_instructions: OptionSerializer::None,
+            log_messages: OptionSerializer::Some(vec![
+                format!("Program {} invoke [1]", program),
+                format!("Program {} invoke [2]", program),
+                format!("Program {} success", program),
+                format!("Program {} success", program),
+            ]),
+            pre_token_balances: OptionSerializer::None,
+            post_token_balances: OptionSerializer::None,
+            rewards: OptionSerializer::None,
+            loaded_addresses: OptionSerializer::Some(UiLoadedAddresses {
+                writable: vec!["AltWritable111".to_string()],
+                readonly: vec!["AltReadonly111".to_string()],
+            }),
+            return_data: OptionSerializer::None,
+            compute_units_consumed: OptionSerializer::None,
+            cost_units: OptionSerializer::None,
+        };
+        let transaction = EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Json(UiTransaction {
+                signatures: vec![Signature::new_unique().to_string()],
+                message: UiMessage::Raw(ui_raw_message),
+            }),
+            meta: Some(transaction_status_meta),
+            version: None,
+        };
+
+        let result =
+            from_encoded_transaction(&transaction, 123, &ProgramsSelector::new_all_programs())
+                .unwrap();
+        assert_eq!(result.len(), 2);
+        // Every LogContext carries them, CPIs included
+        assert_eq!(result[0].transaction_accounts, result[1].transaction_accounts);
+
+        let accounts = result[0].transaction_accounts.as_ref().unwrap();
+        assert_eq!(accounts.fee_payer, "Payer111");
+        assert_eq!(accounts.signers, vec!["Payer111", "Cosigner111"]);
+        assert_eq!(accounts.writable, vec!["Payer111", "Vault111", "AltWritable111"]);
+        assert_eq!(accounts.readonly, vec!["Cosigner111", program, "AltReadonly111"]);
+        assert_eq!(accounts.loaded_writable, vec!["AltWritable111"]);
+        assert_eq!(accounts.loaded_readonly, vec!["AltReadonly111"]);
+    }
+
+    #[test]
+    fn test_encoded_transaction_attaches_error_detail() {
+        let program = "9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7";
+        let error = TransactionError::InstructionError(1, InstructionError::Custom(6001));
+        let ui_raw_message = UiRawMessage {
+            header: Default::default(),
+            account_keys: vec![],
+            recent_blockhash: "".to_string(),
+            instructions: vec![],
+            address_table_lookups: None,
+        };
+        let transaction_status_meta = UiTransactionStatusMeta {
+            err: Some(UiTransactionError::from(error.clone())),
+            status: Err(UiTransactionError::from(error)),
+            fee: 5000,
+            pre_balances: vec![],
+            post_balances: vec![],
+            inner_instructions: OptionSerializer::None,
+            log_messages: OptionSerializer::Some(vec![
+                "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+                "Program 11111111111111111111111111111111 success".to_string(),
+                format!("Program {} invoke [1]", program),
+                format!("Program {} failed: custom program error: 0x1771", program),
+            ]),
+            pre_token_balances: OptionSerializer::None,
+            post_token_balances: OptionSerializer::None,
+            rewards: OptionSerializer::None,
+            loaded_addresses: OptionSerializer::None,
+            return_data: OptionSerializer::None,
+            compute_units_consumed: OptionSerializer::None,
+            cost_units: OptionSerializer::None,
+        };
+        let transaction = EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Json(UiTransaction {
+                signatures: vec![Signature::new_unique().to_string()],
+                message: UiMessage::Raw(ui_raw_message),
+            }),
+            meta: Some(transaction_status_meta),
+            version: None,
+        };
+
+        let result =
+            from_encoded_transaction(&transaction, 123, &ProgramsSelector::new_all_programs())
+                .unwrap();
+        assert_eq!(result.len(), 2);
+        assert!(result[0].transaction_error_detail.is_none());
+        let detail = result[1].transaction_error_detail.as_ref().unwrap();
+        assert_eq!(detail.instruction_index, Some(1));
+        assert_eq!(detail.instruction_error.as_deref(), Some("Custom"));
+        assert_eq!(detail.custom_code, Some(6001));
+        assert_eq!(
+            detail.message,
+            "Instruction 1 failed: custom program error 6001 (0x1771)"
+        );
+    }
+
+    #[test]
+    fn test_failing_instruction_record_carries_signers() {
+        let program = "9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7";
+        let error = TransactionError::InstructionError(1, InstructionError::Custom(6001));
+        let ui_raw_message = UiRawMessage {
+            header: solana_sdk::message::MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 2,
+            },
+            account_keys: vec![
+                "Payer111".to_string(),
+                "Cosigner111".to_string(),
+                "11111111111111111111111111111111".to_string(),
+                program.to_string(),
+            ],
+            recent_blockhash: "".to_string(),
+            instructions: vec![],
+            address_table_lookups: None,
+        };
+        let transaction_status_meta = UiTransactionStatusMeta {
+            err: Some(UiTransactionError::from(error.clone())),
+            status: Err(UiTransactionError::from(error)),
+            fee: 10_000,
+            pre_balances: vec![],
+            post_balances: vec![],
+            inner_instructions: OptionSerializer::None,
+            log_messages: OptionSerializer::Some(vec![
+                "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+                "Program 11111111111111111111111111111111 success".to_string(),
+                format!("Program {} invoke [1]", program),
+                format!("Program {} failed: custom program error: 0x1771", program),
+            ]),
+            pre_token_balances: OptionSerializer::None,
+            post_token_balances: OptionSerializer::None,
+            rewards: OptionSerializer::None,
+            loaded_addresses: OptionSerializer::None,
+            return_data: OptionSerializer::None,
+            compute_units_consumed: OptionSerializer::None,
+            cost_units: OptionSerializer::None,
+        };
+        let transaction = EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Json(UiTransaction {
+                signatures: vec![Signature::new_unique().to_string()],
+                message: UiMessage::Raw(ui_raw_message),
+            }),
+            meta: Some(transaction_status_meta),
+            version: None,
+        };
+
+        let result =
+            from_encoded_transaction(&transaction, 123, &ProgramsSelector::new_all_programs())
+                .unwrap();
+        assert_eq!(result.len(), 2);
+        // The failing record alone answers "failures signed by Cosigner111"
+        let failing = &result[1];
+        assert_eq!(failing.instruction_index, 1);
+        assert!(failing.transaction_error_detail.is_some());
+        let accounts = failing.transaction_accounts.as_ref().unwrap();
+        assert_eq!(accounts.fee_payer, "Payer111");
+        assert_eq!(accounts.signers, vec!["Payer111", "Cosigner111"]);
+    }
 }
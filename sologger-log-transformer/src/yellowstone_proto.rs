@@ -0,0 +1,528 @@
+//! The subset of the Yellowstone gRPC (Dragon's Mouth) protocol needed to subscribe to
+//! transactions: `geyser.proto` and `solana-storage.proto` messages, written out as prost
+//! structs so no protoc is needed at build time. Field tags match the upstream protos;
+//! fields and oneof variants not listed here are skipped when decoding.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context};
+use solana_rpc_client_api::response::UiTokenAmount as SolanaUiTokenAmount;
+use solana_sdk::hash::Hash;
+use solana_sdk::message::compiled_instruction::CompiledInstruction as SolanaCompiledInstruction;
+use solana_sdk::message::v0::{LoadedAddresses, MessageAddressTableLookup as SolanaLookup};
+use solana_sdk::message::{
+    v0, Message as LegacyMessage, MessageHeader as SolanaMessageHeader, VersionedMessage,
+};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction_error::TransactionError as SolanaTransactionError;
+use solana_transaction_status::{
+    InnerInstruction as SolanaInnerInstruction, InnerInstructions as SolanaInnerInstructions,
+    TransactionStatusMeta as SolanaTransactionStatusMeta, TransactionTokenBalance,
+    VersionedTransactionWithStatusMeta,
+};
+
+/// gRPC path of the bidirectional `Subscribe` stream.
+pub const SUBSCRIBE_PATH: &str = "/geyser.Geyser/Subscribe";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum CommitmentLevel {
+    Processed = 0,
+    Confirmed = 1,
+    Finalized = 2,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequest {
+    /// Transaction filters by name; an update lists the names it matched in `filters`
+    #[prost(map = "string, message", tag = "3")]
+    pub transactions: HashMap<String, SubscribeRequestFilterTransactions>,
+    #[prost(enumeration = "CommitmentLevel", optional, tag = "6")]
+    pub commitment: Option<i32>,
+    #[prost(message, optional, tag = "9")]
+    pub ping: Option<SubscribeRequestPing>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequestFilterTransactions {
+    #[prost(bool, optional, tag = "1")]
+    pub vote: Option<bool>,
+    #[prost(bool, optional, tag = "2")]
+    pub failed: Option<bool>,
+    #[prost(string, optional, tag = "5")]
+    pub signature: Option<String>,
+    /// Transactions that mention any of these accounts
+    #[prost(string, repeated, tag = "3")]
+    pub account_include: Vec<String>,
+    #[prost(string, repeated, tag = "4")]
+    pub account_exclude: Vec<String>,
+    #[prost(string, repeated, tag = "6")]
+    pub account_required: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequestPing {
+    #[prost(int32, tag = "1")]
+    pub id: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdate {
+    #[prost(string, repeated, tag = "1")]
+    pub filters: Vec<String>,
+    #[prost(oneof = "subscribe_update::UpdateOneof", tags = "4, 6, 9")]
+    pub update_oneof: Option<subscribe_update::UpdateOneof>,
+}
+
+pub mod subscribe_update {
+    /// Unboxed like the upstream generated code; ping and pong updates are rare
+    #[allow(clippy::large_enum_variant)]
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    pub enum UpdateOneof {
+        #[prost(message, tag = "4")]
+        Transaction(super::SubscribeUpdateTransaction),
+        #[prost(message, tag = "6")]
+        Ping(super::SubscribeUpdatePing),
+        #[prost(message, tag = "9")]
+        Pong(super::SubscribeUpdatePong),
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdateTransaction {
+    #[prost(message, optional, tag = "1")]
+    pub transaction: Option<SubscribeUpdateTransactionInfo>,
+    #[prost(uint64, tag = "2")]
+    pub slot: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdateTransactionInfo {
+    #[prost(bytes = "vec", tag = "1")]
+    pub signature: Vec<u8>,
+    #[prost(bool, tag = "2")]
+    pub is_vote: bool,
+    #[prost(message, optional, tag = "3")]
+    pub transaction: Option<Transaction>,
+    #[prost(message, optional, tag = "4")]
+    pub meta: Option<TransactionStatusMeta>,
+    /// Position of the transaction in its block
+    #[prost(uint64, tag = "5")]
+    pub index: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdatePing {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdatePong {
+    #[prost(int32, tag = "1")]
+    pub id: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Transaction {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub signatures: Vec<Vec<u8>>,
+    #[prost(message, optional, tag = "2")]
+    pub message: Option<Message>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Message {
+    #[prost(message, optional, tag = "1")]
+    pub header: Option<MessageHeader>,
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub account_keys: Vec<Vec<u8>>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub recent_blockhash: Vec<u8>,
+    #[prost(message, repeated, tag = "4")]
+    pub instructions: Vec<CompiledInstruction>,
+    #[prost(bool, tag = "5")]
+    pub versioned: bool,
+    #[prost(message, repeated, tag = "6")]
+    pub address_table_lookups: Vec<MessageAddressTableLookup>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessageHeader {
+    #[prost(uint32, tag = "1")]
+    pub num_required_signatures: u32,
+    #[prost(uint32, tag = "2")]
+    pub num_readonly_signed_accounts: u32,
+    #[prost(uint32, tag = "3")]
+    pub num_readonly_unsigned_accounts: u32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MessageAddressTableLookup {
+    #[prost(bytes = "vec", tag = "1")]
+    pub account_key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub writable_indexes: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub readonly_indexes: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CompiledInstruction {
+    #[prost(uint32, tag = "1")]
+    pub program_id_index: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub accounts: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransactionStatusMeta {
+    #[prost(message, optional, tag = "1")]
+    pub err: Option<TransactionError>,
+    #[prost(uint64, tag = "2")]
+    pub fee: u64,
+    #[prost(uint64, repeated, tag = "3")]
+    pub pre_balances: Vec<u64>,
+    #[prost(uint64, repeated, tag = "4")]
+    pub post_balances: Vec<u64>,
+    #[prost(message, repeated, tag = "5")]
+    pub inner_instructions: Vec<InnerInstructions>,
+    /// Distinguishes "no inner instructions recorded" from an empty list
+    #[prost(bool, tag = "10")]
+    pub inner_instructions_none: bool,
+    #[prost(string, repeated, tag = "6")]
+    pub log_messages: Vec<String>,
+    #[prost(bool, tag = "11")]
+    pub log_messages_none: bool,
+    #[prost(message, repeated, tag = "7")]
+    pub pre_token_balances: Vec<TokenBalance>,
+    #[prost(message, repeated, tag = "8")]
+    pub post_token_balances: Vec<TokenBalance>,
+    #[prost(bytes = "vec", repeated, tag = "12")]
+    pub loaded_writable_addresses: Vec<Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "13")]
+    pub loaded_readonly_addresses: Vec<Vec<u8>>,
+    #[prost(uint64, optional, tag = "16")]
+    pub compute_units_consumed: Option<u64>,
+    #[prost(uint64, optional, tag = "17")]
+    pub cost_units: Option<u64>,
+}
+
+/// A bincode-serialized `TransactionError`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransactionError {
+    #[prost(bytes = "vec", tag = "1")]
+    pub err: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InnerInstructions {
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub instructions: Vec<InnerInstruction>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InnerInstruction {
+    #[prost(uint32, tag = "1")]
+    pub program_id_index: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub accounts: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub data: Vec<u8>,
+    #[prost(uint32, optional, tag = "4")]
+    pub stack_height: Option<u32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TokenBalance {
+    #[prost(uint32, tag = "1")]
+    pub account_index: u32,
+    #[prost(string, tag = "2")]
+    pub mint: String,
+    #[prost(message, optional, tag = "3")]
+    pub ui_token_amount: Option<UiTokenAmount>,
+    #[prost(string, tag = "4")]
+    pub owner: String,
+    #[prost(string, tag = "5")]
+    pub program_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct UiTokenAmount {
+    #[prost(double, tag = "1")]
+    pub ui_amount: f64,
+    #[prost(uint32, tag = "2")]
+    pub decimals: u32,
+    #[prost(string, tag = "3")]
+    pub amount: String,
+    #[prost(string, tag = "4")]
+    pub ui_amount_string: String,
+}
+
+/// Converts a transaction update into the SDK's transaction-with-meta, the form the
+/// transformer already knows how to parse. Rewards and return data are not carried over.
+pub(crate) fn versioned_transaction_with_status_meta(
+    info: &SubscribeUpdateTransactionInfo,
+) -> anyhow::Result<VersionedTransactionWithStatusMeta> {
+    let transaction = info
+        .transaction
+        .as_ref()
+        .ok_or_else(|| anyhow!("transaction update without a transaction"))?;
+    let meta = info
+        .meta
+        .as_ref()
+        .ok_or_else(|| anyhow!("transaction update without meta"))?;
+    Ok(VersionedTransactionWithStatusMeta {
+        transaction: versioned_transaction(transaction)?,
+        meta: status_meta(meta)?,
+    })
+}
+
+fn versioned_transaction(transaction: &Transaction) -> anyhow::Result<VersionedTransaction> {
+    let signatures = transaction
+        .signatures
+        .iter()
+        .map(|signature| Signature::try_from(signature.as_slice()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| anyhow!("invalid signature"))?;
+    let message = transaction
+        .message
+        .as_ref()
+        .ok_or_else(|| anyhow!("transaction without a message"))?;
+    let header = message.header.clone().unwrap_or_default();
+    let header = SolanaMessageHeader {
+        num_required_signatures: u8::try_from(header.num_required_signatures)?,
+        num_readonly_signed_accounts: u8::try_from(header.num_readonly_signed_accounts)?,
+        num_readonly_unsigned_accounts: u8::try_from(header.num_readonly_unsigned_accounts)?,
+    };
+    let account_keys = pubkeys(&message.account_keys)?;
+    let recent_blockhash = Hash::new_from_array(
+        message
+            .recent_blockhash
+            .as_slice()
+            .try_into()
+            .context("invalid recent blockhash")?,
+    );
+    let instructions = message
+        .instructions
+        .iter()
+        .map(|instruction| {
+            Ok(SolanaCompiledInstruction {
+                program_id_index: u8::try_from(instruction.program_id_index)?,
+                accounts: instruction.accounts.clone(),
+                data: instruction.data.clone(),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let message = if message.versioned {
+        let address_table_lookups = message
+            .address_table_lookups
+            .iter()
+            .map(|lookup| {
+                Ok(SolanaLookup {
+                    account_key: pubkey(&lookup.account_key)?,
+                    writable_indexes: lookup.writable_indexes.clone(),
+                    readonly_indexes: lookup.readonly_indexes.clone(),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        VersionedMessage::V0(v0::Message {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+            address_table_lookups,
+        })
+    } else {
+        VersionedMessage::Legacy(LegacyMessage {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+        })
+    };
+    Ok(VersionedTransaction {
+        signatures,
+        message,
+    })
+}
+
+fn status_meta(meta: &TransactionStatusMeta) -> anyhow::Result<SolanaTransactionStatusMeta> {
+    let status = match &meta.err {
+        None => Ok(()),
+        Some(err) => Err(bincode::deserialize::<SolanaTransactionError>(&err.err)
+            .context("invalid transaction error")?),
+    };
+    let inner_instructions = (!meta.inner_instructions_none)
+        .then(|| {
+            meta.inner_instructions
+                .iter()
+                .map(|inner| {
+                    Ok(SolanaInnerInstructions {
+                        index: u8::try_from(inner.index)?,
+                        instructions: inner
+                            .instructions
+                            .iter()
+                            .map(|instruction| {
+                                Ok(SolanaInnerInstruction {
+                                    instruction: SolanaCompiledInstruction {
+                                        program_id_index: u8::try_from(
+                                            instruction.program_id_index,
+                                        )?,
+                                        accounts: instruction.accounts.clone(),
+                                        data: instruction.data.clone(),
+                                    },
+                                    stack_height: instruction.stack_height,
+                                })
+                            })
+                            .collect::<anyhow::Result<Vec<_>>>()?,
+                    })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .transpose()?;
+
+    Ok(SolanaTransactionStatusMeta {
+        status,
+        fee: meta.fee,
+        pre_balances: meta.pre_balances.clone(),
+        post_balances: meta.post_balances.clone(),
+        inner_instructions,
+        log_messages: (!meta.log_messages_none).then(|| meta.log_messages.clone()),
+        pre_token_balances: Some(token_balances(&meta.pre_token_balances)?),
+        post_token_balances: Some(token_balances(&meta.post_token_balances)?),
+        rewards: None,
+        loaded_addresses: LoadedAddresses {
+            writable: pubkeys(&meta.loaded_writable_addresses)?,
+            readonly: pubkeys(&meta.loaded_readonly_addresses)?,
+        },
+        return_data: None,
+        compute_units_consumed: meta.compute_units_consumed,
+        cost_units: meta.cost_units,
+    })
+}
+
+fn token_balances(balances: &[TokenBalance]) -> anyhow::Result<Vec<TransactionTokenBalance>> {
+    balances
+        .iter()
+        .map(|balance| {
+            let amount = balance.ui_token_amount.clone().unwrap_or_default();
+            Ok(TransactionTokenBalance {
+                account_index: u8::try_from(balance.account_index)?,
+                mint: balance.mint.clone(),
+                ui_token_amount: SolanaUiTokenAmount {
+                    ui_amount: Some(amount.ui_amount),
+                    decimals: u8::try_from(amount.decimals)?,
+                    amount: amount.amount,
+                    ui_amount_string: amount.ui_amount_string,
+                },
+                owner: balance.owner.clone(),
+                program_id: balance.program_id.clone(),
+            })
+        })
+        .collect()
+}
+
+fn pubkey(bytes: &[u8]) -> anyhow::Result<Pubkey> {
+    Pubkey::try_from(bytes).map_err(|_| anyhow!("invalid pubkey of {} bytes", bytes.len()))
+}
+
+fn pubkeys(keys: &[Vec<u8>]) -> anyhow::Result<Vec<Pubkey>> {
+    keys.iter().map(|key| pubkey(key)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_context_transformer::from_yellowstone_transaction;
+    use solana_sdk::instruction::InstructionError;
+    use sologger_log_context::programs_selector::ProgramsSelector;
+
+    const PROGRAM: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+    #[test]
+    fn test_yellowstone_v0_transaction_with_error() {
+        let payer = vec![9u8; 32];
+        let loaded = vec![5u8; 32];
+        let program = bs58::decode(PROGRAM).into_vec().unwrap();
+        let err = SolanaTransactionError::InstructionError(0, InstructionError::Custom(6001));
+        let update = SubscribeUpdateTransaction {
+            slot: 7,
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                signature: vec![1; 64],
+                is_vote: false,
+                transaction: Some(Transaction {
+                    signatures: vec![vec![1; 64]],
+                    message: Some(Message {
+                        header: Some(MessageHeader {
+                            num_required_signatures: 1,
+                            num_readonly_signed_accounts: 0,
+                            num_readonly_unsigned_accounts: 1,
+                        }),
+                        account_keys: vec![payer, program],
+                        recent_blockhash: vec![0; 32],
+                        instructions: vec![CompiledInstruction {
+                            program_id_index: 1,
+                            accounts: vec![0, 2],
+                            data: vec![1, 2, 3],
+                        }],
+                        versioned: true,
+                        address_table_lookups: vec![MessageAddressTableLookup {
+                            account_key: vec![4; 32],
+                            writable_indexes: vec![0],
+                            readonly_indexes: vec![],
+                        }],
+                    }),
+                }),
+                meta: Some(TransactionStatusMeta {
+                    err: Some(TransactionError {
+                        err: bincode::serialize(&err).unwrap(),
+                    }),
+                    fee: 5000,
+                    pre_balances: vec![1_000_000, 1, 0],
+                    post_balances: vec![995_000, 1, 0],
+                    log_messages: vec![
+                        format!("Program {} invoke [1]", PROGRAM),
+                        format!("Program {} failed: custom program error: 0x1771", PROGRAM),
+                    ],
+                    loaded_writable_addresses: vec![loaded],
+                    ..Default::default()
+                }),
+                index: 12,
+            }),
+        };
+
+        let log_contexts =
+            from_yellowstone_transaction(&update, &ProgramsSelector::new_all_programs()).unwrap();
+        assert_eq!(log_contexts.len(), 1);
+        let log_context = &log_contexts[0];
+        assert_eq!(log_context.slot, 7);
+        assert_eq!(log_context.transaction_index, Some(12));
+        assert!(!log_context.transaction_error.is_empty());
+        let detail = log_context.transaction_error_detail.as_ref().unwrap();
+        assert_eq!(detail.custom_code, Some(6001));
+        let accounts = log_context.transaction_accounts.as_ref().unwrap();
+        assert_eq!(
+            accounts.loaded_writable,
+            vec![bs58::encode([5u8; 32]).into_string()]
+        );
+    }
+
+    #[test]
+    fn test_yellowstone_update_without_meta_is_an_error() {
+        let update = SubscribeUpdateTransaction {
+            slot: 7,
+            transaction: Some(SubscribeUpdateTransactionInfo {
+                transaction: Some(Transaction::default()),
+                ..Default::default()
+            }),
+        };
+        assert!(
+            from_yellowstone_transaction(&update, &ProgramsSelector::new_all_programs()).is_err()
+        );
+    }
+}
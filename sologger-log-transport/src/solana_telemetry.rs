@@ -13,10 +13,10 @@
 use std::time::{Duration, SystemTime};
 
 use opentelemetry::global;
-use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::metrics::{Counter, Gauge, Histogram, Meter};
 use opentelemetry::trace::{Span, SpanBuilder, SpanKind, Status, TraceContextExt, Tracer};
 use opentelemetry::{Context, KeyValue};
-use sologger_log_context::sologger_log_context::LogContext;
+use sologger_log_context::sologger_log_context::{DecodedLogEvent, LogContext};
 
 /// Instrumentation scope name used for the tracer and meter.
 pub const SCOPE_NAME: &str = "sologger";
@@ -147,6 +147,9 @@ where
     if context.has_errors() {
         span.set_status(Status::error(context.errors.join("; ")));
     }
+    for event in &context.decoded_events {
+        span.add_event(event.name.clone(), event_attributes(event));
+    }
     let span_context = parent_context.with_span(span);
 
     let mut cursor = start_time;
@@ -209,15 +212,42 @@ fn span_attributes(context: &LogContext) -> Vec<KeyValue> {
     attributes
 }
 
+/// Attributes of the span event recorded for a decoded Anchor event. The decoded data is
+/// flattened to one `solana.event.data.<field>` attribute per top-level field.
+fn event_attributes(event: &DecodedLogEvent) -> Vec<KeyValue> {
+    let mut attributes = vec![
+        KeyValue::new("solana.event.program_id", event.program.clone()),
+        KeyValue::new("solana.event.discriminator", event.discriminator.clone()),
+        KeyValue::new("solana.event.data_log_index", event.data_log_index as i64),
+    ];
+    if let Some(fields) = event.data.as_object() {
+        for (name, value) in fields {
+            let key = format!("solana.event.data.{}", name);
+            attributes.push(match value {
+                serde_json::Value::Bool(b) => KeyValue::new(key, *b),
+                serde_json::Value::Number(n) if n.is_i64() => {
+                    KeyValue::new(key, n.as_i64().unwrap())
+                }
+                serde_json::Value::String(s) => KeyValue::new(key, s.clone()),
+                other => KeyValue::new(key, other.to_string()),
+            });
+        }
+    }
+    attributes
+}
+
 /// Metric instruments for parsed Solana logs. Create once (the instruments are cached
 /// handles) and call [`SologgerMetrics::record`] per parsed batch.
 pub struct SologgerMetrics {
     compute_units: Histogram<u64>,
+    priority_fee: Histogram<u64>,
+    cu_requested: Histogram<u64>,
     transactions: Counter<u64>,
     transaction_failures: Counter<u64>,
     truncated_logs: Counter<u64>,
     websocket_reconnects: Counter<u64>,
     slots_missed: Counter<u64>,
+    pipeline_queue_depth: Gauge<u64>,
 }
 
 impl SologgerMetrics {
@@ -235,6 +265,16 @@ impl SologgerMetrics {
                 .with_unit("cu")
                 .with_description("Compute units consumed per program invocation")
                 .build(),
+            priority_fee: meter
+                .u64_histogram("sologger.priority_fee")
+                .with_unit("lamports")
+                .with_description("Priority fee paid per transaction")
+                .build(),
+            cu_requested: meter
+                .u64_histogram("sologger.cu_requested")
+                .with_unit("cu")
+                .with_description("Compute unit limit requested per transaction with SetComputeUnitLimit")
+                .build(),
             transactions: meter
                 .u64_counter("sologger.transactions")
                 .with_description("Transactions processed")
@@ -255,10 +295,14 @@ impl SologgerMetrics {
                 .u64_counter("sologger.slots.missed")
                 .with_description("Slots that passed while a subscription was disconnected")
                 .build(),
+            pipeline_queue_depth: meter
+                .u64_gauge("sologger.pipeline.queue_depth")
+                .with_description("Batches received and not yet processed by a pipeline worker")
+                .build(),
         }
     }
 
-    /// Records CU usage, failures and truncations for a parsed batch. Contexts are
+    /// Records CU usage, fees, failures and truncations for a parsed batch. Contexts are
     /// grouped by signature so transaction-level counters increment once per
     /// transaction.
     pub fn record(&self, log_contexts: &[LogContext]) {
@@ -279,6 +323,13 @@ impl SologgerMetrics {
         for transaction in group_by_signature(log_contexts) {
             self.transactions.add(1, &[]);
 
+            if let Some(fees) = transaction.iter().find_map(|c| c.fees.as_ref()) {
+                self.priority_fee.record(fees.priority_fee, &[]);
+                if let Some(cu_limit) = fees.cu_limit {
+                    self.cu_requested.record(u64::from(cu_limit), &[]);
+                }
+            }
+
             // Attribute the failure to the deepest failing invocation — the root cause
             // of the abort, not the outermost program that propagated it
             let failure_origin = transaction
@@ -314,6 +365,11 @@ impl SologgerMetrics {
     pub fn record_slot_gap(&self, missed_slots: u64) {
         self.slots_missed.add(missed_slots, &[]);
     }
+
+    /// Records how many batches are waiting for the pipeline workers.
+    pub fn record_queue_depth(&self, depth: u64) {
+        self.pipeline_queue_depth.record(depth, &[]);
+    }
 }
 
 impl Default for SologgerMetrics {
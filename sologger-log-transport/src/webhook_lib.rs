@@ -6,6 +6,7 @@ use std::fs;
 use std::time::Duration;
 
 use anyhow::{Context as AnyhowContext, Result as AnyResult};
+use sologger_log_context::account_change::AccountChange;
 use sologger_log_context::sologger_log_context::LogContext;
 
 use crate::webhook_config::{WebhookConfig, WebhookFormat};
@@ -97,6 +98,43 @@ impl WebhookTransport {
             .collect()
     }
 
+    /// True when an account change passes every configured rule: `errorsOnly` keeps
+    /// changes whose data could not be decoded, `programs` matches the owner, and an
+    /// `instructions` allowlist excludes account changes altogether.
+    pub fn matches_account(&self, change: &AccountChange) -> bool {
+        if self.config.errors_only && change.decode_error.is_none() {
+            return false;
+        }
+        if !self.config.programs.is_empty() && !self.config.programs.contains(&change.owner) {
+            return false;
+        }
+        self.config.instructions.is_empty()
+    }
+
+    /// Builds the POST body for one account change, according to the configured format.
+    pub fn build_account_payload(&self, change: &AccountChange) -> String {
+        match self.config.format {
+            WebhookFormat::Json => change.to_json(),
+            WebhookFormat::Discord => serde_json::json!({
+                "content": truncate(&build_account_message(change), DISCORD_CONTENT_LIMIT)
+            })
+            .to_string(),
+            WebhookFormat::Slack => serde_json::json!({
+                "text": truncate(&build_account_message(change), SLACK_TEXT_LIMIT)
+            })
+            .to_string(),
+        }
+    }
+
+    /// The payloads for every matching account change.
+    pub fn matching_account_payloads(&self, changes: &[AccountChange]) -> Vec<String> {
+        changes
+            .iter()
+            .filter(|change| self.matches_account(change))
+            .map(|change| self.build_account_payload(change))
+            .collect()
+    }
+
     /// POSTs one payload. The caller decides retry/backoff policy; this is one attempt
     /// bounded by the configured timeout.
     pub async fn send_payload(&self, payload: String) -> AnyResult<()> {
@@ -157,11 +195,37 @@ fn build_message(log_context: &LogContext) -> String {
             log_context.error_code.unwrap_or_default()
         ));
     }
-    if !log_context.transaction_error.is_empty() && log_context.transaction_error != "null" {
+    if let Some(detail) = &log_context.transaction_error_detail {
+        lines.push(format!("tx error: {}", detail.message));
+    } else if !log_context.transaction_error.is_empty() && log_context.transaction_error != "null" {
         lines.push(format!("tx error: {}", log_context.transaction_error));
     }
     for event in &log_context.decoded_events {
-        lines.push(format!("event: {}", event));
+        lines.push(format!("event: {} {}", event.name, event.data));
+    }
+    if let Some(accounts) = &log_context.transaction_accounts {
+        let signers: Vec<String> = accounts
+            .signers
+            .iter()
+            .map(|signer| format!("`{}`", signer))
+            .collect();
+        lines.push(format!("signers: {}", signers.join(", ")));
+    }
+    if let Some(fees) = &log_context.fees {
+        lines.push(format!(
+            "fee: {} lamports (priority {}) paid by `{}`",
+            fees.fee, fees.priority_fee, fees.fee_payer
+        ));
+    }
+    for change in &log_context.balance_changes {
+        let sign = if change.ui_amount_change.starts_with('-') { "" } else { "+" };
+        lines.push(format!(
+            "balance: `{}` {}{} {}",
+            change.account,
+            sign,
+            change.ui_amount_change,
+            change.mint.as_deref().unwrap_or("SOL")
+        ));
     }
     if log_context.consumed_cu > 0 || log_context.max_cu > 0 {
         lines.push(format!(
@@ -173,6 +237,40 @@ fn build_message(log_context: &LogContext) -> String {
     lines.join("\n")
 }
 
+/// Human-readable summary of one account change, used for the Discord and Slack formats.
+fn build_account_message(change: &AccountChange) -> String {
+    let status = if change.decode_error.is_some() {
+        "❌"
+    } else {
+        "🔄"
+    };
+    let mut lines = Vec::new();
+
+    let mut headline = format!("{} `{}`", status, change.pubkey);
+    if let Some(account_type) = &change.account_type {
+        headline.push_str(&format!(" **{}**", account_type));
+    }
+    lines.push(headline);
+    lines.push(format!("slot {} · owner `{}`", change.slot, change.owner));
+
+    if let Some(error) = &change.decode_error {
+        lines.push(format!("decode error: {}", error));
+    }
+    if change.previous_slot.is_none() {
+        if let Some(data) = &change.data {
+            lines.push(format!("state: {}", data));
+        }
+    }
+    for field in &change.diff {
+        lines.push(format!(
+            "{}: {} → {}",
+            field.path, field.previous, field.current
+        ));
+    }
+
+    lines.join("\n")
+}
+
 /// Truncates on a char boundary, appending an ellipsis when content was dropped.
 fn truncate(text: &str, limit: usize) -> String {
     if text.chars().count() <= limit {
@@ -186,7 +284,7 @@ fn truncate(text: &str, limit: usize) -> String {
 #[cfg(test)]
 mod tests {
     use sologger_log_context::programs_selector::ProgramsSelector;
-    use sologger_log_context::sologger_log_context::LogContext;
+    use sologger_log_context::sologger_log_context::{DecodedLogEvent, LogContext};
 
     use super::*;
     use crate::webhook_config::{WebhookConfig, WebhookFormat};
@@ -325,6 +423,88 @@ mod tests {
         assert!(text.contains("CU: 4645/200000"));
     }
 
+    #[test]
+    fn decoded_events_are_structured() {
+        let mut contexts = parse_fixture();
+        let program = contexts[1].program_id.clone();
+        contexts[1].decoded_events.push(DecodedLogEvent {
+            name: "PositionOpened".to_string(),
+            program,
+            discriminator: "0102030405060708".to_string(),
+            data: serde_json::json!({"liquidity": "1000"}),
+            data_log_index: 0,
+            from_log_message: false,
+            partial: false,
+        });
+
+        let json = transport(WebhookConfig {
+            url: "http://localhost/hook".to_string(),
+            ..Default::default()
+        });
+        let parsed: serde_json::Value =
+            serde_json::from_str(&json.build_payload(&contexts[1])).unwrap();
+        // Objects in the raw record, not JSON strings nested inside it
+        assert_eq!(parsed["decoded_events"][0]["data"]["liquidity"], "1000");
+
+        let discord = transport(WebhookConfig {
+            url: "http://localhost/hook".to_string(),
+            format: WebhookFormat::Discord,
+            ..Default::default()
+        });
+        let parsed: serde_json::Value =
+            serde_json::from_str(&discord.build_payload(&contexts[1])).unwrap();
+        assert!(parsed["content"]
+            .as_str()
+            .unwrap()
+            .contains("event: PositionOpened {\"liquidity\":\"1000\"}"));
+    }
+
+    #[test]
+    fn account_changes_are_matched_and_formatted() {
+        let change = AccountChange {
+            slot: 43,
+            pubkey: "Poo1State111111111111111111111111111111111".to_string(),
+            owner: "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C".to_string(),
+            lamports: 1_000_000,
+            data_len: 637,
+            account_type: Some("PoolState".to_string()),
+            data: Some(serde_json::json!({"status": 1})),
+            idl_version: Some("0.2.0".to_string()),
+            decode_error: None,
+            previous_slot: Some(42),
+            diff: vec![sologger_log_context::account_change::AccountFieldChange {
+                path: "status".to_string(),
+                previous: serde_json::json!(0),
+                current: serde_json::json!(1),
+            }],
+        };
+
+        let owner_only = transport(WebhookConfig {
+            url: "http://localhost/hook".to_string(),
+            programs: vec![change.owner.clone()],
+            ..Default::default()
+        });
+        assert!(owner_only.matches_account(&change));
+        let errors_only = transport(WebhookConfig {
+            url: "http://localhost/hook".to_string(),
+            errors_only: true,
+            ..Default::default()
+        });
+        assert!(errors_only.matching_account_payloads(&[change.clone()]).is_empty());
+
+        let slack = transport(WebhookConfig {
+            url: "http://localhost/hook".to_string(),
+            format: WebhookFormat::Slack,
+            ..Default::default()
+        });
+        let parsed: serde_json::Value =
+            serde_json::from_str(&slack.build_account_payload(&change)).unwrap();
+        let text = parsed["text"].as_str().unwrap();
+        assert!(text.contains("**PoolState**"));
+        assert!(text.contains("slot 43"));
+        assert!(text.contains("status: 0 → 1"));
+    }
+
     #[test]
     fn truncation_respects_char_boundaries() {
         let long = "ß".repeat(3000);
@@ -8,7 +8,7 @@ use opentelemetry::{Key, Value};
 use opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader, SdkMeterProvider};
 use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
 use sologger_log_context::programs_selector::ProgramsSelector;
-use sologger_log_context::sologger_log_context::LogContext;
+use sologger_log_context::sologger_log_context::{DecodedLogEvent, LogContext, TransactionFees};
 use sologger_log_transport::solana_telemetry::{
     record_transaction_trace_with_tracer, SologgerMetrics,
 };
@@ -199,6 +199,58 @@ fn one_trace_per_transaction_test() {
     );
 }
 
+#[test]
+fn decoded_events_become_span_events_test() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let tracer = provider.tracer("test");
+
+    let mut log_contexts = parse(&failing_cpi_logs(), "SIG_A");
+    let program = log_contexts[0].program_id.clone();
+    log_contexts[0].decoded_events.push(DecodedLogEvent {
+        name: "PositionOpened".to_string(),
+        program,
+        discriminator: "0102030405060708".to_string(),
+        data: serde_json::json!({"liquidity": "1000", "tick": -5, "full_range": false}),
+        data_log_index: 0,
+        from_log_message: false,
+        partial: false,
+    });
+
+    record_transaction_trace_with_tracer(&tracer, &log_contexts);
+    provider.force_flush().unwrap();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let clmm = spans
+        .iter()
+        .find(|s| s.name == "CLMM9tUo OpenPosition")
+        .unwrap();
+    let event = clmm.events.iter().next().unwrap();
+    assert_eq!(event.name, "PositionOpened");
+    let event_attr = |key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|kv| kv.key == Key::new(key.to_string()))
+            .map(|kv| kv.value.clone())
+    };
+    assert_eq!(
+        event_attr("solana.event.discriminator").unwrap().as_str(),
+        "0102030405060708"
+    );
+    assert_eq!(
+        event_attr("solana.event.data.liquidity").unwrap().as_str(),
+        "1000"
+    );
+    assert_eq!(event_attr("solana.event.data.tick"), Some(Value::I64(-5)));
+    assert_eq!(
+        event_attr("solana.event.data.full_range"),
+        Some(Value::Bool(false))
+    );
+}
+
 #[test]
 fn empty_batch_is_a_noop_test() {
     let exporter = InMemorySpanExporter::default();
@@ -220,7 +272,15 @@ fn metrics_recording_test() {
         .build();
     let metrics = SologgerMetrics::with_meter(&provider.meter("test"));
 
-    let log_contexts = parse(&failing_cpi_logs(), "SIG_A");
+    let mut log_contexts = parse(&failing_cpi_logs(), "SIG_A");
+    log_contexts[0].fees = Some(TransactionFees {
+        fee_payer: "Payer111".to_string(),
+        fee: 20_000,
+        priority_fee: 15_000,
+        cu_limit: Some(300_000),
+        cu_price: Some(50_000),
+        consumed_cu: Some(42_000),
+    });
     metrics.record(&log_contexts);
     metrics.record_reconnect();
     provider.force_flush().unwrap();
@@ -235,6 +295,8 @@ fn metrics_recording_test() {
 
     assert!(names.contains(&"sologger.compute_units".to_string()));
     assert!(names.contains(&"sologger.transactions".to_string()));
+    assert!(names.contains(&"sologger.priority_fee".to_string()));
+    assert!(names.contains(&"sologger.cu_requested".to_string()));
     assert!(names.contains(&"sologger.transactions.failed".to_string()));
     assert!(names.contains(&"sologger.websocket.reconnects".to_string()));
     // No truncated logs in this fixture, so the counter has no data points yet
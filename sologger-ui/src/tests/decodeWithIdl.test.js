@@ -234,9 +234,16 @@ describe('WasmLogContextTransformer.add_idl', () => {
         expect(context.instruction_name).toBe('swap_base_input');
         expect(context.error_code).toBe(6000);
         expect(context.error_name).toBe('NotApproved');
+        // LogContext schema_version 2: decoded events are structured objects
+        expect(context.schema_version).toBe(2);
         expect(context.decoded_events).toHaveLength(1);
-        const event = JSON.parse(context.decoded_events[0]);
-        expect(event.name).toBe('SwapEvent');
-        expect(event.data.base_input).toBe(true);
+        const event = context.decoded_events[0];
+        expect(event).toMatchObject({
+            name: 'SwapEvent',
+            program: 'CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C',
+            data_log_index: 0,
+            data: {base_input: true}
+        });
+        expect(event.discriminator).toMatch(/^[0-9a-f]{16}$/);
     });
 });
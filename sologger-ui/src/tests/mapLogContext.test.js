@@ -16,7 +16,14 @@ function sampleContext(overrides = {}) {
             max_cu: 200000,
             error_code: 6000,
             error_name: 'NotApproved',
-            decoded_events: ['{"name":"SwapEvent","data":{"base_input":true}}'],
+            schema_version: 2,
+            decoded_events: [{
+                name: 'SwapEvent',
+                program: 'CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C',
+                discriminator: '40c6cde8260871e2',
+                data: {base_input: true},
+                data_log_index: 0
+            }],
             log_messages: ['Instruction: swap_base_input'],
             data_logs: ['QMbN...'],
             raw_logs: ['Program CPMM... invoke [1]'],
@@ -40,7 +47,7 @@ describe('mapLogContext', () => {
         expect(row.maxComputeUnits).toBe(200000);
         expect(row.errorCode).toBe(6000);
         expect(row.errorName).toBe('NotApproved');
-        expect(JSON.parse(row.decodedEvents)).toHaveLength(1);
+        expect(JSON.parse(row.decodedEvents)[0].data.base_input).toBe(true);
         expect(JSON.parse(row.errors)).toEqual(['custom program error: 0x1770']);
         expect(row.level).toBe('Info');
     });
@@ -0,0 +1,380 @@
+//! Account state source: programSubscribe / accountSubscribe subscriptions for the
+//! accounts configured in `accountSubscriptions`. Every write is decoded with the owner's
+//! IDL `accounts`, diffed against the previous write seen for the account, and shipped as
+//! an [`AccountChange`] through the same transports as LogContexts. Subscriptions
+//! reconnect with the same backoff as the log subscriptions.
+
+use anyhow::Result;
+use futures_util::StreamExt;
+use log::{info, warn};
+use serde_json::Value;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client_api::config::{
+    CommitmentConfig, RpcAccountInfoConfig, RpcProgramAccountsConfig, UiAccountEncoding,
+};
+use solana_sdk::pubkey::Pubkey;
+use sologger_idl_decoder::IdlRegistry;
+use sologger_log_context::account_change::{diff_account_data, AccountChange};
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use crate::log_processor::account_changes_from_updates;
+use crate::log_subscriber::{next_backoff, LogPipeline, INITIAL_BACKOFF};
+use crate::sologger_config::AccountSubscriptionsConfig;
+
+/// What one supervised account task subscribes to.
+#[derive(Clone, Debug)]
+pub(crate) enum AccountSubscription {
+    Program(Pubkey),
+    Account(Pubkey),
+}
+
+/// One subscription per configured program and account. Invalid addresses are skipped
+/// with a warning.
+pub(crate) fn build_account_subscriptions(
+    config: &AccountSubscriptionsConfig,
+) -> Vec<AccountSubscription> {
+    let mut subscriptions = Vec::new();
+    for address in &config.programs {
+        match Pubkey::from_str(address) {
+            Ok(program_id) => subscriptions.push(AccountSubscription::Program(program_id)),
+            Err(err) => warn!("skipping programSubscribe for {}: {}", address, err),
+        }
+    }
+    for address in &config.accounts {
+        match Pubkey::from_str(address) {
+            Ok(pubkey) => subscriptions.push(AccountSubscription::Account(pubkey)),
+            Err(err) => warn!("skipping accountSubscribe for {}: {}", address, err),
+        }
+    }
+    subscriptions
+}
+
+/// Accounts whose last state is kept. Past that, the account first seen longest ago is
+/// forgotten, and its next write is reported like a first write.
+const TRACKED_ACCOUNTS: usize = 100_000;
+
+/// The slot and decoded state of an account's last write.
+type LastWrite = (u64, Option<Value>);
+
+/// The last decoded state seen per account, shared by every account subscription so a
+/// program subscription and an account subscription covering the same account diff
+/// against each other.
+#[derive(Default)]
+pub(crate) struct AccountTracker {
+    /// Last slot and state by account, and the accounts in insertion order for eviction
+    previous: Mutex<(HashMap<String, LastWrite>, VecDeque<String>)>,
+}
+
+impl AccountTracker {
+    /// Decodes one account write with the owner's IDL at `slot` and records it as the
+    /// account's latest state. The diff is against the last state that could be decoded,
+    /// and empty for the first write seen or when this one could not be decoded.
+    pub(crate) fn observe(
+        &self,
+        idl_registry: &IdlRegistry,
+        pubkey: &str,
+        owner: &str,
+        slot: u64,
+        lamports: u64,
+        data: &[u8],
+    ) -> AccountChange {
+        let mut account_type = None;
+        let mut decoded = None;
+        let mut decode_error = None;
+        let idl_version = idl_registry
+            .get_for_slot(owner, slot)
+            .map(|versioned| versioned.version());
+        if let Some(decoder) = idl_registry.decoder_for_slot(owner, slot) {
+            match decoder.decode_account(data) {
+                Ok(Some(account)) => {
+                    account_type = Some(account.name);
+                    decoded = Some(account.data);
+                }
+                Ok(None) => {}
+                Err(err) => decode_error = Some(err.to_string()),
+            }
+        }
+
+        let previous = self.record(pubkey, slot, decoded.clone());
+        let (previous_slot, diff) = match previous {
+            Some((previous_slot, previous_data)) => {
+                let diff = match (&previous_data, &decoded) {
+                    (Some(previous_data), Some(data)) => diff_account_data(previous_data, data),
+                    _ => vec![],
+                };
+                (Some(previous_slot), diff)
+            }
+            None => (None, vec![]),
+        };
+
+        AccountChange {
+            slot,
+            pubkey: pubkey.to_string(),
+            owner: owner.to_string(),
+            lamports,
+            data_len: data.len(),
+            account_type,
+            data: decoded,
+            idl_version,
+            decode_error,
+            previous_slot,
+            diff,
+        }
+    }
+
+    /// Stores an account's latest write and returns the one it replaces. A write that
+    /// could not be decoded keeps the last decoded state.
+    fn record(&self, pubkey: &str, slot: u64, decoded: Option<Value>) -> Option<LastWrite> {
+        let mut previous = self.previous.lock().unwrap();
+        let (by_account, order) = &mut *previous;
+        let replaced = match by_account.get_mut(pubkey) {
+            Some((last_slot, last_decoded)) => {
+                let previous_decoded = match decoded {
+                    Some(decoded) => last_decoded.replace(decoded),
+                    None => last_decoded.clone(),
+                };
+                Some((std::mem::replace(last_slot, slot), previous_decoded))
+            }
+            None => {
+                by_account.insert(pubkey.to_string(), (slot, decoded));
+                order.push_back(pubkey.to_string());
+                None
+            }
+        };
+        while order.len() > TRACKED_ACCOUNTS {
+            if let Some(oldest) = order.pop_front() {
+                by_account.remove(&oldest);
+            }
+        }
+        replaced
+    }
+}
+
+/// Reconnect supervisor for one account subscription, as for the log subscriptions.
+pub(crate) async fn supervise_account_subscription(
+    url: String,
+    subscription: AccountSubscription,
+    commitment: Option<CommitmentConfig>,
+    pipeline: Arc<LogPipeline>,
+    tracker: Arc<AccountTracker>,
+) {
+    let key = match &subscription {
+        AccountSubscription::Program(program_id) => format!("program {}", program_id),
+        AccountSubscription::Account(pubkey) => format!("account {}", pubkey),
+    };
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        match connect_and_stream(&url, &key, &subscription, commitment, &pipeline, &tracker).await {
+            Ok(messages) => {
+                info!(
+                    "[{}] subscription stream ended after {} messages",
+                    key, messages
+                );
+                if messages > 0 {
+                    backoff = INITIAL_BACKOFF;
+                }
+            }
+            Err(err) => warn!("[{}] subscription error: {}", key, err),
+        }
+        warn!("[{}] reconnecting in {:?}", key, backoff);
+        tokio::time::sleep(backoff).await;
+        backoff = next_backoff(backoff);
+    }
+}
+
+/// Connects, subscribes, and pumps the stream until it ends. Returns how many
+/// notifications were processed on this connection.
+async fn connect_and_stream(
+    url: &str,
+    key: &str,
+    subscription: &AccountSubscription,
+    commitment: Option<CommitmentConfig>,
+    pipeline: &LogPipeline,
+    tracker: &AccountTracker,
+) -> Result<u64> {
+    let client = PubsubClient::new(url).await?;
+    let account_config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment,
+        ..Default::default()
+    };
+    let mut processed: u64 = 0;
+
+    match subscription {
+        AccountSubscription::Program(program_id) => {
+            let config = RpcProgramAccountsConfig {
+                account_config,
+                with_context: Some(true),
+                ..Default::default()
+            };
+            let (mut notifications, _unsubscribe) =
+                client.program_subscribe(program_id, Some(config)).await?;
+            info!("[{}] subscribed via programSubscribe", key);
+
+            while let Some(response) = notifications.next().await {
+                let account = response.value.account;
+                let Some(data) = account.data.decode() else {
+                    warn!("[{}] undecodable data for {}", key, response.value.pubkey);
+                    continue;
+                };
+                let change = tracker.observe(
+                    &pipeline.idl_registry(),
+                    &response.value.pubkey,
+                    &account.owner,
+                    response.context.slot,
+                    account.lamports,
+                    &data,
+                );
+                processed += 1;
+                ship(change).await;
+            }
+        }
+        AccountSubscription::Account(pubkey) => {
+            let (mut notifications, _unsubscribe) = client
+                .account_subscribe(pubkey, Some(account_config))
+                .await?;
+            info!("[{}] subscribed via accountSubscribe", key);
+
+            let pubkey = pubkey.to_string();
+            while let Some(response) = notifications.next().await {
+                let account = response.value;
+                let Some(data) = account.data.decode() else {
+                    warn!("[{}] undecodable data", key);
+                    continue;
+                };
+                let change = tracker.observe(
+                    &pipeline.idl_registry(),
+                    &pubkey,
+                    &account.owner,
+                    response.context.slot,
+                    account.lamports,
+                    &data,
+                );
+                processed += 1;
+                ship(change).await;
+            }
+        }
+    }
+
+    Ok(processed)
+}
+
+async fn ship(change: AccountChange) {
+    let changes = [change];
+
+    #[cfg(feature = "enable_webhook")]
+    crate::webhook_sender::dispatch_account_changes(&changes);
+
+    if let Err(err) = account_changes_from_updates(&changes).await {
+        warn!("failed to ship account changes: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sologger_idl_decoder::{account_discriminator, Idl};
+
+    const OWNER: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+    fn registry() -> IdlRegistry {
+        let mut registry = IdlRegistry::new();
+        registry.insert(
+            OWNER,
+            Idl::from_json(
+                r#"{"name": "vault", "version": "1.0.0", "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [{"name": "balance", "type": "u64"}, {"name": "locked", "type": "bool"}]}}]}"#,
+            )
+            .unwrap(),
+        );
+        registry
+    }
+
+    fn vault_data(balance: u64, locked: bool) -> Vec<u8> {
+        let mut data = account_discriminator("Vault").to_vec();
+        data.extend_from_slice(&balance.to_le_bytes());
+        data.push(locked as u8);
+        data
+    }
+
+    #[test]
+    fn test_observe_diffs_against_previous_write() {
+        let registry = registry();
+        let tracker = AccountTracker::default();
+
+        let first = tracker.observe(&registry, "Acct1", OWNER, 10, 5000, &vault_data(100, false));
+        assert_eq!(first.account_type.as_deref(), Some("Vault"));
+        assert_eq!(
+            first.data,
+            Some(serde_json::json!({"balance": 100, "locked": false}))
+        );
+        assert_eq!(first.idl_version.as_deref(), Some("1.0.0"));
+        assert_eq!(first.previous_slot, None);
+        assert!(first.diff.is_empty());
+
+        let second = tracker.observe(&registry, "Acct1", OWNER, 12, 5000, &vault_data(250, false));
+        assert_eq!(second.previous_slot, Some(10));
+        assert_eq!(second.diff.len(), 1);
+        assert_eq!(second.diff[0].path, "balance");
+        assert_eq!(second.diff[0].previous, 100);
+        assert_eq!(second.diff[0].current, 250);
+
+        // Other accounts have their own history
+        let other = tracker.observe(&registry, "Acct2", OWNER, 12, 5000, &vault_data(1, true));
+        assert_eq!(other.previous_slot, None);
+
+        // Data that matches the discriminator but not the layout
+        let broken = tracker.observe(
+            &registry,
+            "Acct1",
+            OWNER,
+            13,
+            5000,
+            &vault_data(1, true)[..10],
+        );
+        assert!(broken.decode_error.is_some());
+        assert_eq!(broken.data, None);
+        assert!(broken.diff.is_empty());
+
+        // The next write diffs against the last state that could be decoded
+        let repaired =
+            tracker.observe(&registry, "Acct1", OWNER, 14, 5000, &vault_data(300, false));
+        assert_eq!(repaired.previous_slot, Some(13));
+        assert_eq!(repaired.diff.len(), 1);
+        assert_eq!(repaired.diff[0].previous, 250);
+        assert_eq!(repaired.diff[0].current, 300);
+
+        // Owners without an IDL still produce records
+        let unknown = tracker.observe(&registry, "Acct3", "Other", 13, 1, &[1, 2, 3]);
+        assert_eq!(unknown.account_type, None);
+        assert_eq!(unknown.data_len, 3);
+        assert_eq!(unknown.decode_error, None);
+    }
+
+    #[test]
+    fn test_tracker_forgets_oldest_accounts() {
+        let registry = registry();
+        let tracker = AccountTracker::default();
+        for index in 0..(TRACKED_ACCOUNTS + 10) {
+            tracker.record(&format!("Acct{}", index), 10, None);
+        }
+        assert_eq!(tracker.previous.lock().unwrap().0.len(), TRACKED_ACCOUNTS);
+
+        let kept = tracker.observe(&registry, "Acct10", OWNER, 12, 5000, &vault_data(1, false));
+        assert_eq!(kept.previous_slot, Some(10));
+        let forgotten = tracker.observe(&registry, "Acct9", OWNER, 12, 5000, &vault_data(1, false));
+        assert_eq!(forgotten.previous_slot, None);
+    }
+
+    #[test]
+    fn test_build_account_subscriptions() {
+        let subscriptions = build_account_subscriptions(&AccountSubscriptionsConfig {
+            programs: vec![OWNER.to_string(), "not a pubkey".to_string()],
+            accounts: vec!["11111111111111111111111111111111".to_string()],
+        });
+        assert_eq!(subscriptions.len(), 2);
+        assert!(matches!(subscriptions[0], AccountSubscription::Program(_)));
+        assert!(matches!(subscriptions[1], AccountSubscription::Account(_)));
+    }
+}
@@ -0,0 +1,181 @@
+//! getBlockTime lookups for sources whose notifications carry only a slot
+//! (logsSubscribe), enabled with `resolveBlockTime`. One call per slot, cached. A failed
+//! lookup is only remembered for [`RETRY_AFTER`]: at `processed` commitment a slot is
+//! often not yet available, so a later transaction of the same slot retries it, but the
+//! rest of a busy slot doesn't each make the call meanwhile.
+
+use log::trace;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use sologger_log_context::sologger_log_context::LogContext;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Slots kept; roughly ten minutes of mainnet blocks.
+const CAPACITY: usize = 1500;
+
+/// How long a slot whose lookup failed is left unresolved before it is tried again.
+const RETRY_AFTER: Duration = Duration::from_secs(2);
+
+#[derive(Default)]
+pub(crate) struct BlockTimeCache {
+    /// Block times by slot, and the slots in insertion order for eviction
+    times: Mutex<(HashMap<u64, i64>, VecDeque<u64>)>,
+    /// When the lookup of a slot last failed, for slots within RETRY_AFTER of it
+    failures: Mutex<HashMap<u64, Instant>>,
+}
+
+impl BlockTimeCache {
+    /// Sets `block_time` on the contexts that have none, one lookup per distinct slot.
+    pub(crate) async fn fill(&self, rpc_client: &RpcClient, log_contexts: &mut [LogContext]) {
+        let mut resolved: HashMap<u64, Option<i64>> = HashMap::new();
+        for log_context in log_contexts.iter_mut() {
+            if log_context.block_time.is_some() {
+                continue;
+            }
+            let slot = log_context.slot as u64;
+            let block_time = match resolved.get(&slot) {
+                Some(block_time) => *block_time,
+                None => {
+                    let block_time = self.get(rpc_client, slot).await;
+                    resolved.insert(slot, block_time);
+                    block_time
+                }
+            };
+            log_context.block_time = block_time;
+        }
+    }
+
+    async fn get(&self, rpc_client: &RpcClient, slot: u64) -> Option<i64> {
+        if let Some(block_time) = self.cached(slot) {
+            return Some(block_time);
+        }
+        if self.recently_failed(slot) {
+            return None;
+        }
+        match rpc_client.get_block_time(slot).await {
+            Ok(block_time) => {
+                self.insert(slot, block_time);
+                self.failures.lock().unwrap().remove(&slot);
+                Some(block_time)
+            }
+            Err(err) => {
+                trace!("getBlockTime {} failed: {}", slot, err);
+                self.failed(slot);
+                None
+            }
+        }
+    }
+
+    fn recently_failed(&self, slot: u64) -> bool {
+        self.failures
+            .lock()
+            .unwrap()
+            .get(&slot)
+            .is_some_and(|at| at.elapsed() < RETRY_AFTER)
+    }
+
+    /// Remembers a failed lookup, dropping the failures that are due for a retry.
+    fn failed(&self, slot: u64) {
+        let mut failures = self.failures.lock().unwrap();
+        failures.retain(|_, at| at.elapsed() < RETRY_AFTER);
+        failures.insert(slot, Instant::now());
+    }
+
+    fn cached(&self, slot: u64) -> Option<i64> {
+        self.times.lock().unwrap().0.get(&slot).copied()
+    }
+
+    fn insert(&self, slot: u64, block_time: i64) {
+        let mut times = self.times.lock().unwrap();
+        let (by_slot, order) = &mut *times;
+        if by_slot.insert(slot, block_time).is_none() {
+            order.push_back(slot);
+        }
+        while order.len() > CAPACITY {
+            if let Some(oldest) = order.pop_front() {
+                by_slot.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_evicts_oldest_slots() {
+        let cache = BlockTimeCache::default();
+        for slot in 0..(CAPACITY as u64 + 10) {
+            cache.insert(slot, 1_700_000_000 + slot as i64);
+        }
+        assert_eq!(cache.cached(0), None);
+        assert_eq!(cache.cached(9), None);
+        assert_eq!(cache.cached(10), Some(1_700_000_010));
+        assert_eq!(cache.times.lock().unwrap().0.len(), CAPACITY);
+
+        // Re-inserting a cached slot does not count twice
+        cache.insert(10, 1_700_000_010);
+        assert_eq!(cache.times.lock().unwrap().1.len(), CAPACITY);
+    }
+
+    #[tokio::test]
+    async fn test_fill_keeps_known_block_times() {
+        let cache = BlockTimeCache::default();
+        cache.insert(42, 1_700_000_000);
+        // Unreachable endpoint: uncached slots stay unresolved
+        let rpc_client = RpcClient::new("http://127.0.0.1:1".to_string());
+
+        let logs = vec![
+            "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+            "Program 11111111111111111111111111111111 success".to_string(),
+        ];
+        let parse = |slot| {
+            LogContext::parse_logs(
+                &logs,
+                "".to_string(),
+                &sologger_log_context::programs_selector::ProgramsSelector::new_all_programs(),
+                slot,
+                "sig".to_string(),
+            )
+        };
+        let mut log_contexts = parse(42);
+        log_contexts.extend(parse(43));
+        let mut from_block = parse(44);
+        from_block[0].block_time = Some(1_600_000_000);
+        log_contexts.extend(from_block);
+
+        cache.fill(&rpc_client, &mut log_contexts).await;
+        assert_eq!(log_contexts[0].block_time, Some(1_700_000_000));
+        assert_eq!(log_contexts[1].block_time, None);
+        assert_eq!(log_contexts[2].block_time, Some(1_600_000_000));
+        // The failed slot is left alone for a while; the others were never looked up
+        assert!(cache.recently_failed(43));
+        assert!(!cache.recently_failed(42));
+        assert!(!cache.recently_failed(44));
+    }
+
+    #[tokio::test]
+    async fn test_failed_lookups_retry_after_a_while() {
+        let cache = BlockTimeCache::default();
+        let rpc_client = RpcClient::new_mock("succeeds".to_string());
+        cache.failed(42);
+        // Within RETRY_AFTER the slot is not looked up again
+        assert_eq!(cache.get(&rpc_client, 42).await, None);
+
+        let expired = Instant::now() - RETRY_AFTER;
+        cache.failures.lock().unwrap().insert(42, expired);
+        assert!(!cache.recently_failed(42));
+        // Expired failures are dropped when another slot fails
+        cache.failed(43);
+        assert!(!cache.failures.lock().unwrap().contains_key(&42));
+
+        // Once due, the slot is looked up again and a success clears the failure
+        cache.failed(44);
+        cache.failures.lock().unwrap().insert(44, expired);
+        assert_eq!(cache.get(&rpc_client, 44).await, Some(0));
+        assert!(!cache.failures.lock().unwrap().contains_key(&44));
+        assert_eq!(cache.cached(44), Some(0));
+    }
+}
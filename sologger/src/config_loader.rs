@@ -0,0 +1,391 @@
+//! Loading of sologger-config.json: the typed [`SologgerConfig`] plus the parts read
+//! straight from the JSON (`programsSelector`, `idls`, `layouts`).
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::Result;
+use log::trace;
+
+use crate::sologger_config::SologgerConfig;
+use sologger_idl_decoder::{Idl, IdlRegistry, LayoutSchema, VersionedIdl};
+use sologger_log_context::programs_selector::ProgramsSelector;
+
+/// Reads sologger-config.json at `path` into the typed config, the program selector and
+/// the IDL registry. Shared by the sologger binary and the Geyser plugin.
+pub fn load_config(path: &str) -> Result<(SologgerConfig, ProgramsSelector, IdlRegistry)> {
+    trace!("sologger_config_path: {}", path);
+    let mut file = File::open(Path::new(path))?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+
+    let result: serde_json::Value = serde_json::from_str(&contents)?;
+    trace!("SologgerConfig: {}", result);
+    let programs_selector = create_programs_selector_from_config(&result);
+    let idl_registry = create_idl_registry_from_config(&result);
+    let sologger_config = serde_json::from_str(&contents).map_err(|_err| ConfigError::Loading)?;
+
+    Ok((sologger_config, programs_selector, idl_registry))
+}
+
+/// Builds the IDL registry from the optional "idls" map in sologger-config.json:
+/// program ID -> path of an Anchor IDL JSON file (either spec version), relative to the
+/// working directory. For upgraded programs the value can instead be an object
+/// `{"path", "validFromSlot", "validUntilSlot", "version"}`, or an array of them covering
+/// the program's history. A missing or unreadable IDL is reported and skipped rather than
+/// aborting startup, since decoding is an enrichment on top of normal parsing.
+pub fn create_idl_registry_from_config(config: &serde_json::Value) -> IdlRegistry {
+    let mut registry = IdlRegistry::new();
+    load_layouts_from_config(config, &mut registry);
+    let Some(idls) = config["idls"].as_object() else {
+        return registry;
+    };
+
+    for (program_id, idl_value) in idls {
+        let entries = match idl_value {
+            serde_json::Value::Array(entries) => entries.iter().collect(),
+            entry => vec![entry],
+        };
+        for entry in entries {
+            let (path, valid_from_slot, valid_until_slot, label) = match entry {
+                serde_json::Value::String(path) => (path.as_str(), None, None, None),
+                serde_json::Value::Object(spec)
+                    if spec.get("path").is_some_and(|p| p.is_string()) =>
+                {
+                    (
+                        spec["path"].as_str().unwrap(),
+                        spec.get("validFromSlot").and_then(|slot| slot.as_u64()),
+                        spec.get("validUntilSlot").and_then(|slot| slot.as_u64()),
+                        spec.get("version")
+                            .and_then(|version| version.as_str())
+                            .map(str::to_string),
+                    )
+                }
+                _ => {
+                    // eprintln because the logger is not initialized until after config loading
+                    eprintln!(
+                        "sologger: idls entry for {} is neither a path nor a {{\"path\": ...}} object, skipping",
+                        program_id
+                    );
+                    continue;
+                }
+            };
+            match std::fs::read_to_string(path) {
+                Ok(idl_json) => match Idl::from_json(&idl_json) {
+                    Ok(idl) => {
+                        trace!("loaded IDL for {} from {}", program_id, path);
+                        registry.insert_versioned(
+                            program_id,
+                            VersionedIdl {
+                                idl,
+                                valid_from_slot,
+                                valid_until_slot,
+                                label,
+                            },
+                        );
+                    }
+                    Err(err) => eprintln!(
+                        "sologger: failed to parse IDL for {} from {}: {}",
+                        program_id, path, err
+                    ),
+                },
+                Err(err) => eprintln!(
+                    "sologger: failed to read IDL for {} from {}: {}",
+                    program_id, path, err
+                ),
+            }
+        }
+    }
+    registry
+}
+
+/// Registers the optional "layouts" map in sologger-config.json: program ID -> path of a
+/// user-defined borsh layout, TOML when the path ends in `.toml` and JSON otherwise.
+/// Loaded before `idls`, so an IDL for the same program replaces the layout.
+fn load_layouts_from_config(config: &serde_json::Value, registry: &mut IdlRegistry) {
+    let Some(layouts) = config["layouts"].as_object() else {
+        return;
+    };
+    for (program_id, path) in layouts {
+        let Some(path) = path.as_str() else {
+            eprintln!(
+                "sologger: layouts entry for {} is not a path, skipping",
+                program_id
+            );
+            continue;
+        };
+        let layout = std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                let parsed = if path.ends_with(".toml") {
+                    LayoutSchema::from_toml(&contents)
+                } else {
+                    LayoutSchema::from_json(&contents)
+                };
+                parsed.map_err(|err| err.to_string())
+            });
+        match layout {
+            Ok(layout) => {
+                trace!("loaded layout for {} from {}", program_id, path);
+                registry.insert_layout(program_id.as_str(), layout);
+            }
+            Err(err) => eprintln!(
+                "sologger: failed to load layout for {} from {}: {}",
+                program_id, path, err
+            ),
+        }
+    }
+}
+
+pub fn create_programs_selector_from_config(config: &serde_json::Value) -> ProgramsSelector {
+    let programs_selector = &config["programsSelector"];
+
+    if programs_selector.is_null() {
+        ProgramsSelector::default()
+    } else {
+        let programs = &programs_selector["programs"];
+        let programs: Vec<String> = if programs.is_array() {
+            programs
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|val| val.as_str().unwrap().to_string())
+                .collect()
+        } else {
+            Vec::default()
+        };
+
+        ProgramsSelector::new(&programs)
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Loading,
+}
+
+impl std::error::Error for ConfigError {}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use ConfigError::*;
+        match self {
+            Loading => write!(f, "Loading"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_create_programs_selector_from_config_null() {
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com"
+        });
+
+        let programs_selector = create_programs_selector_from_config(&config);
+        assert!(!programs_selector.select_all_programs);
+        assert!(programs_selector.programs.is_empty());
+    }
+
+    #[test]
+    fn test_create_programs_selector_from_config_with_programs() {
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "programsSelector": {
+                "programs": [
+                    "11111111111111111111111111111112",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+                ]
+            }
+        });
+
+        let programs_selector = create_programs_selector_from_config(&config);
+        assert!(!programs_selector.select_all_programs);
+        assert_eq!(programs_selector.programs.len(), 2);
+        assert!(programs_selector.is_program_selected_string("11111111111111111111111111111112"));
+        assert!(programs_selector
+            .is_program_selected_string("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"));
+    }
+
+    #[test]
+    fn test_create_programs_selector_from_config_all_programs() {
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "programsSelector": {
+                "programs": ["*"]
+            }
+        });
+
+        let programs_selector = create_programs_selector_from_config(&config);
+        assert!(programs_selector.select_all_programs);
+        assert_eq!(programs_selector.programs.len(), 0);
+    }
+
+    #[test]
+    fn test_create_programs_selector_from_config_empty_programs() {
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "programsSelector": {
+                "programs": []
+            }
+        });
+
+        let programs_selector = create_programs_selector_from_config(&config);
+        assert!(!programs_selector.select_all_programs);
+        assert!(programs_selector.programs.is_empty());
+    }
+
+    #[test]
+    fn test_create_programs_selector_from_config_non_array_programs() {
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "programsSelector": {
+                "programs": "not_an_array"
+            }
+        });
+
+        let programs_selector = create_programs_selector_from_config(&config);
+        assert!(!programs_selector.select_all_programs);
+        assert!(programs_selector.programs.is_empty());
+    }
+
+    #[test]
+    fn test_create_idl_registry_from_config_missing() {
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com"
+        });
+
+        let registry = create_idl_registry_from_config(&config);
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn test_create_idl_registry_from_config_with_idl() {
+        // Uses the real Raydium IDL fixture from the decoder crate
+        let idl_path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../sologger-idl-decoder/tests/fixtures/raydium_cp_swap_idl.json"
+        );
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "idls": {
+                "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C": idl_path
+            }
+        });
+
+        let registry = create_idl_registry_from_config(&config);
+        assert_eq!(registry.len(), 1);
+        assert!(registry
+            .get("CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C")
+            .is_some());
+    }
+
+    #[test]
+    fn test_create_idl_registry_from_config_slot_versioned() {
+        let fixtures = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../sologger-idl-decoder/tests/fixtures"
+        );
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "idls": {
+                "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C": [
+                    {
+                        "path": format!("{}/legacy_anchor_idl.json", fixtures),
+                        "validUntilSlot": 999,
+                        "version": "v1"
+                    },
+                    {
+                        "path": format!("{}/raydium_cp_swap_idl.json", fixtures),
+                        "validFromSlot": 1000
+                    },
+                    { "validFromSlot": 2000 }
+                ]
+            }
+        });
+
+        let registry = create_idl_registry_from_config(&config);
+        let program_id = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+        // The entry without a path is skipped
+        assert_eq!(registry.versions(program_id).len(), 2);
+        assert_eq!(
+            registry.get_for_slot(program_id, 5).unwrap().version(),
+            "v1"
+        );
+        assert_eq!(
+            registry.get_for_slot(program_id, 1000).unwrap().version(),
+            "0.2.0"
+        );
+    }
+
+    #[test]
+    fn test_create_idl_registry_from_config_bad_entries() {
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "idls": {
+                "MissingFile111111111111111111111111111111111": "./no/such/file.json",
+                "NotAString1111111111111111111111111111111111": 42
+            }
+        });
+
+        // Bad entries are skipped with a warning instead of aborting startup
+        let registry = create_idl_registry_from_config(&config);
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn test_create_idl_registry_from_config_with_layouts() {
+        let dir = std::env::temp_dir().join(format!("sologger-layouts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let toml_path = dir.join("vault.toml");
+        std::fs::write(
+            &toml_path,
+            "name = \"vault\"\n[[events]]\nname = \"Deposit\"\ndiscriminator = [1]\nfields = [{ name = \"amount\", type = \"u64\" }]\n",
+        )
+        .unwrap();
+        let json_path = dir.join("bad.json");
+        std::fs::write(
+            &json_path,
+            r#"{"name": "bad", "events": [{"name": "A", "discriminator": []}]}"#,
+        )
+        .unwrap();
+
+        let config = json!({
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "layouts": {
+                "Vau1t11111111111111111111111111111111111111": toml_path,
+                "Bad1111111111111111111111111111111111111111": json_path
+            }
+        });
+
+        // The invalid layout is skipped like a bad IDL
+        let registry = create_idl_registry_from_config(&config);
+        assert_eq!(registry.len(), 1);
+        assert_eq!(
+            registry
+                .get("Vau1t11111111111111111111111111111111111111")
+                .unwrap()
+                .program_name(),
+            "vault"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_config_error_display() {
+        let error = ConfigError::Loading;
+        assert_eq!(format!("{}", error), "Loading");
+    }
+
+    #[test]
+    fn test_config_error_debug() {
+        let error = ConfigError::Loading;
+        assert_eq!(format!("{:?}", error), "Loading");
+    }
+}
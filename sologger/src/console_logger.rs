@@ -5,6 +5,7 @@
 use std::io::IsTerminal;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+use sologger_log_context::account_change::AccountChange;
 use sologger_log_context::sologger_log_context::LogContext;
 
 static ENABLED: AtomicBool = AtomicBool::new(false);
@@ -25,6 +26,14 @@ pub fn print_log_contexts(log_contexts: &[LogContext]) {
     print!("{}", render_batch(log_contexts, color));
 }
 
+/// Prints account changes to stdout, colorized when stdout is a terminal.
+pub fn print_account_changes(changes: &[AccountChange]) {
+    let color = std::io::stdout().is_terminal();
+    for change in changes {
+        print!("{}", render_account_change(change, color));
+    }
+}
+
 struct Palette {
     red: &'static str,
     green: &'static str,
@@ -88,16 +97,53 @@ fn render_transaction(out: &mut String, contexts: &[LogContext], p: &Palette) {
         "{}── slot {} · {}{} {}\n",
         p.bold, first.slot, first.signature, p.reset, verdict
     ));
-    if !first.transaction_error.is_empty() && first.transaction_error != "null" {
+    let failed_invocation = contexts
+        .iter()
+        .find(|c| c.transaction_error_detail.is_some());
+    if let Some(failed) = failed_invocation {
+        let detail = failed.transaction_error_detail.as_ref().unwrap();
+        let mut message = format!("{} in {}", detail.message, short_id(&failed.program_id));
+        if let Some(name) = &failed.error_name {
+            message.push_str(&format!(" ({})", name));
+        }
+        out.push_str(&format!("  {}tx error: {}{}\n", p.red, message, p.reset));
+    } else if !first.transaction_error.is_empty() && first.transaction_error != "null" {
         out.push_str(&format!(
             "  {}tx error: {}{}\n",
             p.red, first.transaction_error, p.reset
         ));
     }
 
+    if let Some(fees) = contexts.iter().find_map(|c| c.fees.as_ref()) {
+        let mut line = format!(
+            "  {}fee {} lamports (priority {}) · payer {}",
+            p.dim,
+            fees.fee,
+            fees.priority_fee,
+            short_id(&fees.fee_payer)
+        );
+        if let Some(consumed_cu) = fees.consumed_cu {
+            line.push_str(&format!(" · {} CU", consumed_cu));
+        }
+        out.push_str(&format!("{}{}\n", line, p.reset));
+    }
+
     for context in contexts {
         render_invocation(out, context, p);
     }
+
+    for change in contexts.iter().flat_map(|c| &c.balance_changes) {
+        let (color, sign) = if change.ui_amount_change.starts_with('-') {
+            (p.red, "")
+        } else {
+            (p.green, "+")
+        };
+        let asset = change.mint.as_deref().map_or("SOL".to_string(), short_id);
+        out.push_str(&format!(
+            "  {}Δ {} {}{}{} {}{}\n",
+            p.dim, change.account, color, sign, change.ui_amount_change, asset, p.reset
+        ));
+    }
 }
 
 fn render_invocation(out: &mut String, context: &LogContext, p: &Palette) {
@@ -129,11 +175,25 @@ fn render_invocation(out: &mut String, context: &LogContext, p: &Palette) {
         out.push_str(&format!("{}  {}· {}{}\n", indent, p.dim, message, p.reset));
     }
     for event in &context.decoded_events {
-        out.push_str(&format!("{}  {}★ {}{}\n", indent, p.yellow, event, p.reset));
+        out.push_str(&format!(
+            "{}  {}★ {} {}{}\n",
+            indent, p.yellow, event.name, event.data, p.reset
+        ));
     }
     for error in &context.errors {
         out.push_str(&format!("{}  {}✗ {}{}\n", indent, p.red, error, p.reset));
     }
+    for error in &context.decode_errors {
+        out.push_str(&format!(
+            "{}  {}✗ undecodable {} #{}: {}{}\n",
+            indent,
+            p.red,
+            error.event.as_deref().unwrap_or("data log"),
+            error.data_log_index,
+            error.message,
+            p.reset
+        ));
+    }
     if let Some(name) = &context.error_name {
         let code = context.error_code.unwrap_or_default();
         out.push_str(&format!(
@@ -149,6 +209,43 @@ fn render_invocation(out: &mut String, context: &LogContext, p: &Palette) {
     }
 }
 
+/// Renders one account change: a header line, then the full state for the first write
+/// seen, or one line per changed field after that.
+pub fn render_account_change(change: &AccountChange, color: bool) -> String {
+    let p = if color { &COLORS } else { &PLAIN };
+    let mut out = format!(
+        "{}── slot {} · account {}{}",
+        p.bold, change.slot, change.pubkey, p.reset
+    );
+    if let Some(account_type) = &change.account_type {
+        out.push_str(&format!(" {}{}{}", p.cyan, account_type, p.reset));
+    }
+    out.push_str(&format!(
+        " {}owner {}{}\n",
+        p.dim,
+        short_id(&change.owner),
+        p.reset
+    ));
+
+    if let Some(error) = &change.decode_error {
+        out.push_str(&format!("  {}✗ undecodable: {}{}\n", p.red, error, p.reset));
+    }
+    match (&change.data, change.previous_slot) {
+        (Some(data), None) => {
+            out.push_str(&format!("  {}★ {}{}\n", p.yellow, data, p.reset));
+        }
+        _ => {
+            for field in &change.diff {
+                out.push_str(&format!(
+                    "  {}· {}: {} → {}{}\n",
+                    p.yellow, field.path, field.previous, field.current, p.reset
+                ));
+            }
+        }
+    }
+    out
+}
+
 fn short_id(id: &str) -> String {
     if id.len() > 9 {
         format!("{}…", &id[..8])
@@ -159,10 +256,14 @@ fn short_id(id: &str) -> String {
 
 #[cfg(test)]
 mod tests {
+    use sologger_log_context::account_change::{AccountChange, AccountFieldChange};
     use sologger_log_context::programs_selector::ProgramsSelector;
-    use sologger_log_context::sologger_log_context::LogContext;
+    use sologger_log_context::sologger_log_context::{
+        BalanceChange, DecodedLogEvent, LogContext, LogDecodeError, TransactionFees,
+    };
+    use sologger_log_context::transaction_error::TransactionErrorDetail;
 
-    use super::render_batch;
+    use super::{render_account_change, render_batch};
 
     fn parse_fixture() -> Vec<LogContext> {
         let logs: Vec<String> = vec![
@@ -252,4 +353,119 @@ mod tests {
         assert!(output.contains("── slot 7 · OKSIG ✓"));
         assert!(output.contains("Tokenkeg… Transfer 4645/200000 CU ✓"));
     }
+
+    #[test]
+    fn renders_decoded_events() {
+        let mut contexts = parse_fixture();
+        let program = contexts[0].program_id.clone();
+        contexts[0].decoded_events.push(DecodedLogEvent {
+            name: "PositionOpened".to_string(),
+            program,
+            discriminator: "0102030405060708".to_string(),
+            data: serde_json::json!({"liquidity": "1000"}),
+            data_log_index: 0,
+            from_log_message: false,
+            partial: false,
+        });
+
+        let program = contexts[0].program_id.clone();
+        contexts[0].decode_errors.push(LogDecodeError {
+            program,
+            event: Some("PositionClosed".to_string()),
+            data_log_index: 1,
+            from_log_message: false,
+            offset: Some(12),
+            field: Some("fee".to_string()),
+            message: "invalid event data: payload truncated".to_string(),
+        });
+
+        let output = render_batch(&contexts, false);
+        assert!(output.contains("    ★ PositionOpened {\"liquidity\":\"1000\"}"));
+        assert!(output.contains(
+            "    ✗ undecodable PositionClosed #1: invalid event data: payload truncated"
+        ));
+    }
+
+    #[test]
+    fn renders_balance_changes() {
+        let mut contexts = parse_fixture();
+        contexts[0].balance_changes = vec![
+            BalanceChange::new("Payer111".to_string(), None, None, 9, 1_500_000_000, 500_000_000)
+                .unwrap(),
+            BalanceChange::new(
+                "PoolUsdc111".to_string(),
+                Some("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string()),
+                None,
+                6,
+                0,
+                2_500_000,
+            )
+            .unwrap(),
+        ];
+
+        let output = render_batch(&contexts, false);
+        assert!(output.contains("  Δ Payer111 -1 SOL\n"));
+        assert!(output.contains("  Δ PoolUsdc111 +2.5 EPjFWdd5…\n"));
+    }
+
+    #[test]
+    fn renders_transaction_error_detail() {
+        let mut contexts = parse_fixture();
+        contexts[1].transaction_error_detail =
+            TransactionErrorDetail::from_json(r#"{"InstructionError":[0,{"Custom":1}]}"#);
+        contexts[1].error_name = Some("InsufficientLamports".to_string());
+
+        let output = render_batch(&contexts, false);
+        assert!(output.contains(
+            "  tx error: Instruction 0 failed: custom program error 1 (0x1) in 11111111… (InsufficientLamports)\n"
+        ));
+    }
+
+    #[test]
+    fn renders_transaction_fees() {
+        let mut contexts = parse_fixture();
+        contexts[0].fees = Some(TransactionFees {
+            fee_payer: "Payer111".to_string(),
+            fee: 20_000,
+            priority_fee: 15_000,
+            cu_limit: Some(300_000),
+            cu_price: Some(50_000),
+            consumed_cu: Some(42_000),
+        });
+
+        let output = render_batch(&contexts, false);
+        assert!(output.contains("  fee 20000 lamports (priority 15000) · payer Payer111 · 42000 CU\n"));
+    }
+
+    #[test]
+    fn renders_account_changes() {
+        let mut change = AccountChange {
+            slot: 43,
+            pubkey: "Poo1State111111111111111111111111111111111".to_string(),
+            owner: "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C".to_string(),
+            lamports: 1_000_000,
+            data_len: 637,
+            account_type: Some("PoolState".to_string()),
+            data: Some(serde_json::json!({"status": 1})),
+            idl_version: None,
+            decode_error: None,
+            previous_slot: None,
+            diff: vec![],
+        };
+        let output = render_account_change(&change, false);
+        assert!(output.starts_with(
+            "── slot 43 · account Poo1State111111111111111111111111111111111 PoolState owner CPMMoo8L…"
+        ));
+        assert!(output.contains("  ★ {\"status\":1}"));
+
+        change.previous_slot = Some(42);
+        change.diff.push(AccountFieldChange {
+            path: "status".to_string(),
+            previous: serde_json::json!(0),
+            current: serde_json::json!(1),
+        });
+        let output = render_account_change(&change, false);
+        assert!(output.contains("  · status: 0 → 1"));
+        assert!(!output.contains("★"));
+    }
 }
@@ -0,0 +1,343 @@
+//! Push ingestion: an embedded HTTP endpoint for providers that deliver transactions by
+//! webhook rather than over a subscription. Accepts POSTed getTransaction-shaped JSON
+//! (one object, or an array of them), authenticated with a shared secret, and hands every
+//! transaction to the pipeline workers the subscriptions use. Enabled with the
+//! `httpIngest` block.
+
+use anyhow::Result;
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use log::{info, trace, warn};
+use serde_json::{json, Value};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use sologger_log_transformer::log_context_transformer::from_encoded_confirmed_transaction;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpListener;
+
+use crate::log_subscriber::{stamp_received_at, unix_millis};
+use crate::pipeline_workers::PipelineWorkers;
+use crate::sologger_config::HttpIngestConfig;
+
+/// Binds `bindAddress`, after checking the config can authenticate anyone.
+pub(crate) async fn bind(config: &HttpIngestConfig) -> Result<TcpListener> {
+    if config.secret.is_empty() {
+        anyhow::bail!("httpIngest requires a non-empty secret");
+    }
+    let listener = TcpListener::bind(&config.bind_address).await?;
+    info!(
+        "[http-ingest] accepting transactions on http://{}{}",
+        listener.local_addr()?,
+        config.path
+    );
+    Ok(listener)
+}
+
+/// Serves until the task is aborted.
+pub(crate) async fn run(
+    listener: TcpListener,
+    config: Arc<HttpIngestConfig>,
+    workers: Arc<PipelineWorkers>,
+) {
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                // e.g. out of file descriptors; back off instead of spinning
+                warn!("[http-ingest] accept failed: {}", err);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let config = Arc::clone(&config);
+        let workers = Arc::clone(&workers);
+        tokio::spawn(async move {
+            let service = service_fn(move |request| {
+                handle(request, Arc::clone(&config), Arc::clone(&workers))
+            });
+            if let Err(err) = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                trace!("[http-ingest] connection closed: {}", err);
+            }
+        });
+    }
+}
+
+async fn handle(
+    request: Request<Incoming>,
+    config: Arc<HttpIngestConfig>,
+    workers: Arc<PipelineWorkers>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let received_at = unix_millis(SystemTime::now());
+    if request.uri().path() != config.path {
+        return Ok(error_response(StatusCode::NOT_FOUND, "not found"));
+    }
+    if request.method() != Method::POST {
+        return Ok(error_response(StatusCode::METHOD_NOT_ALLOWED, "use POST"));
+    }
+    if !authorized(request.headers().get(AUTHORIZATION), &config.secret) {
+        return Ok(error_response(StatusCode::UNAUTHORIZED, "unauthorized"));
+    }
+
+    let body = match Limited::new(request.into_body(), config.max_body_bytes)
+        .collect()
+        .await
+    {
+        Ok(collected) => collected.to_bytes(),
+        Err(err) if err.is::<LengthLimitError>() => {
+            return Ok(error_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "body too large",
+            ));
+        }
+        Err(err) => {
+            return Ok(error_response(StatusCode::BAD_REQUEST, &err.to_string()));
+        }
+    };
+    let transactions = match parse_transactions(&body) {
+        Ok(transactions) => transactions,
+        Err(err) => return Ok(error_response(StatusCode::BAD_REQUEST, &err.to_string())),
+    };
+
+    // One pipeline batch per request; a transaction that fails to parse is skipped
+    let mut accepted = 0usize;
+    let mut log_contexts = Vec::new();
+    for transaction in &transactions {
+        match from_encoded_confirmed_transaction(
+            transaction,
+            transaction.slot,
+            &workers.pipeline().program_selector,
+        ) {
+            Ok(parsed) => {
+                accepted += 1;
+                log_contexts.extend(parsed);
+            }
+            Err(err) => warn!(
+                "[http-ingest] failed to parse transaction in slot {}: {}",
+                transaction.slot, err
+            ),
+        }
+    }
+    let records = log_contexts.len();
+    stamp_received_at(&mut log_contexts, received_at);
+    workers.submit(log_contexts).await;
+
+    Ok(json_response(
+        StatusCode::OK,
+        json!({
+            "transactions": transactions.len(),
+            "accepted": accepted,
+            "records": records
+        }),
+    ))
+}
+
+/// The body as one getTransaction result or an array of them.
+fn parse_transactions(body: &[u8]) -> Result<Vec<EncodedConfirmedTransactionWithStatusMeta>> {
+    match serde_json::from_slice::<Value>(body)? {
+        Value::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                serde_json::from_value(item)
+                    .map_err(|err| anyhow::anyhow!("transaction {}: {}", index, err))
+            })
+            .collect(),
+        item => Ok(vec![serde_json::from_value(item)?]),
+    }
+}
+
+/// Accepts the secret verbatim (as webhook providers send a configured auth header) or
+/// as a Bearer token.
+fn authorized(header: Option<&HeaderValue>, secret: &str) -> bool {
+    let Some(value) = header.and_then(|value| value.to_str().ok()) else {
+        return false;
+    };
+    let token = value.strip_prefix("Bearer ").unwrap_or(value);
+    constant_time_eq(token.as_bytes(), secret.as_bytes())
+}
+
+/// Compares without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Full<Bytes>> {
+    json_response(status, json!({ "error": message }))
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_subscriber::LogPipeline;
+    use crate::sologger_config::SologgerConfig;
+    use sologger_idl_decoder::IdlRegistry;
+    use sologger_log_context::programs_selector::ProgramsSelector;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    const SECRET: &str = "shared-secret";
+
+    /// A getTransaction result (json encoding) for a System program call.
+    fn transaction_json(slot: u64, signature: &str) -> Value {
+        json!({
+            "slot": slot,
+            "blockTime": 1_700_000_000,
+            "transaction": {
+                "signatures": [signature],
+                "message": {
+                    "accountKeys": [
+                        "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C",
+                        "11111111111111111111111111111111"
+                    ],
+                    "header": {
+                        "numRequiredSignatures": 1,
+                        "numReadonlySignedAccounts": 0,
+                        "numReadonlyUnsignedAccounts": 1
+                    },
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": [
+                        { "programIdIndex": 1, "accounts": [0], "data": "", "stackHeight": null }
+                    ]
+                }
+            },
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 5000,
+                "preBalances": [1_000_000, 1],
+                "postBalances": [995_000, 1],
+                "innerInstructions": [],
+                "logMessages": [
+                    "Program 11111111111111111111111111111111 invoke [1]",
+                    "Program 11111111111111111111111111111111 success"
+                ],
+                "preTokenBalances": [],
+                "postTokenBalances": [],
+                "rewards": [],
+                "computeUnitsConsumed": 150
+            },
+            "version": "legacy"
+        })
+    }
+
+    async fn spawn_endpoint() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let config = HttpIngestConfig {
+            bind_address: address.clone(),
+            path: "/transactions".to_string(),
+            secret: SECRET.to_string(),
+            max_body_bytes: 64 * 1024,
+        };
+        let pipeline = LogPipeline::new(
+            &SologgerConfig::default(),
+            &ProgramsSelector::new_all_programs(),
+            &IdlRegistry::default(),
+        );
+        let workers = PipelineWorkers::start(Arc::new(pipeline), 1, 16);
+        tokio::spawn(run(listener, Arc::new(config), Arc::new(workers)));
+        address
+    }
+
+    /// Sends one request and returns the status code and body.
+    async fn post(
+        address: &str,
+        path: &str,
+        authorization: Option<&str>,
+        body: &str,
+    ) -> (u16, Value) {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let authorization = authorization
+            .map(|value| format!("authorization: {}\r\n", value))
+            .unwrap_or_default();
+        let request = format!(
+            "POST {} HTTP/1.1\r\nhost: {}\r\n{}content-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            path,
+            address,
+            authorization,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_authorized() {
+        let header = |value: &'static str| Some(HeaderValue::from_static(value));
+        assert!(authorized(header(SECRET).as_ref(), SECRET));
+        assert!(authorized(header("Bearer shared-secret").as_ref(), SECRET));
+        assert!(!authorized(header("Bearer shared-secreT").as_ref(), SECRET));
+        assert!(!authorized(header("shared").as_ref(), SECRET));
+        assert!(!authorized(None, SECRET));
+    }
+
+    #[test]
+    fn test_parse_transactions_single_and_array() {
+        let single = transaction_json(5, "sig1").to_string();
+        assert_eq!(parse_transactions(single.as_bytes()).unwrap().len(), 1);
+
+        let array = json!([transaction_json(5, "sig1"), transaction_json(6, "sig2")]).to_string();
+        let transactions = parse_transactions(array.as_bytes()).unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[1].slot, 6);
+
+        let err = parse_transactions(br#"[{"slot": 1}]"#).unwrap_err();
+        assert!(err.to_string().starts_with("transaction 0:"));
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_ingests_pushed_transactions() {
+        let address = spawn_endpoint().await;
+        let body = json!([transaction_json(5, "sig1"), transaction_json(6, "sig2")]).to_string();
+
+        let (status, response) = post(
+            &address,
+            "/transactions",
+            Some("Bearer shared-secret"),
+            &body,
+        )
+        .await;
+        assert_eq!(status, 200);
+        assert_eq!(response["transactions"], 2);
+        assert_eq!(response["accepted"], 2);
+        assert_eq!(response["records"], 2);
+
+        let (status, _) = post(&address, "/transactions", Some("wrong"), &body).await;
+        assert_eq!(status, 401);
+        let (status, _) = post(&address, "/transactions", None, &body).await;
+        assert_eq!(status, 401);
+        let (status, _) = post(&address, "/other", Some(SECRET), &body).await;
+        assert_eq!(status, 404);
+        let (status, response) = post(&address, "/transactions", Some(SECRET), "{").await;
+        assert_eq!(status, 400);
+        assert!(response["error"].is_string());
+
+        let oversized = " ".repeat(64 * 1024 + 1);
+        let (status, _) = post(&address, "/transactions", Some(SECRET), &oversized).await;
+        assert_eq!(status, 413);
+    }
+}
@@ -0,0 +1,321 @@
+//! `sologger idl <command>`: offline IDL tooling that runs without a config file or RPC.
+//! Every `<idl>` argument is an Anchor IDL (either spec) or a user-defined layout
+//! (`.toml`, or JSON).
+//!
+//! - `sologger idl list <idl>` lists the events, instructions, accounts and errors with
+//!   their discriminators and codes.
+//! - `sologger idl decode <idl> <payload>` decodes one base64 `Program data:` payload.
+//! - `sologger idl error <idl> <code>` looks up a custom error code (decimal or `0x` hex,
+//!   as in `custom program error: 0x1770`).
+//! - `sologger idl lint <idl>` reports duplicate discriminators and unresolved types; fails
+//!   when there are any.
+//! - `sologger idl schema <idl> [--format json-schema|clickhouse|postgres] [--table-prefix <prefix>]`
+//!   prints the table shapes of the IDL's events, as produced by the decoder.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::json;
+use sologger_idl_decoder::{
+    events_ddl, events_json_schema, hex, lint_idl, Idl, IdlDecoder, LayoutSchema, SqlDialect,
+};
+use std::fmt::Write;
+
+const USAGE: &str = "usage:
+  sologger idl list <idl>
+  sologger idl decode <idl> <base64 payload>
+  sologger idl error <idl> <code>
+  sologger idl lint <idl>
+  sologger idl schema <idl> [--format json-schema|clickhouse|postgres] [--table-prefix <prefix>]";
+
+/// Runs an `idl` subcommand. `args` are the arguments after `idl`; the output is
+/// returned rather than printed so the caller owns stdout.
+pub fn run(args: &[String]) -> Result<String> {
+    match args.first().map(String::as_str) {
+        Some("list") => list(&args[1..]),
+        Some("decode") => decode(&args[1..]),
+        Some("error") => error(&args[1..]),
+        Some("lint") => lint(&args[1..]),
+        Some("schema") => schema(&args[1..]),
+        Some(command) => bail!("unknown idl command {:?}\n{}", command, USAGE),
+        None => bail!(USAGE),
+    }
+}
+
+/// The positional arguments of a command that takes exactly `N`.
+fn positional<const N: usize>(args: &[String]) -> Result<[&str; N]> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    args.try_into()
+        .map_err(|_| anyhow!("expected {} arguments\n{}", N, USAGE))
+}
+
+fn list(args: &[String]) -> Result<String> {
+    let [path] = positional(args)?;
+    let idl = load_idl(path)?;
+    let mut out = String::new();
+    writeln!(
+        out,
+        "{} {}",
+        idl.program_name(),
+        idl.program_version().unwrap_or("")
+    )?;
+    writeln!(out, "events:")?;
+    for event in &idl.events {
+        writeln!(
+            out,
+            "  {}  {}",
+            hex(&event.discriminator_bytes()),
+            event.name
+        )?;
+    }
+    writeln!(out, "instructions:")?;
+    for instruction in &idl.instructions {
+        let args: Vec<&str> = instruction
+            .args
+            .iter()
+            .map(|arg| arg.name.as_str())
+            .collect();
+        writeln!(
+            out,
+            "  {}  {}({})",
+            hex(&instruction.discriminator_bytes()),
+            instruction.name,
+            args.join(", ")
+        )?;
+    }
+    writeln!(out, "accounts:")?;
+    for account in &idl.accounts {
+        writeln!(
+            out,
+            "  {}  {}",
+            hex(&account.discriminator_bytes()),
+            account.name
+        )?;
+    }
+    writeln!(out, "errors:")?;
+    for error in &idl.errors {
+        writeln!(
+            out,
+            "  {} (0x{:x})  {}  {}",
+            error.code,
+            error.code,
+            error.name,
+            error.msg.as_deref().unwrap_or("")
+        )?;
+    }
+    Ok(out.trim_end().to_string())
+}
+
+fn decode(args: &[String]) -> Result<String> {
+    let [path, payload] = positional(args)?;
+    let idl = load_idl(path)?;
+    // Accept a whole log line as copied from an explorer
+    let payload = payload.trim();
+    let payload = payload.strip_prefix("Program data:").unwrap_or(payload);
+    match IdlDecoder::new(&idl).decode_event(payload)? {
+        Some(event) => Ok(serde_json::to_string_pretty(&json!({
+            "name": event.name,
+            "discriminator": hex(&event.discriminator),
+            "data": event.data,
+        }))?),
+        None => bail!("the payload matches no event of {}", idl.program_name()),
+    }
+}
+
+fn error(args: &[String]) -> Result<String> {
+    let [path, code] = positional(args)?;
+    let idl = load_idl(path)?;
+    let parsed = match code.strip_prefix("0x") {
+        Some(hex_code) => u32::from_str_radix(hex_code, 16),
+        None => code.parse(),
+    };
+    let code = parsed.map_err(|_| anyhow!("{:?} is not an error code", code))?;
+    match idl.lookup_error(code) {
+        Some(error) => Ok(format!(
+            "{} (0x{:x})  {}  {}",
+            error.code,
+            error.code,
+            error.name,
+            error.msg.as_deref().unwrap_or("")
+        )
+        .trim_end()
+        .to_string()),
+        None => bail!(
+            "{} has no error {} (0x{:x})",
+            idl.program_name(),
+            code,
+            code
+        ),
+    }
+}
+
+fn lint(args: &[String]) -> Result<String> {
+    let [path] = positional(args)?;
+    let idl = load_idl(path)?;
+    let lints = lint_idl(&idl);
+    if lints.is_empty() {
+        return Ok(format!("{}: no issues", path));
+    }
+    let lines: Vec<String> = lints.iter().map(|lint| format!("  {}", lint)).collect();
+    bail!("{}: {} issues\n{}", path, lints.len(), lines.join("\n"))
+}
+
+fn schema(args: &[String]) -> Result<String> {
+    let mut path = None;
+    let mut format = "json-schema";
+    let mut table_prefix = "";
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--format needs a value\n{}", USAGE))?
+            }
+            "--table-prefix" => {
+                table_prefix = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--table-prefix needs a value\n{}", USAGE))?
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => bail!("unexpected argument {:?}\n{}", arg, USAGE),
+        }
+    }
+    let path = path.ok_or_else(|| anyhow!(USAGE))?;
+    let idl = load_idl(path)?;
+
+    match format {
+        "json-schema" => Ok(serde_json::to_string_pretty(&events_json_schema(&idl))?),
+        "clickhouse" => Ok(events_ddl(&idl, SqlDialect::ClickHouse, table_prefix)),
+        "postgres" => Ok(events_ddl(&idl, SqlDialect::Postgres, table_prefix)),
+        other => bail!("unknown format {:?}\n{}", other, USAGE),
+    }
+}
+
+/// An Anchor IDL, or a layout when the path ends in `.toml`. JSON layouts parse as IDLs
+/// directly: their events are legacy-style inline fields with explicit discriminators.
+fn load_idl(path: &str) -> Result<Idl> {
+    let contents = std::fs::read_to_string(path).map_err(|err| anyhow!("{}: {}", path, err))?;
+    let idl = if path.ends_with(".toml") {
+        LayoutSchema::from_toml(&contents).map(LayoutSchema::into_idl)
+    } else {
+        Idl::from_json(&contents)
+    };
+    idl.map_err(|err| anyhow!("{}: {}", path, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGACY_IDL_PATH: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../sologger-idl-decoder/tests/fixtures/legacy_anchor_idl.json"
+    );
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn temp_file(name: &str, contents: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("sologger-idl-cli-{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_list() {
+        let listing = run(&args(&["list", LEGACY_IDL_PATH])).unwrap();
+        assert!(listing.starts_with("legacy_demo 0.1.0"));
+        // sha256("event:TradeEvent")[..8]
+        assert!(listing.contains("  bddb7fd34ee661ee  TradeEvent"));
+        assert!(listing.contains("trade()"));
+        assert!(listing.contains("  6001 (0x1771)  MarketClosed  Market closed"));
+    }
+
+    #[test]
+    fn test_decode_layout_payload() {
+        let layout = temp_file(
+            "vault.toml",
+            "name = \"vault\"\n[[events]]\nname = \"Deposit\"\ndiscriminator = [1]\nfields = [{ name = \"amount\", type = \"u64\" }]\n",
+        );
+        // Discriminator 1, then amount = 500 as a little-endian u64
+        let payload = "Program data: AfQBAAAAAAAA";
+
+        let decoded = run(&args(&["decode", &layout, payload])).unwrap();
+        let decoded: serde_json::Value = serde_json::from_str(&decoded).unwrap();
+        assert_eq!(decoded["name"], "Deposit");
+        assert_eq!(decoded["discriminator"], "01");
+        assert_eq!(decoded["data"]["amount"], 500);
+
+        // [9, 9, 9]: no event has discriminator 9
+        assert!(run(&args(&["decode", &layout, "CQkJ"])).is_err());
+        assert!(run(&args(&["decode", &layout, "not base64!"])).is_err());
+        std::fs::remove_file(layout).unwrap();
+    }
+
+    #[test]
+    fn test_error_lookup() {
+        let expected = "6001 (0x1771)  MarketClosed  Market closed";
+        assert_eq!(
+            run(&args(&["error", LEGACY_IDL_PATH, "6001"])).unwrap(),
+            expected
+        );
+        assert_eq!(
+            run(&args(&["error", LEGACY_IDL_PATH, "0x1771"])).unwrap(),
+            expected
+        );
+        assert!(run(&args(&["error", LEGACY_IDL_PATH, "42"])).is_err());
+        assert!(run(&args(&["error", LEGACY_IDL_PATH, "nope"])).is_err());
+    }
+
+    #[test]
+    fn test_lint() {
+        assert!(run(&args(&["lint", LEGACY_IDL_PATH])).is_ok());
+
+        let broken = temp_file(
+            "broken.json",
+            r#"{"name": "broken", "events": [{"name": "Ghost", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]}]}"#,
+        );
+        let err = run(&args(&["lint", &broken])).unwrap_err().to_string();
+        assert!(err.contains("1 issues"));
+        assert!(err.contains("event Ghost: type Ghost is not defined in types"));
+        std::fs::remove_file(broken).unwrap();
+    }
+
+    #[test]
+    fn test_schema_formats() {
+        let json_schema = run(&args(&["schema", LEGACY_IDL_PATH])).unwrap();
+        let json_schema: serde_json::Value = serde_json::from_str(&json_schema).unwrap();
+        assert_eq!(json_schema["title"], "legacy_demo events");
+
+        let ddl = run(&args(&[
+            "schema",
+            LEGACY_IDL_PATH,
+            "--format",
+            "postgres",
+            "--table-prefix",
+            "legacy_",
+        ]))
+        .unwrap();
+        assert!(ddl.contains("CREATE TABLE IF NOT EXISTS \"legacy_trade_event\""));
+
+        let ddl = run(&args(&[
+            "schema",
+            "--format",
+            "clickhouse",
+            LEGACY_IDL_PATH,
+        ]))
+        .unwrap();
+        assert!(ddl.contains("ENGINE = MergeTree"));
+    }
+
+    #[test]
+    fn test_schema_errors() {
+        assert!(run(&args(&[])).is_err());
+        assert!(run(&args(&["unknown"])).is_err());
+        assert!(run(&args(&["list", LEGACY_IDL_PATH, "extra"])).is_err());
+        assert!(run(&args(&["schema"])).is_err());
+        assert!(run(&args(&["schema", LEGACY_IDL_PATH, "--format", "mysql"])).is_err());
+        assert!(run(&args(&["schema", "./no/such/idl.json"])).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! Hot-reloadable IDL directory: every `*.json` file in `idlDirectory` is loaded as an
+//! Anchor IDL and keyed by the IDL's own address. The directory is polled for added,
+//! removed or modified files, and on a change the registry is rebuilt and swapped into
+//! the running [`LogPipeline`] — new IDLs take effect without dropping the live stream.
+
+use log::{info, trace, warn};
+use sologger_idl_decoder::{Idl, IdlRegistry};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::log_subscriber::LogPipeline;
+
+/// One entry per IDL file: name, modification time and size.
+type Fingerprint = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+pub(crate) struct IdlDirectory {
+    path: PathBuf,
+    fingerprint: Option<Fingerprint>,
+    /// Whether the last poll could not read the directory, so it is reported once
+    unreadable: bool,
+}
+
+impl IdlDirectory {
+    pub(crate) fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            fingerprint: None,
+            unreadable: false,
+        }
+    }
+
+    /// Loads the directory when any IDL file was added, removed or modified since the
+    /// last call (always on the first call). Returns None when nothing changed, and while
+    /// the directory can't be read, so the last registry stays in use until it can.
+    pub(crate) fn reload_if_changed(&mut self) -> Option<IdlRegistry> {
+        let files = self.idl_files()?;
+        let fingerprint = fingerprint(&files);
+        if self.fingerprint.as_ref() == Some(&fingerprint) {
+            return None;
+        }
+        self.fingerprint = Some(fingerprint);
+        Some(load(&files))
+    }
+
+    /// The IDL files, or None when the directory can't be read. Warns when it becomes
+    /// unreadable; later polls that still can't read it only trace.
+    fn idl_files(&mut self) -> Option<Vec<PathBuf>> {
+        let entries = match std::fs::read_dir(&self.path) {
+            Ok(entries) => entries,
+            Err(err) => {
+                if self.unreadable {
+                    trace!(
+                        "[idl] cannot read idlDirectory {}: {}",
+                        self.path.display(),
+                        err
+                    );
+                } else {
+                    warn!(
+                        "[idl] cannot read idlDirectory {}: {}",
+                        self.path.display(),
+                        err
+                    );
+                    self.unreadable = true;
+                }
+                return None;
+            }
+        };
+        if self.unreadable {
+            info!(
+                "[idl] idlDirectory {} is readable again",
+                self.path.display()
+            );
+            self.unreadable = false;
+        }
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+        Some(files)
+    }
+}
+
+fn fingerprint(files: &[PathBuf]) -> Fingerprint {
+    files
+        .iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(path).ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            let len = metadata.map_or(0, |m| m.len());
+            (path.clone(), modified, len)
+        })
+        .collect()
+}
+
+/// Reads every IDL file. A file that fails to parse, or whose program can't be
+/// determined, is logged and skipped.
+fn load(files: &[PathBuf]) -> IdlRegistry {
+    let mut registry = IdlRegistry::new();
+    for path in files {
+        let idl = match std::fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| Idl::from_json(&json).map_err(|err| err.to_string()))
+        {
+            Ok(idl) => idl,
+            Err(err) => {
+                warn!("[idl] skipping {}: {}", path.display(), err);
+                continue;
+            }
+        };
+        let Some(program_id) = program_id_for(&idl, path) else {
+            warn!(
+                "[idl] skipping {}: the IDL has no address and the file is not named <program id>.json",
+                path.display()
+            );
+            continue;
+        };
+        registry.insert(program_id, idl);
+    }
+    registry
+}
+
+/// The IDL's own address, or the file name when it is a program id (the layout of
+/// `idlCacheDir`, so the two can point at the same directory).
+fn program_id_for(idl: &Idl, path: &Path) -> Option<String> {
+    if let Some(address) = idl.program_address() {
+        return Some(address.to_string());
+    }
+    let stem = path.file_stem()?.to_str()?;
+    let is_pubkey = bs58::decode(stem)
+        .into_vec()
+        .is_ok_and(|bytes| bytes.len() == 32);
+    is_pubkey.then(|| stem.to_string())
+}
+
+/// Polls the directory every `interval` and, on a change, swaps `base` overlaid with the
+/// directory's IDLs into the pipeline. Directory IDLs replace `base` entries for the
+/// same program. Runs until aborted.
+pub(crate) async fn watch(
+    mut directory: IdlDirectory,
+    base: IdlRegistry,
+    pipeline: Arc<LogPipeline>,
+    interval: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        if let Some(directory_idls) = directory.reload_if_changed() {
+            let mut registry = base.clone();
+            registry.merge(&directory_idls);
+            info!(
+                "[idl] {} changed; {} programs with IDLs now registered",
+                directory.path.display(),
+                registry.len()
+            );
+            pipeline.set_idl_registry(registry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sologger_log_context::programs_selector::ProgramsSelector;
+    use std::sync::RwLock;
+
+    const RAYDIUM_PROGRAM_ID: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+    const LEGACY_PROGRAM_ID: &str = "9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7";
+    const RAYDIUM_IDL: &str =
+        include_str!("../../sologger-idl-decoder/tests/fixtures/raydium_cp_swap_idl.json");
+    const LEGACY_IDL: &str =
+        include_str!("../../sologger-idl-decoder/tests/fixtures/legacy_anchor_idl.json");
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sologger-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_reload_if_changed() {
+        let dir = temp_dir("idl-directory");
+        // Keyed by the IDL's own address, whatever the file is called
+        std::fs::write(dir.join("raydium.json"), RAYDIUM_IDL).unwrap();
+        // No address in the IDL: keyed by the file name
+        std::fs::write(dir.join(format!("{}.json", LEGACY_PROGRAM_ID)), LEGACY_IDL).unwrap();
+        std::fs::write(dir.join("broken.json"), "{ not json").unwrap();
+        std::fs::write(dir.join("unknown_program.json"), LEGACY_IDL).unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let mut directory = IdlDirectory::new(&dir);
+        let registry = directory.reload_if_changed().unwrap();
+        assert_eq!(registry.len(), 2);
+        assert!(registry.get(RAYDIUM_PROGRAM_ID).is_some());
+        assert_eq!(
+            registry.get(LEGACY_PROGRAM_ID).unwrap().program_name(),
+            "legacy_demo"
+        );
+
+        assert!(directory.reload_if_changed().is_none());
+
+        std::fs::remove_file(dir.join("raydium.json")).unwrap();
+        let registry = directory.reload_if_changed().unwrap();
+        assert_eq!(registry.len(), 1);
+
+        // A missing directory is reported once and keeps the last registry; it is
+        // reloaded once it can be listed again
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(directory.reload_if_changed().is_none());
+        assert!(directory.unreadable);
+        assert!(directory.reload_if_changed().is_none());
+        assert!(directory.unreadable);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("raydium.json"), RAYDIUM_IDL).unwrap();
+        let registry = directory.reload_if_changed().unwrap();
+        assert!(!directory.unreadable);
+        assert_eq!(registry.len(), 1);
+        assert!(registry.get(RAYDIUM_PROGRAM_ID).is_some());
+        assert!(directory.reload_if_changed().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_watch_swaps_pipeline_registry() {
+        let dir = temp_dir("idl-watch");
+        let mut base = IdlRegistry::new();
+        base.insert_json(LEGACY_PROGRAM_ID, LEGACY_IDL).unwrap();
+
+        let pipeline = Arc::new(LogPipeline {
+            program_selector: ProgramsSelector::new_all_programs(),
+            idl_registry: RwLock::new(Arc::new(base.clone())),
+            rpc_client: None,
+            backfill_truncated: false,
+            block_times: None,
+            recorder: None,
+        });
+        let watcher = tokio::spawn(watch(
+            IdlDirectory::new(&dir),
+            base,
+            Arc::clone(&pipeline),
+            Duration::from_millis(10),
+        ));
+
+        std::fs::write(dir.join("raydium.json"), RAYDIUM_IDL).unwrap();
+        let mut swapped = false;
+        for _ in 0..200 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            if pipeline.idl_registry().get(RAYDIUM_PROGRAM_ID).is_some() {
+                swapped = true;
+                break;
+            }
+        }
+        watcher.abort();
+        assert!(swapped);
+        // The base registry's IDLs survive the swap
+        assert!(pipeline.idl_registry().get(LEGACY_PROGRAM_ID).is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+//! On-chain IDL loading: for selected programs with no entry in the `idls` map, derive
+//! the program's Anchor IDL account, fetch it via getAccountInfo on the HTTP RPC,
+//! inflate it and register it. Fetched IDLs are cached as `<idlCacheDir>/<program>.json`
+//! so restarts don't cost an RPC round trip. Programs without an on-chain IDL are not
+//! cached and are looked up again on every start, so a later `anchor idl init` is picked
+//! up. Driven by the `fetchIdls` flag in sologger-config.json.
+
+use anyhow::Result;
+use log::{info, warn};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use sologger_idl_decoder::{inflate_idl_account, IdlRegistry};
+use sologger_log_context::programs_selector::ProgramsSelector;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The seed `anchor idl init` uses for the IDL account.
+const IDL_SEED: &str = "anchor:idl";
+
+/// The address of a program's Anchor IDL account:
+/// create_with_seed(find_program_address(&[], program_id), "anchor:idl", program_id).
+pub(crate) fn idl_address(program_id: &Pubkey) -> Pubkey {
+    let (base, _) = Pubkey::find_program_address(&[], program_id);
+    Pubkey::create_with_seed(&base, IDL_SEED, program_id).expect("anchor:idl is a valid seed")
+}
+
+/// Fetches and registers the on-chain IDL of every selected program that the registry
+/// has no IDL for yet. Failures are logged and skipped: decoding is an enrichment, so a
+/// missing IDL never blocks startup.
+pub(crate) async fn load_missing(
+    rpc_client: &RpcClient,
+    program_selector: &ProgramsSelector,
+    cache_dir: Option<&Path>,
+    idl_registry: &mut IdlRegistry,
+) {
+    if program_selector.select_all_programs {
+        warn!("[idl] fetchIdls requires explicit programs in programsSelector; skipping");
+        return;
+    }
+
+    for program_bytes in &program_selector.programs {
+        let program_id = bs58::encode(program_bytes).into_string();
+        if idl_registry.get(&program_id).is_some() {
+            continue;
+        }
+        match fetch_idl_json(rpc_client, &program_id, cache_dir).await {
+            Ok(Some(idl_json)) => match idl_registry.insert_json(&program_id, &idl_json) {
+                Ok(()) => info!("[idl] loaded on-chain IDL for {}", program_id),
+                Err(err) => warn!(
+                    "[idl] on-chain IDL for {} did not parse: {}",
+                    program_id, err
+                ),
+            },
+            Ok(None) => info!("[idl] {} has no on-chain IDL", program_id),
+            Err(err) => warn!("[idl] failed to fetch IDL for {}: {}", program_id, err),
+        }
+    }
+}
+
+/// Returns the IDL JSON for a program from the cache directory, or from its on-chain IDL
+/// account (writing it to the cache). `None` when the program has no IDL account.
+async fn fetch_idl_json(
+    rpc_client: &RpcClient,
+    program_id: &str,
+    cache_dir: Option<&Path>,
+) -> Result<Option<String>> {
+    let cache_path = cache_dir.map(|dir| dir.join(format!("{}.json", program_id)));
+    if let Some(path) = &cache_path {
+        if let Ok(idl_json) = std::fs::read_to_string(path) {
+            return Ok(Some(idl_json));
+        }
+    }
+
+    let address = idl_address(&Pubkey::from_str(program_id)?);
+    let response = rpc_client
+        .get_account_with_commitment(&address, rpc_client.commitment())
+        .await?;
+    let Some(account) = response.value else {
+        return Ok(None);
+    };
+    let idl_json = inflate_idl_account(&account.data)?;
+
+    if let Some(path) = &cache_path {
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, &idl_json));
+        if let Err(err) = written {
+            warn!("[idl] failed to cache IDL at {}: {}", path.display(), err);
+        }
+    }
+    Ok(Some(idl_json))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const PROGRAM_ID: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+    /// Recorded IDL account data (the legacy demo IDL), base64 as getAccountInfo returns it.
+    const IDL_ACCOUNT_B64: &str =
+        include_str!("../../sologger-idl-decoder/tests/fixtures/legacy_anchor_idl_account.b64");
+
+    /// A minimal JSON-RPC stand-in: answers getAccountInfo for `address` with the fixture
+    /// and every other account with null. Returns the HTTP url.
+    async fn spawn_rpc_stand_in(address: Pubkey) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(stream, address));
+            }
+        });
+        url
+    }
+
+    async fn serve_connection(mut stream: TcpStream, address: Pubkey) {
+        let mut buf = Vec::new();
+        loop {
+            // Read one request: headers, then a Content-Length body
+            let header_end = loop {
+                if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+                let mut chunk = [0u8; 4096];
+                match stream.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                }
+            };
+            let headers = String::from_utf8_lossy(&buf[..header_end]).to_lowercase();
+            let content_length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|len| len.trim().parse().ok())
+                .unwrap_or(0);
+            while buf.len() < header_end + content_length {
+                let mut chunk = [0u8; 4096];
+                match stream.read(&mut chunk).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                }
+            }
+            let request: Value =
+                serde_json::from_slice(&buf[header_end..header_end + content_length]).unwrap();
+            buf.drain(..header_end + content_length);
+
+            let value = if request["method"] == "getAccountInfo"
+                && request["params"][0] == address.to_string()
+            {
+                json!({
+                    "data": [IDL_ACCOUNT_B64.trim(), "base64"],
+                    "executable": false,
+                    "lamports": 1_000_000,
+                    "owner": PROGRAM_ID,
+                    "rentEpoch": 0,
+                    "space": 565
+                })
+            } else {
+                Value::Null
+            };
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": { "context": { "slot": 1 }, "value": value }
+            })
+            .to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if stream.write_all(response.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    #[test]
+    fn test_idl_address_matches_anchor_derivation() {
+        // Derived outside this crate from the Anchor spec: sha256 over the PDA seeds with
+        // an off-curve check for the base, then sha256(base || "anchor:idl" || program)
+        let program_id = Pubkey::from_str(PROGRAM_ID).unwrap();
+        assert_eq!(
+            idl_address(&program_id),
+            Pubkey::from_str("3HD1FNEKoNh5aYfvw3VrNWy6WwrEtS6JYx1RmFTE7DMC").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_missing_fetches_and_caches() {
+        let program_id = Pubkey::from_str(PROGRAM_ID).unwrap();
+        let url = spawn_rpc_stand_in(idl_address(&program_id)).await;
+        let rpc_client = RpcClient::new(url);
+        let cache_dir =
+            std::env::temp_dir().join(format!("sologger-idl-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&cache_dir);
+
+        // A selected program with no on-chain IDL is skipped
+        let program_selector = ProgramsSelector::new(&[
+            PROGRAM_ID.to_string(),
+            "9RX7oz3WN5VRTqekBBHBvEJFVMNRnrCmVy7S6B6S5oU7".to_string(),
+        ]);
+        let mut idl_registry = IdlRegistry::new();
+        load_missing(
+            &rpc_client,
+            &program_selector,
+            Some(&cache_dir),
+            &mut idl_registry,
+        )
+        .await;
+
+        assert_eq!(idl_registry.len(), 1);
+        assert_eq!(
+            idl_registry.get(PROGRAM_ID).unwrap().program_name(),
+            "legacy_demo"
+        );
+        let cached = cache_dir.join(format!("{}.json", PROGRAM_ID));
+        assert!(cached.exists());
+
+        // A second run is served from the cache, with no RPC reachable
+        let offline_client = RpcClient::new("http://127.0.0.1:1".to_string());
+        let mut idl_registry = IdlRegistry::new();
+        load_missing(
+            &offline_client,
+            &program_selector,
+            Some(&cache_dir),
+            &mut idl_registry,
+        )
+        .await;
+        assert_eq!(idl_registry.len(), 1);
+
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_load_missing_keeps_configured_idls() {
+        let rpc_client = RpcClient::new("http://127.0.0.1:1".to_string());
+        let mut idl_registry = IdlRegistry::new();
+        idl_registry
+            .insert_json(PROGRAM_ID, r#"{"name":"configured","events":[]}"#)
+            .unwrap();
+
+        load_missing(
+            &rpc_client,
+            &ProgramsSelector::new(&[PROGRAM_ID.to_string()]),
+            None,
+            &mut idl_registry,
+        )
+        .await;
+        assert_eq!(
+            idl_registry.get(PROGRAM_ID).unwrap().program_name(),
+            "configured"
+        );
+    }
+}
@@ -1,8 +1,30 @@
 pub mod sologger_config;
+pub mod config_loader;
 pub mod console_logger;
+pub mod idl_cli;
 mod log_processor;
 #[cfg(feature = "solana_client_subscriber")]
+mod account_subscriber;
+#[cfg(feature = "solana_client_subscriber")]
 mod backfill;
+#[cfg(feature = "solana_client_subscriber")]
+mod block_time;
+#[cfg(feature = "solana_client_subscriber")]
+mod http_ingest;
+#[cfg(feature = "solana_client_subscriber")]
+mod idl_directory;
+#[cfg(feature = "solana_client_subscriber")]
+mod idl_loader;
+#[cfg(feature = "solana_client_subscriber")]
+mod pipeline_workers;
+#[cfg(feature = "solana_client_subscriber")]
+mod recorder;
+#[cfg(feature = "solana_client_subscriber")]
+pub mod replay;
+#[cfg(feature = "solana_client_subscriber")]
+mod solana_logs;
+#[cfg(feature = "solana_client_subscriber")]
+mod yellowstone_client;
 #[cfg(feature = "enable_otel")]
 pub mod telemetry;
 #[cfg(feature = "enable_webhook")]
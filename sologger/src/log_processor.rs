@@ -1,6 +1,7 @@
 use anyhow::Result;
 use log::{error, info};
 
+use sologger_log_context::account_change::AccountChange;
 use sologger_log_context::sologger_log_context::LogContext;
 
 pub async fn log_contexts_from_logs(log_contexts: &Vec<LogContext>) -> Result<()> {
@@ -18,17 +19,47 @@ pub async fn log_contexts_from_logs(log_contexts: &Vec<LogContext>) -> Result<()
     Ok(())
 }
 
+/// Ships account changes through the same log transport as log contexts. Changes whose
+/// data could not be decoded are logged at error level.
+pub async fn account_changes_from_updates(changes: &[AccountChange]) -> Result<()> {
+    if crate::console_logger::is_enabled() {
+        crate::console_logger::print_account_changes(changes);
+        return Ok(());
+    }
+    for change in changes {
+        if change.decode_error.is_some() {
+            error!("{}", &change.to_json());
+        } else {
+            info!("{}", &change.to_json());
+        }
+    }
+    Ok(())
+}
+
 #[tokio::test]
 pub async fn log_contexts_from_logs_test() {
     let log_context = LogContext {
+        schema_version: sologger_log_context::sologger_log_context::LOG_CONTEXT_SCHEMA_VERSION,
         log_messages: vec![],
         data_logs: vec![],
         decoded_events: vec![],
+        decode_errors: vec![],
+        idl_version: None,
         raw_logs: vec![],
+        instruction_data: None,
+        instruction_accounts: vec![],
+        decoded_instruction: None,
+        balance_changes: vec![],
+        fees: None,
+        transaction_accounts: None,
         errors: vec![],
         error_code: None,
         error_name: None,
         transaction_error: "".to_string(),
+        transaction_error_detail: None,
+        block_time: None,
+        transaction_index: None,
+        received_at: None,
         program_id: "".to_string(),
         parent_program_id: "".to_string(),
         depth: 0,
@@ -43,14 +74,27 @@ pub async fn log_contexts_from_logs_test() {
     };
 
     let log_context_error = LogContext {
+        schema_version: sologger_log_context::sologger_log_context::LOG_CONTEXT_SCHEMA_VERSION,
         log_messages: vec![],
         data_logs: vec![],
         decoded_events: vec![],
+        decode_errors: vec![],
+        idl_version: None,
         raw_logs: vec![],
+        instruction_data: None,
+        instruction_accounts: vec![],
+        decoded_instruction: None,
+        balance_changes: vec![],
+        fees: None,
+        transaction_accounts: None,
         errors: vec![],
         error_code: None,
         error_name: None,
         transaction_error: "Error".to_string(),
+        transaction_error_detail: None,
+        block_time: None,
+        transaction_index: None,
+        received_at: None,
         program_id: "".to_string(),
         parent_program_id: "".to_string(),
         depth: 0,
@@ -0,0 +1,297 @@
+//! Pipeline workers (`pipelineWorkers`): a bounded pool between the live subscriptions and
+//! [`LogPipeline::process`], so a slow truncation refetch or transport doesn't stop the
+//! subscription tasks from reading their streams. Each worker owns a queue; batches are
+//! sharded by signature (block batches, which span transactions, by slot), so every
+//! batch of one transaction is processed by the same worker in arrival order. When every
+//! queue slot of a worker is taken, submitting waits for it. Sources stamp `received_at`
+//! before submitting, so time spent queued counts as ingest latency.
+//! [`PipelineWorkers::shutdown`] processes what is still queued before returning.
+
+use log::{info, warn};
+use sologger_log_context::sologger_log_context::LogContext;
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::task::JoinHandle;
+
+use crate::log_subscriber::LogPipeline;
+
+pub(crate) struct PipelineWorkers {
+    pipeline: Arc<LogPipeline>,
+    /// Emptied by `shutdown`, which closes the queues
+    queues: RwLock<Vec<mpsc::Sender<Vec<LogContext>>>>,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+    /// Batches submitted and not yet processed
+    depth: Arc<AtomicUsize>,
+    /// Submissions that found their worker's queue full
+    waits: AtomicU64,
+}
+
+impl PipelineWorkers {
+    /// Spawns `workers` workers with `queue_capacity` batches of queue each. With no
+    /// workers, batches are processed inline by [`PipelineWorkers::submit`].
+    pub(crate) fn start(pipeline: Arc<LogPipeline>, workers: usize, queue_capacity: usize) -> Self {
+        Self::start_with(
+            pipeline,
+            workers,
+            queue_capacity,
+            |pipeline, log_contexts| async move { pipeline.process(log_contexts).await },
+        )
+    }
+
+    /// [`PipelineWorkers::start`] with what a worker does to each batch given by `process`.
+    fn start_with<F, Fut>(
+        pipeline: Arc<LogPipeline>,
+        workers: usize,
+        queue_capacity: usize,
+        process: F,
+    ) -> Self
+    where
+        F: Fn(Arc<LogPipeline>, Vec<LogContext>) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let depth = Arc::new(AtomicUsize::new(0));
+        let (queues, handles) = (0..workers)
+            .map(|_| {
+                let (sender, mut receiver) =
+                    mpsc::channel::<Vec<LogContext>>(queue_capacity.max(1));
+                let pipeline = Arc::clone(&pipeline);
+                let depth = Arc::clone(&depth);
+                let process = process.clone();
+                let handle = tokio::spawn(async move {
+                    while let Some(log_contexts) = receiver.recv().await {
+                        process(Arc::clone(&pipeline), log_contexts).await;
+                        let _depth = depth.fetch_sub(1, Ordering::Relaxed) - 1;
+                        #[cfg(feature = "enable_otel")]
+                        crate::telemetry::record_queue_depth(_depth);
+                    }
+                });
+                (sender, handle)
+            })
+            .unzip();
+        PipelineWorkers {
+            pipeline,
+            queues: RwLock::new(queues),
+            handles: Mutex::new(handles),
+            depth,
+            waits: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn pipeline(&self) -> &LogPipeline {
+        &self.pipeline
+    }
+
+    /// Batches waiting for, or being processed by, a worker.
+    pub(crate) fn queue_depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    /// Hands one parsed batch to its worker, or processes it inline without workers (or
+    /// after shutdown).
+    pub(crate) async fn submit(&self, log_contexts: Vec<LogContext>) {
+        if log_contexts.is_empty() {
+            return;
+        }
+        let queue = {
+            let queues = self.queues.read().unwrap_or_else(|err| err.into_inner());
+            queues.get(shard(&log_contexts, queues.len())).cloned()
+        };
+        let Some(queue) = queue else {
+            self.pipeline.process(log_contexts).await;
+            return;
+        };
+        let _depth = self.depth.fetch_add(1, Ordering::Relaxed) + 1;
+        #[cfg(feature = "enable_otel")]
+        crate::telemetry::record_queue_depth(_depth);
+
+        let log_contexts = match queue.try_send(log_contexts) {
+            Ok(()) => return,
+            Err(TrySendError::Full(log_contexts)) => {
+                let waits = self.waits.fetch_add(1, Ordering::Relaxed) + 1;
+                if waits.is_power_of_two() {
+                    warn!(
+                        "[pipeline] workers are falling behind ({} batches queued); reading waits for them ({} times so far)",
+                        self.queue_depth(),
+                        waits
+                    );
+                }
+                match queue.send(log_contexts).await {
+                    Ok(()) => return,
+                    Err(err) => err.0,
+                }
+            }
+            Err(TrySendError::Closed(log_contexts)) => log_contexts,
+        };
+        // The worker is gone (it panicked); keep the batch rather than dropping it
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+        self.pipeline.process(log_contexts).await;
+    }
+
+    /// Closes the queues and waits for the workers to process every batch still in them.
+    /// A submission that is already waiting for its queue is still processed.
+    pub(crate) async fn shutdown(&self) {
+        let queues =
+            std::mem::take(&mut *self.queues.write().unwrap_or_else(|err| err.into_inner()));
+        drop(queues);
+        let handles =
+            std::mem::take(&mut *self.handles.lock().unwrap_or_else(|err| err.into_inner()));
+        if self.queue_depth() > 0 {
+            info!("[pipeline] draining {} queued batches", self.queue_depth());
+        }
+        for handle in handles {
+            if let Err(err) = handle.await {
+                warn!("[pipeline] worker stopped: {}", err);
+            }
+        }
+    }
+}
+
+/// The worker for a batch: by signature when the batch is one transaction, by slot when
+/// it spans several (blockSubscribe). Batches are grouped by signature, so comparing the
+/// first and last is enough.
+fn shard(log_contexts: &[LogContext], workers: usize) -> usize {
+    if workers == 0 {
+        return 0;
+    }
+    let mut hasher = DefaultHasher::new();
+    match (log_contexts.first(), log_contexts.last()) {
+        (Some(first), Some(last)) if first.signature == last.signature => {
+            first.signature.hash(&mut hasher)
+        }
+        (Some(first), _) => first.slot.hash(&mut hasher),
+        (None, _) => {}
+    }
+    (hasher.finish() % workers as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sologger_config::SologgerConfig;
+    use sologger_idl_decoder::IdlRegistry;
+    use sologger_log_context::programs_selector::ProgramsSelector;
+    use std::time::Duration;
+
+    fn log_context(signature: &str, slot: usize) -> LogContext {
+        LogContext::new(
+            "11111111111111111111111111111111".to_string(),
+            1,
+            "1".to_string(),
+            0,
+            slot,
+            signature.to_string(),
+        )
+    }
+
+    fn pipeline() -> Arc<LogPipeline> {
+        Arc::new(LogPipeline::new(
+            &SologgerConfig::default(),
+            &ProgramsSelector::new_all_programs(),
+            &IdlRegistry::default(),
+        ))
+    }
+
+    #[test]
+    fn test_shard_by_signature_then_slot() {
+        let transaction = vec![log_context("sig1", 5), log_context("sig1", 5)];
+        let shard_of_transaction = shard(&transaction, 8);
+        // Every batch of a transaction lands on the same worker, whatever else it holds
+        assert_eq!(shard(&transaction[..1], 8), shard_of_transaction);
+        assert_eq!(shard(&[log_context("sig1", 6)], 8), shard_of_transaction);
+
+        // A block batch follows its slot
+        let block = vec![log_context("sig1", 7), log_context("sig2", 7)];
+        let other_block = vec![log_context("sig3", 7), log_context("sig4", 7)];
+        assert_eq!(shard(&block, 8), shard(&other_block, 8));
+
+        assert_eq!(shard(&transaction, 1), 0);
+        assert_eq!(shard(&transaction, 0), 0);
+    }
+
+    #[tokio::test]
+    async fn test_workers_drain_queue() {
+        let workers = PipelineWorkers::start(pipeline(), 4, 2);
+        for slot in 0..64 {
+            let signature = format!("sig{}", slot);
+            workers.submit(vec![log_context(&signature, slot)]).await;
+        }
+        workers.submit(Vec::new()).await;
+
+        for _ in 0..200 {
+            if workers.queue_depth() == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(workers.queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_same_shard_processed_in_submit_order() {
+        let processed = Arc::new(Mutex::new(Vec::new()));
+        let record = Arc::clone(&processed);
+        let workers = PipelineWorkers::start_with(pipeline(), 4, 8, move |_, log_contexts| {
+            let record = Arc::clone(&record);
+            async move {
+                // The first batch of each pair is the slow one
+                if log_contexts[0].id == "first" {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                let batch: Vec<String> = log_contexts
+                    .iter()
+                    .map(|log_context| format!("{}:{}", log_context.signature, log_context.id))
+                    .collect();
+                record.lock().unwrap().push(batch.join(","));
+            }
+        });
+
+        let batch = |signatures: &[&str], slot: usize, id: &str| -> Vec<LogContext> {
+            signatures
+                .iter()
+                .map(|signature| {
+                    let mut log_context = log_context(signature, slot);
+                    log_context.id = id.to_string();
+                    log_context
+                })
+                .collect()
+        };
+        // Two batches of one transaction, then two block batches of one slot
+        workers.submit(batch(&["sig1"], 5, "first")).await;
+        workers.submit(batch(&["sig1"], 5, "second")).await;
+        workers.submit(batch(&["sig2", "sig3"], 7, "first")).await;
+        workers.submit(batch(&["sig4", "sig5"], 7, "second")).await;
+        workers.shutdown().await;
+
+        let processed = processed.lock().unwrap();
+        let position = |batch: &str| processed.iter().position(|done| done == batch).unwrap();
+        assert_eq!(processed.len(), 4);
+        assert!(position("sig1:first") < position("sig1:second"));
+        assert!(position("sig2:first,sig3:first") < position("sig4:second,sig5:second"));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drains_queue() {
+        let workers = PipelineWorkers::start(pipeline(), 2, 64);
+        for slot in 0..32 {
+            let signature = format!("sig{}", slot);
+            workers.submit(vec![log_context(&signature, slot)]).await;
+        }
+        workers.shutdown().await;
+        assert_eq!(workers.queue_depth(), 0);
+
+        // Later batches are processed inline
+        workers.submit(vec![log_context("sig1", 5)]).await;
+        assert_eq!(workers.queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_no_workers_process_inline() {
+        let workers = PipelineWorkers::start(pipeline(), 0, 1);
+        workers.submit(vec![log_context("sig1", 5)]).await;
+        assert_eq!(workers.queue_depth(), 0);
+    }
+}
@@ -0,0 +1,341 @@
+//! Recording mode (`record`): every raw logsSubscribe / blockSubscribe notification is
+//! written, before parsing, to rotating NDJSON files (optionally gzip-compressed) with its
+//! subscription key and receive time. The files replay with the "file" source, so
+//! production traffic doubles as regression input for new parser versions.
+//!
+//! Writes happen on a dedicated thread behind a bounded queue; when the disk falls
+//! behind, notifications are dropped from the recording (never from the pipeline).
+
+use anyhow::Result;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::warn;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::time::SystemTime;
+
+use crate::log_subscriber::unix_millis;
+use crate::sologger_config::RecordConfig;
+
+const RECORD_QUEUE_CAPACITY: usize = 10_000;
+const FILE_PREFIX: &str = "sologger-";
+
+/// One recorded line; replay recognizes the envelope by `receivedAt` + `notification`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordLine<'a, T> {
+    key: &'a str,
+    source: &'a str,
+    received_at: u64,
+    notification: &'a T,
+}
+
+pub(crate) struct Recorder {
+    sender: SyncSender<String>,
+    dropped: AtomicU64,
+}
+
+impl Recorder {
+    /// Creates the directory and starts the writer thread.
+    pub(crate) fn start(config: &RecordConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.directory)?;
+        let (sender, receiver) = sync_channel(RECORD_QUEUE_CAPACITY);
+        let writer = RotatingWriter {
+            config: config.clone(),
+            current: None,
+            written: 0,
+            sequence: 0,
+        };
+        std::thread::Builder::new()
+            .name("sologger-record".to_string())
+            .spawn(move || writer.run(receiver))?;
+        Ok(Recorder {
+            sender,
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// Queues one notification received on subscription `key` from `source`.
+    pub(crate) fn record<T: Serialize>(&self, key: &str, source: &str, notification: &T) {
+        let line = RecordLine {
+            key,
+            source,
+            received_at: unix_millis(SystemTime::now()),
+            notification,
+        };
+        let line = match serde_json::to_string(&line) {
+            Ok(line) => line,
+            Err(err) => {
+                warn!(
+                    "[record] failed to serialize a {} notification: {}",
+                    source, err
+                );
+                return;
+            }
+        };
+        // A disconnected queue means the writer thread already reported its failure
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(line) {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                warn!(
+                    "[record] writer is falling behind; {} notifications dropped from the recording",
+                    dropped
+                );
+            }
+        }
+    }
+}
+
+/// A recording file, compressed or not.
+enum Output {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Output {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Output::Plain(writer) => writer,
+            Output::Gzip(writer) => writer,
+        }
+    }
+
+    /// Flushes, and writes the gzip trailer.
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Output::Plain(mut writer) => writer.flush(),
+            Output::Gzip(writer) => writer.finish()?.flush(),
+        }
+    }
+}
+
+struct RotatingWriter {
+    config: RecordConfig,
+    current: Option<Output>,
+    /// Uncompressed bytes written to the current file
+    written: u64,
+    /// Disambiguates files opened within the same millisecond
+    sequence: u32,
+}
+
+impl RotatingWriter {
+    /// Writes queued lines until every sender is gone. Flushes whenever the queue runs
+    /// dry, so an idle recording is complete on disk.
+    fn run(mut self, receiver: Receiver<String>) {
+        while let Ok(line) = receiver.recv() {
+            self.write(&line);
+            while let Ok(line) = receiver.try_recv() {
+                self.write(&line);
+            }
+            if let Some(output) = &mut self.current {
+                if let Err(err) = output.writer().flush() {
+                    warn!("[record] flush failed: {}", err);
+                    self.current = None;
+                }
+            }
+        }
+        self.close();
+    }
+
+    fn write(&mut self, line: &str) {
+        if self.current.is_none() || self.written >= self.config.max_file_bytes {
+            self.rotate();
+        }
+        let Some(output) = &mut self.current else {
+            return;
+        };
+        let result = output
+            .writer()
+            .write_all(line.as_bytes())
+            .and_then(|_| output.writer().write_all(b"\n"));
+        match result {
+            Ok(()) => self.written += line.len() as u64 + 1,
+            Err(err) => {
+                // Reopen on the next line rather than writing into a broken file
+                warn!("[record] write failed: {}", err);
+                self.current = None;
+            }
+        }
+    }
+
+    fn rotate(&mut self) {
+        self.close();
+        let extension = if self.config.gzip {
+            "ndjson.gz"
+        } else {
+            "ndjson"
+        };
+        let path = Path::new(&self.config.directory).join(format!(
+            "{}{:013}-{:04}.{}",
+            FILE_PREFIX,
+            unix_millis(SystemTime::now()),
+            self.sequence,
+            extension
+        ));
+        self.sequence = (self.sequence + 1) % 10_000;
+        match File::create(&path) {
+            Ok(file) => {
+                let file = BufWriter::new(file);
+                self.current = Some(if self.config.gzip {
+                    Output::Gzip(GzEncoder::new(file, Compression::default()))
+                } else {
+                    Output::Plain(file)
+                });
+                self.written = 0;
+                self.prune();
+            }
+            Err(err) => warn!("[record] cannot create {}: {}", path.display(), err),
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(output) = self.current.take() {
+            if let Err(err) = output.finish() {
+                warn!("[record] failed to finish recording file: {}", err);
+            }
+        }
+    }
+
+    /// Deletes the oldest recording files beyond `maxFiles`, the open one included.
+    fn prune(&self) {
+        let Some(max_files) = self.config.max_files else {
+            return;
+        };
+        let mut files = recording_files(Path::new(&self.config.directory));
+        if files.len() <= max_files {
+            return;
+        }
+        let excess = files.len() - max_files;
+        for path in files.drain(..excess) {
+            if let Err(err) = std::fs::remove_file(&path) {
+                warn!("[record] cannot remove {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+/// The recording files in `directory`, oldest first (names sort chronologically).
+fn recording_files(directory: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(FILE_PREFIX)
+                        && (name.ends_with(".ndjson") || name.ends_with(".ndjson.gz"))
+                })
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use serde_json::{json, Value};
+    use std::io::Read;
+    use std::time::Duration;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sologger-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn config(directory: &Path, gzip: bool) -> RecordConfig {
+        RecordConfig {
+            directory: directory.to_string_lossy().into_owned(),
+            max_file_bytes: 100 * 1024 * 1024,
+            max_files: None,
+            gzip,
+        }
+    }
+
+    /// Drops the recorder and waits for its writer thread to finish the files.
+    fn stop(recorder: Recorder, directory: &Path, expected_lines: usize) -> Vec<String> {
+        drop(recorder);
+        for _ in 0..100 {
+            let lines = read_lines(directory);
+            if lines.len() == expected_lines {
+                return lines;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        read_lines(directory)
+    }
+
+    fn read_lines(directory: &Path) -> Vec<String> {
+        let mut lines = Vec::new();
+        for path in recording_files(directory) {
+            let mut content = String::new();
+            if path.extension().is_some_and(|extension| extension == "gz") {
+                // An unfinished gzip file can't be read to the end yet
+                let file = File::open(&path).unwrap();
+                if GzDecoder::new(file).read_to_string(&mut content).is_err() {
+                    continue;
+                }
+            } else {
+                content = std::fs::read_to_string(&path).unwrap();
+            }
+            lines.extend(content.lines().map(str::to_string));
+        }
+        lines
+    }
+
+    #[test]
+    fn test_records_envelope_lines() {
+        let directory = temp_dir("record-plain");
+        let recorder = Recorder::start(&config(&directory, false)).unwrap();
+        recorder.record("all", "logsSubscribe", &json!({ "context": { "slot": 5 } }));
+        recorder.record("all", "logsSubscribe", &json!({ "context": { "slot": 6 } }));
+
+        let lines = stop(recorder, &directory, 2);
+        assert_eq!(lines.len(), 2);
+        let first: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(first["key"], "all");
+        assert_eq!(first["source"], "logsSubscribe");
+        assert!(first["receivedAt"].as_u64().unwrap() > 1_600_000_000_000);
+        assert_eq!(first["notification"]["context"]["slot"], 5);
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn test_rotates_gzip_files_and_prunes() {
+        let directory = temp_dir("record-gzip");
+        let record_config = RecordConfig {
+            max_file_bytes: 1,
+            max_files: Some(2),
+            ..config(&directory, true)
+        };
+        let recorder = Recorder::start(&record_config).unwrap();
+        for slot in 0..4 {
+            recorder.record("blocks", "blockSubscribe", &json!({ "slot": slot }));
+        }
+
+        // One line per file; only the newest two files are kept
+        let lines = stop(recorder, &directory, 2);
+        let files = recording_files(&directory);
+        assert_eq!(files.len(), 2);
+        assert!(files[0].to_string_lossy().ends_with(".ndjson.gz"));
+        let slots: Vec<u64> = lines
+            .iter()
+            .map(|line| {
+                serde_json::from_str::<Value>(line).unwrap()["notification"]["slot"]
+                    .as_u64()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(slots, vec![2, 3]);
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
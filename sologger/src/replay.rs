@@ -0,0 +1,516 @@
+//! Offline replay (`"source": "file"`, or `sologger replay <file>`): feeds an NDJSON
+//! recording through the normal [`LogPipeline`] so incidents can be reproduced without a
+//! validator or RPC subscription. Each line is a logsSubscribe notification, a
+//! blockSubscribe notification or a getTransaction response, either the bare result, the
+//! whole JSON-RPC message, or a line written by `record`. `.gz` files are decompressed,
+//! and a directory replays its files in name order. Playback is paced by receive time
+//! where the recording has it, by slot otherwise, at the configured speed.
+
+use anyhow::{anyhow, bail, Result};
+use log::{info, warn};
+use serde_json::Value;
+use solana_rpc_client_api::response::{Response, RpcBlockUpdate, RpcLogsResponse};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use sologger_log_context::sologger_log_context::LogContext;
+use sologger_log_transformer::log_context_transformer::{
+    from_encoded_confirmed_transaction, from_rpc_response, from_ui_confirmed_block,
+};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::log_subscriber::{stamp_received_at, LogPipeline};
+use crate::sologger_config::ReplayConfig;
+
+/// Nominal slot time, the clock replay is paced by.
+const SLOT_DURATION_MS: u64 = 400;
+
+/// Longest wait between two lines. Longer gaps, e.g. between recording sessions or a
+/// slot jump, are skipped.
+const MAX_PACE_GAP: Duration = Duration::from_secs(5);
+
+/// Lines read ahead of playback.
+const READ_AHEAD: usize = 1024;
+
+const USAGE: &str =
+    "usage: sologger replay <file> [--speed <n> | --fast] [--config <sologger-config.json>]";
+
+/// Parses the arguments after `replay`. Returns the replay settings and the config path,
+/// when one was given.
+pub fn parse_args(args: &[String]) -> Result<(ReplayConfig, Option<String>)> {
+    let mut path = None;
+    let mut speed = 1;
+    let mut config_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--speed" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--speed needs a value\n{}", USAGE))?;
+                speed = value
+                    .parse()
+                    .map_err(|_| anyhow!("invalid --speed {:?}\n{}", value, USAGE))?;
+            }
+            "--fast" => speed = 0,
+            "--config" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("--config needs a path\n{}", USAGE))?;
+                config_path = Some(value.clone());
+            }
+            flag if flag.starts_with("--") => bail!("unknown option {:?}\n{}", flag, USAGE),
+            file if path.is_none() => path = Some(file.to_string()),
+            extra => bail!("unexpected argument {:?}\n{}", extra, USAGE),
+        }
+    }
+    let path = path.ok_or_else(|| anyhow!(USAGE))?;
+    Ok((ReplayConfig { path, speed }, config_path))
+}
+
+/// One recorded line.
+#[derive(Debug)]
+enum ReplayItem {
+    Logs(Response<RpcLogsResponse>),
+    Block(Response<RpcBlockUpdate>),
+    Transaction(Box<EncodedConfirmedTransactionWithStatusMeta>),
+}
+
+impl ReplayItem {
+    fn slot(&self) -> u64 {
+        match self {
+            ReplayItem::Logs(response) => response.context.slot,
+            ReplayItem::Block(response) => response.value.slot,
+            ReplayItem::Transaction(transaction) => transaction.slot,
+        }
+    }
+
+    fn parse(self, pipeline: &LogPipeline) -> Result<Vec<LogContext>> {
+        match self {
+            ReplayItem::Logs(response) => from_rpc_response(&response, &pipeline.program_selector),
+            ReplayItem::Block(response) => match response.value.block {
+                Some(block) if block.transactions.is_some() => {
+                    from_ui_confirmed_block(block, response.value.slot, &pipeline.program_selector)
+                }
+                _ => Ok(Vec::new()),
+            },
+            ReplayItem::Transaction(transaction) => from_encoded_confirmed_transaction(
+                &transaction,
+                transaction.slot,
+                &pipeline.program_selector,
+            ),
+        }
+    }
+}
+
+/// A parsed line and where it sits on the recording's clock.
+#[derive(Debug)]
+struct ReplayLine {
+    item: ReplayItem,
+    /// Receive time in unix milliseconds, for lines written by `record`
+    received_at: Option<u64>,
+}
+
+/// Recognizes a line by shape. Envelopes are unwrapped first: `notification` of a
+/// `record` line, then `params.result` of a subscription notification or `result` of a
+/// getTransaction response.
+fn parse_line(line: &str) -> Result<ReplayLine> {
+    let mut value: Value = serde_json::from_str(line)?;
+    let received_at = value.get("receivedAt").and_then(Value::as_u64);
+    if received_at.is_some() {
+        if let Some(notification) = value.get_mut("notification") {
+            value = notification.take();
+        }
+    }
+    if let Some(result) = value.pointer_mut("/params/result") {
+        value = result.take();
+    } else if let Some(result) = value.get_mut("result") {
+        value = result.take();
+    }
+
+    let item = match value.get("value") {
+        Some(inner) if inner.get("logs").is_some() => {
+            ReplayItem::Logs(serde_json::from_value(value)?)
+        }
+        Some(inner) if inner.get("block").is_some() => {
+            ReplayItem::Block(serde_json::from_value(value)?)
+        }
+        _ if value.get("transaction").is_some() => {
+            ReplayItem::Transaction(Box::new(serde_json::from_value(value)?))
+        }
+        _ => bail!("not a logsSubscribe, blockSubscribe or getTransaction message"),
+    };
+    Ok(ReplayLine { item, received_at })
+}
+
+/// How long to wait before `line`, after `previous`: the receive time difference when
+/// both lines have one, the slot difference otherwise, at most [`MAX_PACE_GAP`].
+fn pace(previous: Option<(u64, Option<u64>)>, line: (u64, Option<u64>), speed: u32) -> Duration {
+    let Some((previous_slot, previous_received_at)) = previous else {
+        return Duration::ZERO;
+    };
+    if speed == 0 {
+        return Duration::ZERO;
+    }
+    let (slot, received_at) = line;
+    let elapsed_ms = match (previous_received_at, received_at) {
+        (Some(previous), Some(current)) => current.saturating_sub(previous),
+        _ => slot.saturating_sub(previous_slot) * SLOT_DURATION_MS,
+    };
+    let wait = Duration::from_millis(elapsed_ms / speed as u64);
+    if wait > MAX_PACE_GAP {
+        info!(
+            "[replay] skipping a {:?} gap before slot {}; waiting {:?}",
+            wait, slot, MAX_PACE_GAP
+        );
+        return MAX_PACE_GAP;
+    }
+    wait
+}
+
+/// The files `path` stands for: itself, or the `.ndjson` / `.ndjson.gz` files of a
+/// directory in name order (the order `record` writes them in).
+fn replay_files(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| {
+            file.to_str()
+                .is_some_and(|name| name.ends_with(".ndjson") || name.ends_with(".ndjson.gz"))
+        })
+        .collect();
+    if files.is_empty() {
+        bail!("no .ndjson or .ndjson.gz files in {}", path.display());
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Reads the files on a blocking thread and hands their lines over as
+/// `(file, line number, line)`. A read error ends that file with a warning, e.g. the
+/// missing trailer of a gzip file still being recorded.
+fn spawn_reader(files: Vec<PathBuf>) -> mpsc::Receiver<(String, usize, String)> {
+    let (sender, receiver) = mpsc::channel(READ_AHEAD);
+    std::thread::spawn(move || {
+        for path in files {
+            let name = path.display().to_string();
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(err) => {
+                    warn!("[replay] cannot open {}: {}", name, err);
+                    continue;
+                }
+            };
+            let reader: Box<dyn Read> = if name.ends_with(".gz") {
+                Box::new(flate2::read::GzDecoder::new(file))
+            } else {
+                Box::new(file)
+            };
+            for (index, line) in BufReader::new(reader).lines().enumerate() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(err) => {
+                        warn!(
+                            "[replay] {}: stopped reading at line {}: {}",
+                            name,
+                            index + 1,
+                            err
+                        );
+                        break;
+                    }
+                };
+                if sender
+                    .blocking_send((name.clone(), index + 1, line))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+/// Replays the whole recording, then returns. Lines that don't parse are skipped with a
+/// warning.
+pub(crate) async fn run(replay_config: &ReplayConfig, pipeline: &LogPipeline) -> Result<()> {
+    let path = Path::new(&replay_config.path);
+    if !path.exists() {
+        bail!("replay file {} does not exist", replay_config.path);
+    }
+    let mut lines = spawn_reader(replay_files(path)?);
+
+    let mut previous: Option<(u64, Option<u64>)> = None;
+    let mut replayed = 0usize;
+    let mut skipped = 0usize;
+    while let Some((file, line_number, line)) = lines.recv().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line = match parse_line(&line) {
+            Ok(line) => line,
+            Err(err) => {
+                warn!("[replay] {}:{}: {}", file, line_number, err);
+                skipped += 1;
+                continue;
+            }
+        };
+
+        let position = (line.item.slot(), line.received_at);
+        tokio::time::sleep(pace(previous, position, replay_config.speed)).await;
+        // The clock never runs backwards: out-of-order lines play immediately
+        previous = Some(match previous {
+            Some((slot, received_at)) => (slot.max(position.0), received_at.max(position.1)),
+            None => position,
+        });
+
+        match line.item.parse(pipeline) {
+            Ok(mut log_contexts) => {
+                replayed += 1;
+                // The recording's receive time, when it has one, rather than now
+                if let Some(received_at) = line.received_at {
+                    stamp_received_at(&mut log_contexts, received_at);
+                }
+                pipeline.process(log_contexts).await;
+            }
+            Err(err) => {
+                warn!("[replay] {}:{}: {}", file, line_number, err);
+                skipped += 1;
+            }
+        }
+    }
+    info!(
+        "[replay] {}: replayed {} messages, skipped {}",
+        replay_config.path, replayed, skipped
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const SIGNATURE: &str =
+        "5h6xBEauJ3PK6SWCZ1PGjBvj8vDdWG3KpwATGy1ARAXFSDwt8GFXM7W5Ncn16wmqokgpiKRLuS83KUxyZyv2sUYv";
+
+    fn logs_response(slot: u64) -> Value {
+        json!({
+            "context": { "slot": slot },
+            "value": {
+                "signature": SIGNATURE,
+                "err": null,
+                "logs": [
+                    "Program 11111111111111111111111111111111 invoke [1]",
+                    "Program 11111111111111111111111111111111 success"
+                ]
+            }
+        })
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        let (replay, config_path) = parse_args(&args(&["incident.ndjson"])).unwrap();
+        assert_eq!(replay.path, "incident.ndjson");
+        assert_eq!(replay.speed, 1);
+        assert_eq!(config_path, None);
+
+        let (replay, config_path) = parse_args(&args(&[
+            "--speed",
+            "10",
+            "incident.ndjson",
+            "--config",
+            "c.json",
+        ]))
+        .unwrap();
+        assert_eq!(replay.speed, 10);
+        assert_eq!(config_path.as_deref(), Some("c.json"));
+
+        let (replay, _) = parse_args(&args(&["incident.ndjson", "--fast"])).unwrap();
+        assert_eq!(replay.speed, 0);
+
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["a.ndjson", "b.ndjson"])).is_err());
+        assert!(parse_args(&args(&["a.ndjson", "--speed", "x"])).is_err());
+        assert!(parse_args(&args(&["a.ndjson", "--verbose"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_line_recognizes_shapes() {
+        let bare = logs_response(7).to_string();
+        assert!(
+            matches!(parse_line(&bare).unwrap().item, ReplayItem::Logs(response) if response.context.slot == 7)
+        );
+
+        let notification = json!({
+            "jsonrpc": "2.0",
+            "method": "logsNotification",
+            "params": { "result": logs_response(8), "subscription": 3 }
+        })
+        .to_string();
+        assert_eq!(parse_line(&notification).unwrap().item.slot(), 8);
+
+        let block = json!({
+            "jsonrpc": "2.0",
+            "method": "blockNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": 9 },
+                    "value": {
+                        "slot": 9,
+                        "block": {
+                            "previousBlockhash": "11111111111111111111111111111111",
+                            "blockhash": "11111111111111111111111111111111",
+                            "parentSlot": 8,
+                            "transactions": [],
+                            "blockTime": 1_700_000_000,
+                            "blockHeight": 9
+                        },
+                        "err": null
+                    }
+                },
+                "subscription": 4
+            }
+        })
+        .to_string();
+        assert!(
+            matches!(parse_line(&block).unwrap().item, ReplayItem::Block(response) if response.value.slot == 9)
+        );
+
+        let transaction = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "slot": 10,
+                "blockTime": null,
+                "transaction": {
+                    "signatures": [SIGNATURE],
+                    "message": {
+                        "accountKeys": ["11111111111111111111111111111111"],
+                        "header": {
+                            "numRequiredSignatures": 1,
+                            "numReadonlySignedAccounts": 0,
+                            "numReadonlyUnsignedAccounts": 0
+                        },
+                        "recentBlockhash": "11111111111111111111111111111111",
+                        "instructions": []
+                    }
+                },
+                "meta": null
+            }
+        })
+        .to_string();
+        assert!(
+            matches!(parse_line(&transaction).unwrap().item, ReplayItem::Transaction(transaction) if transaction.slot == 10)
+        );
+
+        assert!(parse_line(r#"{"jsonrpc": "2.0", "id": 1, "result": 5}"#).is_err());
+        assert!(parse_line("not json").is_err());
+    }
+
+    #[test]
+    fn test_parse_line_unwraps_record_envelope() {
+        let recorded = json!({
+            "key": "all",
+            "source": "logsSubscribe",
+            "receivedAt": 1_700_000_000_123u64,
+            "notification": logs_response(11)
+        })
+        .to_string();
+        let line = parse_line(&recorded).unwrap();
+        assert_eq!(line.item.slot(), 11);
+        assert_eq!(line.received_at, Some(1_700_000_000_123));
+
+        assert_eq!(
+            parse_line(&logs_response(11).to_string())
+                .unwrap()
+                .received_at,
+            None
+        );
+    }
+
+    #[test]
+    fn test_pace() {
+        assert_eq!(pace(None, (100, None), 1), Duration::ZERO);
+        assert_eq!(pace(Some((100, None)), (100, None), 1), Duration::ZERO);
+        assert_eq!(pace(Some((100, None)), (99, None), 1), Duration::ZERO);
+        assert_eq!(
+            pace(Some((100, None)), (103, None), 1),
+            Duration::from_millis(1200)
+        );
+        assert_eq!(
+            pace(Some((100, None)), (103, None), 4),
+            Duration::from_millis(300)
+        );
+        assert_eq!(pace(Some((100, None)), (103, None), 0), Duration::ZERO);
+
+        // Receive times win over slots when both lines have one
+        assert_eq!(
+            pace(Some((100, Some(5_000))), (103, Some(5_250)), 1),
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            pace(Some((100, Some(5_000))), (103, Some(5_250)), 5),
+            Duration::from_millis(50)
+        );
+        assert_eq!(
+            pace(Some((100, Some(5_000))), (103, None), 1),
+            Duration::from_millis(1200)
+        );
+
+        // Long gaps are cut short
+        assert_eq!(pace(Some((100, None)), (200, None), 1), MAX_PACE_GAP);
+        assert_eq!(
+            pace(Some((100, Some(5_000))), (101, Some(3_605_000)), 10),
+            MAX_PACE_GAP
+        );
+        assert_eq!(
+            pace(Some((100, None)), (200, None), 10),
+            Duration::from_millis(4000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reader_replays_directory_in_order() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("sologger-replay-dir-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.ndjson"), "second\nthird\n").unwrap();
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"first\n").unwrap();
+        std::fs::write(dir.join("a.ndjson.gz"), encoder.finish().unwrap()).unwrap();
+        // An unfinished gzip file: its readable lines still replay
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"fourth\n").unwrap();
+        encoder.flush().unwrap();
+        std::fs::write(dir.join("c.ndjson.gz"), encoder.get_ref()).unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored\n").unwrap();
+
+        let files = replay_files(&dir).unwrap();
+        assert_eq!(files.len(), 3);
+        let mut receiver = spawn_reader(files);
+        let mut lines = Vec::new();
+        while let Some((_, line_number, line)) = receiver.recv().await {
+            lines.push((line_number, line));
+        }
+        assert_eq!(
+            lines,
+            vec![
+                (1, "first".to_string()),
+                (1, "second".to_string()),
+                (2, "third".to_string()),
+                (1, "fourth".to_string())
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
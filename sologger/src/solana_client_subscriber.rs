@@ -1,7 +1,8 @@
 //! Live ingestion: supervised WebSocket subscriptions (logsSubscribe or blockSubscribe)
 //! that auto-reconnect with exponential backoff, detect slot gaps across reconnects,
 //! and run every parsed batch through the shared [`LogPipeline`] — truncation backfill,
-//! IDL enrichment, telemetry/webhook export, and the configured log transport.
+//! IDL enrichment, telemetry/webhook export, and the configured log transport. The
+//! optional push endpoint (`httpIngest`) feeds the same pipeline.
 
 use crate::log_processor::log_contexts_from_logs;
 use crate::sologger_config::{LogSource, SologgerConfig};
@@ -21,39 +22,96 @@ use sologger_log_context::programs_selector::ProgramsSelector;
 use sologger_log_context::sologger_log_context::LogContext;
 use sologger_log_transformer::log_context_transformer::{
     from_encoded_confirmed_transaction, from_rpc_response, from_ui_confirmed_block,
+    from_yellowstone_transaction,
 };
+use sologger_log_transformer::yellowstone_proto::SubscribeRequestFilterTransactions;
+use std::path::Path;
 use std::str::FromStr;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::io::AsyncReadExt;
 
-const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+use crate::block_time::BlockTimeCache;
+use crate::idl_directory::IdlDirectory;
+use crate::pipeline_workers::PipelineWorkers;
+use crate::recorder::Recorder;
+use crate::yellowstone_client::{transaction_filter, YellowstoneStream};
+
+pub(crate) const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
 const MAX_BACKOFF: Duration = Duration::from_secs(30);
 
 /// Everything needed to take a freshly parsed batch to the transports. Shared by the
-/// live subscription tasks and the historical backfill.
-pub(crate) struct LogPipeline {
-    pub program_selector: ProgramsSelector,
-    pub idl_registry: IdlRegistry,
-    pub rpc_client: Option<RpcClient>,
-    pub backfill_truncated: bool,
+/// live subscription tasks, the historical backfill and the Geyser plugin.
+pub struct LogPipeline {
+    pub(crate) program_selector: ProgramsSelector,
+    /// Swapped whole when the IDL directory changes; readers clone the inner Arc
+    pub(crate) idl_registry: RwLock<Arc<IdlRegistry>>,
+    pub(crate) rpc_client: Option<RpcClient>,
+    pub(crate) backfill_truncated: bool,
+    /// Resolves block times for records that arrive without one (`resolveBlockTime`)
+    pub(crate) block_times: Option<BlockTimeCache>,
+    /// Captures raw subscription notifications before parsing (`record`)
+    pub(crate) recorder: Option<Recorder>,
 }
 
 impl LogPipeline {
-    /// Runs one parsed batch through truncation backfill, IDL enrichment, telemetry and
-    /// webhook export, and the log transport.
-    pub(crate) async fn process(&self, log_contexts: Vec<LogContext>) {
+    /// A pipeline without the RPC-backed stages (truncation backfill, block times), for
+    /// sources that deliver complete transactions themselves, e.g. the Geyser plugin.
+    /// Applies the config's IDL decoding settings to the registry.
+    pub fn new(
+        sologger_config: &SologgerConfig,
+        program_selector: &ProgramsSelector,
+        idl_registry: &IdlRegistry,
+    ) -> Self {
+        let mut idl_registry = idl_registry.clone();
+        idl_registry.set_log_message_events(sologger_config.decode_log_message_events);
+        idl_registry.set_decode_mode(sologger_config.decode_mode);
+        LogPipeline {
+            program_selector: program_selector.clone(),
+            idl_registry: RwLock::new(Arc::new(idl_registry)),
+            rpc_client: None,
+            backfill_truncated: false,
+            block_times: None,
+            recorder: None,
+        }
+    }
+
+    /// The programs whose logs this pipeline keeps.
+    pub fn program_selector(&self) -> &ProgramsSelector {
+        &self.program_selector
+    }
+
+    /// The IDL registry currently used for enrichment.
+    pub(crate) fn idl_registry(&self) -> Arc<IdlRegistry> {
+        Arc::clone(&self.idl_registry.read().unwrap())
+    }
+
+    /// Atomically replaces the IDL registry; batches already enriching keep the old one.
+    pub(crate) fn set_idl_registry(&self, idl_registry: IdlRegistry) {
+        *self.idl_registry.write().unwrap() = Arc::new(idl_registry);
+    }
+
+    /// Runs one parsed batch through truncation backfill, timestamping, IDL enrichment,
+    /// telemetry and webhook export, and the log transport. Sources stamp `received_at`
+    /// when a notification arrives; records that still lack it get the time now.
+    pub async fn process(&self, log_contexts: Vec<LogContext>) {
         if log_contexts.is_empty() {
             return;
         }
+        let received_at = unix_millis(SystemTime::now());
         let mut log_contexts = if self.backfill_truncated {
             self.refetch_truncated(log_contexts).await
         } else {
             log_contexts
         };
 
+        stamp_received_at(&mut log_contexts, received_at);
+        if let (Some(block_times), Some(rpc_client)) = (&self.block_times, &self.rpc_client) {
+            block_times.fill(rpc_client, &mut log_contexts).await;
+        }
+
         // Decode Anchor events / resolve error names for programs with a configured IDL
-        self.idl_registry.enrich_all(&mut log_contexts);
+        self.idl_registry().enrich_all(&mut log_contexts);
 
         // Export transaction traces / metrics when enabled in the OTel config
         #[cfg(feature = "enable_otel")]
@@ -128,8 +186,15 @@ impl LogPipeline {
                     slot,
                     &self.program_selector,
                 ) {
-                    Ok(full) if !full.is_empty() => {
+                    Ok(mut full) if !full.is_empty() => {
                         info!("backfilled truncated logs for {}", signature_str);
+                        // Still the notification's arrival, not the refetch's, and its
+                        // position in the block, which getTransaction doesn't return
+                        for context in full.iter_mut() {
+                            context.received_at = group[0].received_at;
+                            context.transaction_index =
+                                context.transaction_index.or(group[0].transaction_index);
+                        }
                         full
                     }
                     Ok(_) => group,
@@ -150,11 +215,28 @@ impl LogPipeline {
     }
 }
 
+pub(crate) fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Sets `received_at` on the records that don't carry one yet. Sources call it as a
+/// notification arrives, so time spent queued for a pipeline worker counts as latency.
+pub(crate) fn stamp_received_at(log_contexts: &mut [LogContext], received_at: u64) {
+    for log_context in log_contexts.iter_mut() {
+        log_context.received_at.get_or_insert(received_at);
+    }
+}
+
 /// What one supervised task subscribes to.
 #[derive(Clone, Debug)]
 enum SubscriptionKind {
     Logs(RpcTransactionLogsFilter),
     Block(RpcBlockSubscribeFilter),
+    Yellowstone {
+        filter: SubscribeRequestFilterTransactions,
+        x_token: Option<String>,
+    },
 }
 
 #[cfg(feature = "solana_client_subscriber")]
@@ -176,17 +258,59 @@ pub async fn start_client(
     };
     trace!("commitment_config: {:?}", commitment_config);
 
-    // The HTTP client is only needed for the backfill paths
-    let rpc_client = (sologger_config.backfill_truncated || sologger_config.backfill.is_some())
+    // The HTTP client is only needed for the backfill paths, IDL fetching and block times
+    let rpc_client = (sologger_config.backfill_truncated
+        || sologger_config.backfill.is_some()
+        || sologger_config.fetch_idls
+        || sologger_config.resolve_block_time)
         .then(|| RpcClient::new(sologger_config.http_url()));
 
+    let mut idl_registry = idl_registry.clone();
+    idl_registry.set_log_message_events(sologger_config.decode_log_message_events);
+    idl_registry.set_decode_mode(sologger_config.decode_mode);
+    if let (true, Some(rpc_client)) = (sologger_config.fetch_idls, &rpc_client) {
+        let cache_dir = sologger_config.idl_cache_dir.as_deref().map(Path::new);
+        crate::idl_loader::load_missing(rpc_client, program_selector, cache_dir, &mut idl_registry)
+            .await;
+    }
+
+    // IDLs in idlDirectory overlay the configured/fetched ones and are reloaded on change
+    let mut idl_directory = sologger_config.idl_directory.as_deref().map(IdlDirectory::new);
+    let base_idl_registry = idl_registry.clone();
+    if let Some(directory_idls) = idl_directory
+        .as_mut()
+        .and_then(IdlDirectory::reload_if_changed)
+    {
+        idl_registry.merge(&directory_idls);
+    }
+
     let pipeline = Arc::new(LogPipeline {
         program_selector: program_selector.clone(),
-        idl_registry: idl_registry.clone(),
+        idl_registry: RwLock::new(Arc::new(idl_registry)),
         rpc_client,
         backfill_truncated: sologger_config.backfill_truncated,
+        block_times: sologger_config
+            .resolve_block_time
+            .then(BlockTimeCache::default),
+        recorder: sologger_config
+            .record
+            .as_ref()
+            .map(Recorder::start)
+            .transpose()?,
     });
 
+    // Offline replay stands in for the live sources, and ends with the recording
+    if sologger_config.source == LogSource::File {
+        let Some(replay_config) = &sologger_config.replay else {
+            anyhow::bail!("source file requires replay");
+        };
+        return crate::replay::run(replay_config, &pipeline).await;
+    }
+    if sologger_config.source == LogSource::SolanaLogs {
+        return crate::solana_logs::run(sologger_config.solana_logs_path.as_deref(), &pipeline)
+            .await;
+    }
+
     // 5.3 Historical backfill, before the live tail starts
     if let Some(backfill_config) = &sologger_config.backfill {
         crate::backfill::run(backfill_config, program_selector, &pipeline).await?;
@@ -196,23 +320,81 @@ pub async fn start_client(
         }
     }
 
+    let idl_watcher = idl_directory.map(|directory| {
+        tokio::spawn(crate::idl_directory::watch(
+            directory,
+            base_idl_registry,
+            Arc::clone(&pipeline),
+            Duration::from_millis(sologger_config.idl_reload_interval_ms.max(1)),
+        ))
+    });
+
     #[cfg(feature = "enable_tokio_rt_metrics")]
     enable_tokio_rt_metrics();
 
     let subscriptions = build_subscriptions(sologger_config, program_selector);
     trace!("subscriptions: {:?}", subscriptions);
 
+    // The gRPC source connects to its own endpoint; the others to the WebSocket RPC
+    let subscription_url = match sologger_config.source {
+        LogSource::YellowstoneGrpc if sologger_config.grpc_url.is_empty() => {
+            anyhow::bail!("source yellowstoneGrpc requires grpcUrl");
+        }
+        LogSource::YellowstoneGrpc => sologger_config.grpc_url.clone(),
+        LogSource::HttpPush if sologger_config.http_ingest.is_none() => {
+            anyhow::bail!("source httpPush requires httpIngest");
+        }
+        LogSource::LogsSubscribe
+        | LogSource::BlockSubscribe
+        | LogSource::HttpPush
+        | LogSource::File
+        | LogSource::SolanaLogs => sologger_config.rpc_url.clone(),
+    };
+    // Subscriptions hand their batches to the workers and go straight back to reading
+    let workers = Arc::new(PipelineWorkers::start(
+        Arc::clone(&pipeline),
+        sologger_config.pipeline_workers,
+        sologger_config.pipeline_queue_capacity,
+    ));
     let mut join_handles = Vec::with_capacity(subscriptions.len());
     for (key, kind) in subscriptions {
         join_handles.push(tokio::spawn(supervise_subscription(
-            sologger_config.rpc_url.clone(),
+            subscription_url.clone(),
             key,
             kind,
             commitment_config,
-            Arc::clone(&pipeline),
+            Arc::clone(&workers),
         )));
     }
 
+    // Pushed transactions join the same workers
+    if let Some(http_ingest) = &sologger_config.http_ingest {
+        let listener = crate::http_ingest::bind(http_ingest).await?;
+        join_handles.push(tokio::spawn(crate::http_ingest::run(
+            listener,
+            Arc::new(http_ingest.clone()),
+            Arc::clone(&workers),
+        )));
+    }
+
+    // Account state subscriptions share the pipeline's (hot-reloaded) IDL registry
+    if let Some(account_subscriptions) = &sologger_config.account_subscriptions {
+        let tracker = Arc::new(crate::account_subscriber::AccountTracker::default());
+        let subscriptions =
+            crate::account_subscriber::build_account_subscriptions(account_subscriptions);
+        for subscription in subscriptions {
+            join_handles.push(tokio::spawn(
+                crate::account_subscriber::supervise_account_subscription(
+                    sologger_config.rpc_url.clone(),
+                    subscription,
+                    commitment_config,
+                    Arc::clone(&pipeline),
+                    Arc::clone(&tracker),
+                ),
+            ));
+        }
+    }
+
     // Wait for input or some application-specific shutdown condition.
     tokio::io::stdin().read_u8().await?;
 
@@ -220,12 +402,18 @@ pub async fn start_client(
     for handle in join_handles {
         handle.abort();
     }
+    if let Some(handle) = idl_watcher {
+        handle.abort();
+    }
+    // What the subscriptions already handed over is still processed
+    workers.shutdown().await;
 
     Ok(())
 }
 
 /// One subscription per selected program (or a single "all" subscription), for the
-/// configured source.
+/// configured source. Yellowstone filters take every selected program at once, so that
+/// source always gets a single subscription, and httpPush and file none.
 fn build_subscriptions(
     sologger_config: &SologgerConfig,
     program_selector: &ProgramsSelector,
@@ -252,6 +440,16 @@ fn build_subscriptions(
                 }
             }
         }
+        LogSource::YellowstoneGrpc => {
+            subscriptions.push((
+                "yellowstone".to_string(),
+                SubscriptionKind::Yellowstone {
+                    filter: transaction_filter(program_selector, sologger_config.all_with_votes),
+                    x_token: sologger_config.grpc_x_token.clone(),
+                },
+            ));
+        }
+        LogSource::HttpPush | LogSource::File | LogSource::SolanaLogs => {}
         LogSource::BlockSubscribe => {
             if program_selector.select_all_programs {
                 subscriptions.push((
@@ -281,7 +479,7 @@ async fn supervise_subscription(
     key: String,
     kind: SubscriptionKind,
     commitment: Option<CommitmentConfig>,
-    pipeline: Arc<LogPipeline>,
+    workers: Arc<PipelineWorkers>,
 ) {
     let mut backoff = INITIAL_BACKOFF;
     let mut last_seen_slot: Option<u64> = None;
@@ -297,7 +495,7 @@ async fn supervise_subscription(
             &key,
             &kind,
             commitment,
-            &pipeline,
+            &workers,
             &mut last_seen_slot,
             is_reconnect,
         )
@@ -318,7 +516,7 @@ async fn supervise_subscription(
     }
 }
 
-fn next_backoff(current: Duration) -> Duration {
+pub(crate) fn next_backoff(current: Duration) -> Duration {
     (current * 2).min(MAX_BACKOFF)
 }
 
@@ -328,18 +526,18 @@ fn slot_gap(last_seen: u64, first_new: u64) -> Option<u64> {
     (first_new > last_seen + 1).then(|| first_new - last_seen - 1)
 }
 
-/// Connects, subscribes, and pumps the stream until it ends. Returns how many
-/// notifications were processed on this connection.
+/// Connects, subscribes, and pumps the stream into the pipeline workers until it ends.
+/// Returns how many notifications were parsed on this connection.
 async fn connect_and_stream(
     url: &str,
     key: &str,
     kind: &SubscriptionKind,
     commitment: Option<CommitmentConfig>,
-    pipeline: &LogPipeline,
+    workers: &PipelineWorkers,
     last_seen_slot: &mut Option<u64>,
     is_reconnect: bool,
 ) -> Result<u64> {
-    let client = PubsubClient::new(url).await?;
+    let pipeline = workers.pipeline();
     let mut processed: u64 = 0;
     let mut gap_checked = false;
 
@@ -367,6 +565,7 @@ async fn connect_and_stream(
 
     match kind {
         SubscriptionKind::Logs(filter) => {
+            let client = PubsubClient::new(url).await?;
             let (mut notifications, _unsubscribe) = client
                 .logs_subscribe(
                     filter.clone(),
@@ -378,11 +577,16 @@ async fn connect_and_stream(
             info!("[{}] subscribed via logsSubscribe", key);
 
             while let Some(response) = notifications.next().await {
+                let received_at = unix_millis(SystemTime::now());
+                if let Some(recorder) = &pipeline.recorder {
+                    recorder.record(key, "logsSubscribe", &response);
+                }
                 observe_slot(response.context.slot, last_seen_slot, &mut gap_checked);
                 match from_rpc_response(&response, &pipeline.program_selector) {
-                    Ok(log_contexts) => {
+                    Ok(mut log_contexts) => {
                         processed += 1;
-                        pipeline.process(log_contexts).await;
+                        stamp_received_at(&mut log_contexts, received_at);
+                        workers.submit(log_contexts).await;
                     }
                     Err(err) => warn!("[{}] failed to parse notification: {}", key, err),
                 }
@@ -396,11 +600,16 @@ async fn connect_and_stream(
                 show_rewards: Some(false),
                 max_supported_transaction_version: Some(0),
             };
+            let client = PubsubClient::new(url).await?;
             let (mut notifications, _unsubscribe) =
                 client.block_subscribe(filter.clone(), Some(config)).await?;
             info!("[{}] subscribed via blockSubscribe", key);
 
             while let Some(response) = notifications.next().await {
+                let received_at = unix_millis(SystemTime::now());
+                if let Some(recorder) = &pipeline.recorder {
+                    recorder.record(key, "blockSubscribe", &response);
+                }
                 let slot = response.value.slot;
                 observe_slot(slot, last_seen_slot, &mut gap_checked);
                 let Some(block) = response.value.block else {
@@ -410,14 +619,34 @@ async fn connect_and_stream(
                     continue;
                 }
                 match from_ui_confirmed_block(block, slot, &pipeline.program_selector) {
-                    Ok(log_contexts) => {
+                    Ok(mut log_contexts) => {
                         processed += 1;
-                        pipeline.process(log_contexts).await;
+                        stamp_received_at(&mut log_contexts, received_at);
+                        workers.submit(log_contexts).await;
                     }
                     Err(err) => warn!("[{}] failed to parse block {}: {}", key, slot, err),
                 }
             }
         }
+        SubscriptionKind::Yellowstone { filter, x_token } => {
+            let mut stream =
+                YellowstoneStream::subscribe(url, x_token.as_deref(), filter.clone(), commitment)
+                    .await?;
+            info!("[{}] subscribed via Yellowstone gRPC", key);
+
+            while let Some(update) = stream.next_transaction().await? {
+                let received_at = unix_millis(SystemTime::now());
+                observe_slot(update.slot, last_seen_slot, &mut gap_checked);
+                match from_yellowstone_transaction(&update, &pipeline.program_selector) {
+                    Ok(mut log_contexts) => {
+                        processed += 1;
+                        stamp_received_at(&mut log_contexts, received_at);
+                        workers.submit(log_contexts).await;
+                    }
+                    Err(err) => warn!("[{}] failed to parse transaction update: {}", key, err),
+                }
+            }
+        }
     }
 
     Ok(processed)
@@ -441,6 +670,8 @@ fn enable_tokio_rt_metrics() {
 mod tests {
     use super::*;
     use crate::sologger_config::LogSource;
+    use solana_rpc_client_api::request::RpcRequest;
+    use std::collections::HashMap;
 
     fn create_test_config() -> SologgerConfig {
         SologgerConfig {
@@ -526,6 +757,33 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_build_subscriptions_yellowstone_source() {
+        let mut config = create_test_config();
+        config.source = LogSource::YellowstoneGrpc;
+        config.grpc_x_token = Some("secret".to_string());
+
+        let subscriptions = build_subscriptions(&config, &create_test_program_selector());
+        assert_eq!(subscriptions.len(), 1);
+        match &subscriptions[0].1 {
+            SubscriptionKind::Yellowstone { filter, x_token } => {
+                assert_eq!(filter.account_include.len(), 2);
+                assert_eq!(x_token.as_deref(), Some("secret"));
+            }
+            other => panic!("expected Yellowstone subscription, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_build_subscriptions_http_push_source() {
+        let mut config = create_test_config();
+        config.source = LogSource::HttpPush;
+
+        // Pushed transactions are the only feed
+        let subscriptions = build_subscriptions(&config, &create_test_program_selector());
+        assert!(subscriptions.is_empty());
+    }
+
     #[test]
     fn test_next_backoff_doubles_and_caps() {
         let mut backoff = INITIAL_BACKOFF;
@@ -572,9 +830,11 @@ mod tests {
     async fn test_pipeline_without_rpc_client_passes_contexts_through() {
         let pipeline = LogPipeline {
             program_selector: ProgramsSelector::new_all_programs(),
-            idl_registry: IdlRegistry::new(),
+            idl_registry: RwLock::new(Arc::new(IdlRegistry::new())),
             rpc_client: None,
             backfill_truncated: true,
+            block_times: None,
+            recorder: None,
         };
 
         let logs: Vec<String> = vec![
@@ -596,6 +856,52 @@ mod tests {
         assert_eq!(resolved, parse());
     }
 
+    #[tokio::test]
+    async fn test_refetch_truncated_keeps_notification_fields() {
+        let logs: Vec<String> = vec![
+            "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+            "Program 11111111111111111111111111111111 success".to_string(),
+        ];
+        // The RPC mock's stored transaction, with complete logs
+        let mut transaction = RpcClient::new_mock("succeeds".to_string())
+            .get_transaction(&Signature::default(), UiTransactionEncoding::Json)
+            .await
+            .unwrap();
+        transaction.transaction.meta.as_mut().unwrap().log_messages = Some(logs.clone()).into();
+        let mocks = HashMap::from([(
+            RpcRequest::GetTransaction,
+            serde_json::to_value(&transaction).unwrap(),
+        )]);
+        let pipeline = LogPipeline {
+            program_selector: ProgramsSelector::new_all_programs(),
+            idl_registry: RwLock::new(Arc::new(IdlRegistry::new())),
+            rpc_client: Some(RpcClient::new_mock_with_mocks(
+                "succeeds".to_string(),
+                mocks,
+            )),
+            backfill_truncated: true,
+            block_times: None,
+            recorder: None,
+        };
+
+        let mut truncated = LogContext::parse_logs(
+            &vec![logs[0].clone(), "Log truncated".to_string()],
+            "".to_string(),
+            &ProgramsSelector::new_all_programs(),
+            2,
+            Signature::from([1; 64]).to_string(),
+        );
+        truncated[0].received_at = Some(1_700_000_000_123);
+        truncated[0].transaction_index = Some(4);
+
+        let resolved = pipeline.refetch_truncated(truncated).await;
+        assert_eq!(resolved.len(), 1);
+        assert_ne!(resolved[0].invoke_result, "Log truncated");
+        assert_eq!(resolved[0].block_time, Some(1628633791));
+        assert_eq!(resolved[0].received_at, Some(1_700_000_000_123));
+        assert_eq!(resolved[0].transaction_index, Some(4));
+    }
+
     #[cfg(feature = "enable_tokio_rt_metrics")]
     #[tokio::test]
     async fn test_enable_tokio_rt_metrics() {
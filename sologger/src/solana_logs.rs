@@ -0,0 +1,79 @@
+//! The "solanaLogs" source (`solana logs | sologger --stdin`): reads the text output of
+//! the `solana logs` CLI from stdin or `solanaLogsPath`, groups it per transaction and
+//! runs each one through the shared [`LogPipeline`]. Meant for local development against
+//! `solana-test-validator`, where no WebSocket setup is needed.
+
+use anyhow::Result;
+use log::{info, warn};
+use sologger_log_transformer::cli_logs::{CliLogsParser, CliTransaction};
+use sologger_log_transformer::log_context_transformer::from_cli_transaction;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+
+use crate::log_subscriber::LogPipeline;
+
+/// `solana logs` prints a transaction in one burst; once the input has been quiet this
+/// long, the transaction in progress is complete rather than waiting for the next header.
+const IDLE_FLUSH: Duration = Duration::from_millis(500);
+
+/// Reads until the end of the input: stdin, or the file at `path`.
+pub(crate) async fn run(path: Option<&str>, pipeline: &LogPipeline) -> Result<()> {
+    match path {
+        Some(path) => {
+            let file = tokio::fs::File::open(path)
+                .await
+                .map_err(|err| anyhow::anyhow!("cannot open {}: {}", path, err))?;
+            info!("[solana-logs] reading {}", path);
+            process_lines(BufReader::new(file), pipeline).await
+        }
+        None => {
+            info!("[solana-logs] reading `solana logs` output from stdin");
+            process_lines(BufReader::new(tokio::io::stdin()), pipeline).await
+        }
+    }
+}
+
+async fn process_lines<R: AsyncBufRead + Unpin>(reader: R, pipeline: &LogPipeline) -> Result<()> {
+    let mut lines = reader.lines();
+    let mut parser = CliLogsParser::new();
+    let mut transactions = 0usize;
+    loop {
+        // next_line is cancel safe, so timing out loses no input
+        let line = match tokio::time::timeout(IDLE_FLUSH, lines.next_line()).await {
+            Ok(line) => line?,
+            Err(_) => {
+                if let Some(transaction) = parser.finish() {
+                    transactions += 1;
+                    process(&transaction, pipeline).await;
+                }
+                continue;
+            }
+        };
+        let Some(line) = line else {
+            break;
+        };
+        if let Some(transaction) = parser.push_line(&line) {
+            transactions += 1;
+            process(&transaction, pipeline).await;
+        }
+    }
+    if let Some(transaction) = parser.finish() {
+        transactions += 1;
+        process(&transaction, pipeline).await;
+    }
+    info!(
+        "[solana-logs] end of input; {} transactions processed",
+        transactions
+    );
+    Ok(())
+}
+
+async fn process(transaction: &CliTransaction, pipeline: &LogPipeline) {
+    match from_cli_transaction(transaction, &pipeline.program_selector) {
+        Ok(log_contexts) => pipeline.process(log_contexts).await,
+        Err(err) => warn!(
+            "[solana-logs] failed to parse transaction {} in slot {}: {}",
+            transaction.signature, transaction.slot, err
+        ),
+    }
+}
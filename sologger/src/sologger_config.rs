@@ -1,9 +1,10 @@
 use serde_derive::{Deserialize, Serialize};
+use sologger_idl_decoder::DecodeMode;
 #[cfg(test)]
 use serde_json::json;
 
 /// This is the main configuration file for sologger. The location of this file is specified by the `SOLOGGER_APP_CONFIG_LOC` environment variable or as the first argument via the cargo run command.
-#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SologgerConfig {
     /// The location of the log4rs config file
@@ -18,24 +19,101 @@ pub struct SologgerConfig {
     /// The URL of the RPC endpoint to connect to
     pub rpc_url: String,
     /// The HTTP RPC endpoint used for getTransaction / getSignaturesForAddress calls
-    /// (truncation backfill and historical backfill). When empty, it is derived from
-    /// `rpcUrl`: ws(s):// becomes http(s)://, and port 8900 becomes 8899 (the local
-    /// validator convention).
+    /// (truncation backfill and historical backfill) and getAccountInfo (`fetchIdls`).
+    /// When empty, it is derived from `rpcUrl`: ws(s):// becomes http(s)://, and port
+    /// 8900 becomes 8899 (the local validator convention).
     #[serde(default)]
     pub rpc_http_url: String,
-    /// The subscription used as the log source: "logsSubscribe" (default) or
-    /// "blockSubscribe". blockSubscribe is not enabled on every RPC provider.
+    /// The subscription used as the log source: "logsSubscribe" (default),
+    /// "blockSubscribe", "yellowstoneGrpc", "httpPush", "file", or "solanaLogs".
+    /// blockSubscribe is not enabled on every RPC provider; yellowstoneGrpc connects to
+    /// `grpcUrl` instead of `rpcUrl`; httpPush subscribes to nothing and only ingests what
+    /// is POSTed to `httpIngest`; file replays the recording named in `replay` and exits;
+    /// solanaLogs parses `solana logs` CLI output from stdin (or `solanaLogsPath`) and
+    /// exits at the end of the input.
     #[serde(default)]
     pub source: LogSource,
+    /// The Yellowstone gRPC (Dragon's Mouth) endpoint used by the "yellowstoneGrpc" source
+    #[serde(default)]
+    pub grpc_url: String,
+    /// The `x-token` sent to `grpcUrl`, for providers that require one
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grpc_x_token: Option<String>,
+    /// Optional embedded HTTP endpoint that accepts transactions pushed by a provider's
+    /// webhook. Runs alongside any source; required by "httpPush".
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_ingest: Option<HttpIngestConfig>,
+    /// Optional recording of every raw logsSubscribe / blockSubscribe notification, for
+    /// later replay with the "file" source
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record: Option<RecordConfig>,
+    /// The recording replayed by the "file" source
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay: Option<ReplayConfig>,
+    /// The file of `solana logs` output read by the "solanaLogs" source, instead of stdin
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solana_logs_path: Option<String>,
     /// When a transaction's logs arrive truncated ("Log truncated"), refetch the full
     /// transaction over HTTP and re-parse it. On by default.
     #[serde(default = "default_true")]
     pub backfill_truncated: bool,
+    /// Resolve `block_time` for logsSubscribe records, whose notifications carry only the
+    /// slot, with a cached getBlockTime call per slot on the HTTP RPC. Off by default.
+    #[serde(default)]
+    pub resolve_block_time: bool,
+    /// Number of pipeline workers that process what live subscriptions receive, so a slow
+    /// truncation refetch or transport doesn't hold up reading the WebSocket. Batches of
+    /// the same transaction (blocks: of the same slot) always go to the same worker, in
+    /// order. 0 processes inline on the subscription task.
+    #[serde(default = "default_pipeline_workers")]
+    pub pipeline_workers: usize,
+    /// Batches each pipeline worker queues before reading the subscriptions waits for it
+    #[serde(default = "default_pipeline_queue_capacity")]
+    pub pipeline_queue_capacity: usize,
     /// Optional historical backfill run at startup: replays past transactions of the
     /// selected programs via getSignaturesForAddress + getTransaction.
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub backfill: Option<BackfillConfig>,
+    /// Fetch the on-chain Anchor IDL of every selected program that has no entry in the
+    /// `idls` map, via getAccountInfo on the HTTP RPC. Off by default.
+    #[serde(default)]
+    pub fetch_idls: bool,
+    /// Directory where fetched IDLs are cached as `<program id>.json`. A cached IDL is
+    /// used instead of refetching; delete the file to pick up an IDL upgrade.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idl_cache_dir: Option<String>,
+    /// Directory of Anchor IDL JSON files, each keyed by the IDL's own `address` (or a
+    /// `<program id>.json` file name). Watched while running: added or changed IDLs take
+    /// effect without a restart, and replace `idls` entries for the same program.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idl_directory: Option<String>,
+    /// How often `idlDirectory` is checked for changes
+    #[serde(default = "default_idl_reload_interval_ms")]
+    pub idl_reload_interval_ms: u64,
+    /// Also decode events emitted as base64 `Program log:` messages, as Anchor did before
+    /// 0.24. For backfilling the history of programs built with those versions.
+    #[serde(default)]
+    pub decode_log_message_events: bool,
+    /// How payloads that match an event but don't fit its layout are handled: "standard"
+    /// (default) drops them, "strict" also rejects bytes left after the last field, and
+    /// "lenient" keeps the fields decoded before the failure. Failures are recorded in
+    /// each record's `decode_errors`.
+    #[serde(default)]
+    pub decode_mode: DecodeMode,
+    /// Optional account state subscriptions: accounts of the listed programs
+    /// (programSubscribe) and individual accounts (accountSubscribe) are decoded with the
+    /// owner's IDL `accounts` and emitted as account-change records.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_subscriptions: Option<AccountSubscriptionsConfig>,
     /// The measure of the network confirmation and stake levels on a particular block.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
@@ -45,11 +123,57 @@ pub struct SologgerConfig {
     pub all_with_votes: bool,
 }
 
+// Hand-written so programmatic construction gets the same defaults as deserialization.
+impl Default for SologgerConfig {
+    fn default() -> Self {
+        Self {
+            log4rs_config_location: String::new(),
+            opentelemetry_config_location: String::new(),
+            webhook_config_location: String::new(),
+            rpc_url: String::new(),
+            rpc_http_url: String::new(),
+            source: LogSource::default(),
+            grpc_url: String::new(),
+            grpc_x_token: None,
+            http_ingest: None,
+            record: None,
+            replay: None,
+            solana_logs_path: None,
+            backfill_truncated: default_true(),
+            resolve_block_time: false,
+            pipeline_workers: default_pipeline_workers(),
+            pipeline_queue_capacity: default_pipeline_queue_capacity(),
+            backfill: None,
+            fetch_idls: false,
+            idl_cache_dir: None,
+            idl_directory: None,
+            idl_reload_interval_ms: default_idl_reload_interval_ms(),
+            decode_log_message_events: false,
+            decode_mode: DecodeMode::default(),
+            account_subscriptions: None,
+            commitment_level: None,
+            all_with_votes: false,
+        }
+    }
+}
+
 fn default_true() -> bool {
     true
 }
 
-/// Which WebSocket subscription feeds the parser.
+fn default_idl_reload_interval_ms() -> u64 {
+    2000
+}
+
+fn default_pipeline_workers() -> usize {
+    4
+}
+
+fn default_pipeline_queue_capacity() -> usize {
+    1024
+}
+
+/// Which subscription feeds the parser.
 #[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum LogSource {
     #[default]
@@ -57,6 +181,100 @@ pub enum LogSource {
     LogsSubscribe,
     #[serde(rename = "blockSubscribe")]
     BlockSubscribe,
+    #[serde(rename = "yellowstoneGrpc")]
+    YellowstoneGrpc,
+    #[serde(rename = "httpPush")]
+    HttpPush,
+    #[serde(rename = "file")]
+    File,
+    #[serde(rename = "solanaLogs")]
+    SolanaLogs,
+}
+
+/// Which accounts to watch. Every write produces a record with the decoded state and a
+/// diff against the previous write seen for the same account.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSubscriptionsConfig {
+    /// Program IDs whose accounts are all watched, via programSubscribe
+    #[serde(default)]
+    pub programs: Vec<String>,
+    /// Individual account addresses to watch, via accountSubscribe
+    #[serde(default)]
+    pub accounts: Vec<String>,
+}
+
+/// Push ingestion: transactions POSTed as getTransaction-shaped JSON (one object, or an
+/// array of them) run through the normal parsing/enrichment/export pipeline.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpIngestConfig {
+    /// Address the endpoint listens on
+    #[serde(default = "default_http_ingest_bind_address")]
+    pub bind_address: String,
+    /// Path transactions are POSTed to; other paths answer 404
+    #[serde(default = "default_http_ingest_path")]
+    pub path: String,
+    /// Shared secret expected in the `Authorization` header, either verbatim or as a
+    /// `Bearer` token. Must not be empty.
+    pub secret: String,
+    /// Largest accepted request body, in bytes
+    #[serde(default = "default_http_ingest_max_body_bytes")]
+    pub max_body_bytes: usize,
+}
+
+fn default_http_ingest_bind_address() -> String {
+    "127.0.0.1:8080".to_string()
+}
+
+fn default_http_ingest_path() -> String {
+    "/transactions".to_string()
+}
+
+fn default_http_ingest_max_body_bytes() -> usize {
+    16 * 1024 * 1024
+}
+
+/// Capture of production traffic: each raw notification is written, before parsing, as
+/// one NDJSON line with its subscription key and receive time.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordConfig {
+    /// Directory the recording files are written to; created when missing
+    pub directory: String,
+    /// Start a new file once the current one holds this many (uncompressed) bytes
+    #[serde(default = "default_record_max_file_bytes")]
+    pub max_file_bytes: u64,
+    /// Keep at most this many files, deleting the oldest; unlimited when unset
+    #[serde(default)]
+    pub max_files: Option<usize>,
+    /// gzip-compress the files (`.ndjson.gz`)
+    #[serde(default)]
+    pub gzip: bool,
+}
+
+fn default_record_max_file_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+/// Offline replay of an NDJSON recording: one logsSubscribe notification, blockSubscribe
+/// notification or getTransaction response per line, as bare results, whole JSON-RPC
+/// messages or lines written by `record`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayConfig {
+    /// The NDJSON file to replay (`.gz` files are decompressed), or a `record` directory,
+    /// whose files are replayed in name order
+    pub path: String,
+    /// Playback speed relative to the recording, paced by receive time for `record` lines
+    /// and by slot (400ms per slot) otherwise: 1 is the original pace, 10 ten times
+    /// faster, and 0 as fast as possible
+    #[serde(default = "default_replay_speed")]
+    pub speed: u32,
+}
+
+fn default_replay_speed() -> u32 {
+    1
 }
 
 /// Historical backfill: replay past transactions of the selected programs through the
@@ -134,6 +352,37 @@ pub fn test_default() {
     assert_eq!(config.opentelemetry_config_location, "");
     assert_eq!(config.source, LogSource::LogsSubscribe);
     assert!(config.backfill.is_none());
+    assert!(!config.fetch_idls);
+    assert_eq!(config.decode_mode, DecodeMode::Standard);
+
+    // The same defaults as a config file that leaves the fields out
+    let deserialized = serde_json::from_value::<SologgerConfig>(json!({"rpcUrl": ""})).unwrap();
+    assert_eq!(config, deserialized);
+    assert_eq!(config.pipeline_workers, 4);
+    assert!(config.backfill_truncated);
+}
+
+#[test]
+pub fn test_deserialize_pipeline_workers() {
+    let config = json!(
+        {
+            "rpcUrl": "wss://api.mainnet-beta.solana.com"
+        }
+    );
+    let sologger_config = serde_json::from_value::<SologgerConfig>(config).unwrap();
+    assert_eq!(sologger_config.pipeline_workers, 4);
+    assert_eq!(sologger_config.pipeline_queue_capacity, 1024);
+
+    let config = json!(
+        {
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "pipelineWorkers": 0,
+            "pipelineQueueCapacity": 16
+        }
+    );
+    let sologger_config = serde_json::from_value::<SologgerConfig>(config).unwrap();
+    assert_eq!(sologger_config.pipeline_workers, 0);
+    assert_eq!(sologger_config.pipeline_queue_capacity, 16);
 }
 
 #[test]
@@ -168,6 +417,16 @@ pub fn test_deserialize_ingestion_options() {
             "rpcHttpUrl": "https://api.mainnet-beta.solana.com",
             "source": "blockSubscribe",
             "backfillTruncated": false,
+            "resolveBlockTime": true,
+            "fetchIdls": true,
+            "idlCacheDir": "./idl-cache",
+            "idlDirectory": "./idls",
+            "idlReloadIntervalMs": 500,
+            "decodeLogMessageEvents": true,
+            "decodeMode": "lenient",
+            "accountSubscriptions": {
+                "programs": ["CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C"]
+            },
             "backfill": {
                 "fromSlot": 1000,
                 "untilSlot": 2000,
@@ -181,7 +440,17 @@ pub fn test_deserialize_ingestion_options() {
     let sologger_config = serde_json::from_value::<SologgerConfig>(config).unwrap();
     assert_eq!(sologger_config.source, LogSource::BlockSubscribe);
     assert!(!sologger_config.backfill_truncated);
+    assert!(sologger_config.resolve_block_time);
     assert_eq!(sologger_config.rpc_http_url, "https://api.mainnet-beta.solana.com");
+    assert!(sologger_config.fetch_idls);
+    assert_eq!(sologger_config.idl_cache_dir.as_deref(), Some("./idl-cache"));
+    assert_eq!(sologger_config.idl_directory.as_deref(), Some("./idls"));
+    assert_eq!(sologger_config.idl_reload_interval_ms, 500);
+    assert!(sologger_config.decode_log_message_events);
+    assert_eq!(sologger_config.decode_mode, DecodeMode::Lenient);
+    let account_subscriptions = sologger_config.account_subscriptions.unwrap();
+    assert_eq!(account_subscriptions.programs.len(), 1);
+    assert!(account_subscriptions.accounts.is_empty());
     let backfill = sologger_config.backfill.unwrap();
     assert_eq!(backfill.from_slot, Some(1000));
     assert_eq!(backfill.until_slot, Some(2000));
@@ -190,6 +459,101 @@ pub fn test_deserialize_ingestion_options() {
     assert!(backfill.exit_after);
 }
 
+#[test]
+pub fn test_deserialize_yellowstone_source() {
+    let config = json!(
+        {
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "source": "yellowstoneGrpc",
+            "grpcUrl": "https://grpc.example.com:443",
+            "grpcXToken": "secret"
+        }
+    );
+
+    let sologger_config = serde_json::from_value::<SologgerConfig>(config).unwrap();
+    assert_eq!(sologger_config.source, LogSource::YellowstoneGrpc);
+    assert_eq!(sologger_config.grpc_url, "https://grpc.example.com:443");
+    assert_eq!(sologger_config.grpc_x_token.as_deref(), Some("secret"));
+}
+
+#[test]
+pub fn test_deserialize_http_ingest() {
+    let config = json!(
+        {
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "source": "httpPush",
+            "httpIngest": {
+                "secret": "shared-secret"
+            }
+        }
+    );
+
+    let sologger_config = serde_json::from_value::<SologgerConfig>(config).unwrap();
+    assert_eq!(sologger_config.source, LogSource::HttpPush);
+    let http_ingest = sologger_config.http_ingest.unwrap();
+    assert_eq!(http_ingest.bind_address, "127.0.0.1:8080");
+    assert_eq!(http_ingest.path, "/transactions");
+    assert_eq!(http_ingest.secret, "shared-secret");
+    assert_eq!(http_ingest.max_body_bytes, 16 * 1024 * 1024);
+}
+
+#[test]
+pub fn test_deserialize_file_source() {
+    let config = json!(
+        {
+            "rpcUrl": "ws://127.0.0.1:8900",
+            "source": "file",
+            "replay": {
+                "path": "./incident.ndjson"
+            }
+        }
+    );
+
+    let sologger_config = serde_json::from_value::<SologgerConfig>(config).unwrap();
+    assert_eq!(sologger_config.source, LogSource::File);
+    let replay = sologger_config.replay.unwrap();
+    assert_eq!(replay.path, "./incident.ndjson");
+    assert_eq!(replay.speed, 1);
+}
+
+#[test]
+pub fn test_deserialize_solana_logs_source() {
+    let config = json!(
+        {
+            "rpcUrl": "ws://127.0.0.1:8900",
+            "source": "solanaLogs",
+            "solanaLogsPath": "./validator-logs.txt"
+        }
+    );
+
+    let sologger_config = serde_json::from_value::<SologgerConfig>(config).unwrap();
+    assert_eq!(sologger_config.source, LogSource::SolanaLogs);
+    assert_eq!(
+        sologger_config.solana_logs_path.as_deref(),
+        Some("./validator-logs.txt")
+    );
+}
+
+#[test]
+pub fn test_deserialize_record() {
+    let config = json!(
+        {
+            "rpcUrl": "wss://api.mainnet-beta.solana.com",
+            "record": {
+                "directory": "./recordings",
+                "gzip": true
+            }
+        }
+    );
+
+    let sologger_config = serde_json::from_value::<SologgerConfig>(config).unwrap();
+    let record = sologger_config.record.unwrap();
+    assert_eq!(record.directory, "./recordings");
+    assert_eq!(record.max_file_bytes, 100 * 1024 * 1024);
+    assert_eq!(record.max_files, None);
+    assert!(record.gzip);
+}
+
 #[test]
 pub fn test_backfill_defaults() {
     let backfill = serde_json::from_value::<BackfillConfig>(json!({})).unwrap();
@@ -50,3 +50,10 @@ pub fn record_slot_gap(missed_slots: u64) {
         metrics.record_slot_gap(missed_slots);
     }
 }
+
+/// Records the pipeline worker queue depth, when metrics are enabled.
+pub fn record_queue_depth(depth: usize) {
+    if let Some(metrics) = METRICS.get() {
+        metrics.record_queue_depth(depth as u64);
+    }
+}
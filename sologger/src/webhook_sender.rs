@@ -5,6 +5,7 @@
 
 use std::sync::{Arc, OnceLock};
 
+use sologger_log_context::account_change::AccountChange;
 use sologger_log_context::sologger_log_context::LogContext;
 use sologger_log_transport::webhook_lib::WebhookTransport;
 
@@ -32,7 +33,18 @@ pub fn dispatch(log_contexts: &[LogContext]) {
     let Some(transport) = TRANSPORT.get() else {
         return;
     };
-    let payloads = transport.matching_payloads(log_contexts);
+    send(transport, transport.matching_payloads(log_contexts));
+}
+
+/// Sends the matching account changes, if a webhook is configured.
+pub fn dispatch_account_changes(changes: &[AccountChange]) {
+    let Some(transport) = TRANSPORT.get() else {
+        return;
+    };
+    send(transport, transport.matching_account_payloads(changes));
+}
+
+fn send(transport: &Arc<WebhookTransport>, payloads: Vec<String>) {
     if payloads.is_empty() {
         return;
     }
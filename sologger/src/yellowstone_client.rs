@@ -0,0 +1,339 @@
+//! Yellowstone gRPC (Dragon's Mouth) transport for the "yellowstoneGrpc" source: one
+//! bidirectional `Subscribe` stream per connection, driven by the same reconnect
+//! supervisor as the WebSocket subscriptions.
+
+use anyhow::{anyhow, Result};
+use log::trace;
+use solana_rpc_client_api::config::{CommitmentConfig, CommitmentLevel as RpcCommitmentLevel};
+use sologger_log_context::programs_selector::ProgramsSelector;
+use sologger_log_transformer::yellowstone_proto::subscribe_update::UpdateOneof;
+use sologger_log_transformer::yellowstone_proto::{
+    CommitmentLevel, SubscribeRequest, SubscribeRequestFilterTransactions, SubscribeRequestPing,
+    SubscribeUpdate, SubscribeUpdateTransaction, SUBSCRIBE_PATH,
+};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::AsciiMetadataValue;
+use tonic::transport::{ClientTlsConfig, Endpoint};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Large blocks of account keys and logs exceed tonic's 4 MiB default.
+const MAX_DECODING_MESSAGE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Name of the single transaction filter sologger registers.
+const FILTER_NAME: &str = "sologger";
+
+/// The transaction filter for the selected programs: everything, or transactions that
+/// mention any of them. Votes are excluded unless `allWithVotes` is set.
+pub(crate) fn transaction_filter(
+    program_selector: &ProgramsSelector,
+    all_with_votes: bool,
+) -> SubscribeRequestFilterTransactions {
+    let account_include = if program_selector.select_all_programs {
+        Vec::new()
+    } else {
+        program_selector
+            .programs
+            .iter()
+            .map(|program_id| bs58::encode(program_id).into_string())
+            .collect()
+    };
+    SubscribeRequestFilterTransactions {
+        vote: (!all_with_votes).then_some(false),
+        account_include,
+        ..Default::default()
+    }
+}
+
+fn commitment_level(commitment: Option<CommitmentConfig>) -> Option<i32> {
+    let level = match commitment?.commitment {
+        RpcCommitmentLevel::Processed => CommitmentLevel::Processed,
+        RpcCommitmentLevel::Confirmed => CommitmentLevel::Confirmed,
+        RpcCommitmentLevel::Finalized => CommitmentLevel::Finalized,
+    };
+    Some(level as i32)
+}
+
+/// An open `Subscribe` stream.
+pub(crate) struct YellowstoneStream {
+    updates: tonic::Streaming<SubscribeUpdate>,
+    /// Requests sent on the open stream, used to answer server pings
+    requests: mpsc::Sender<SubscribeRequest>,
+}
+
+impl YellowstoneStream {
+    /// Connects to `url` (TLS for https) and subscribes with the given filter.
+    pub(crate) async fn subscribe(
+        url: &str,
+        x_token: Option<&str>,
+        filter: SubscribeRequestFilterTransactions,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Self> {
+        let mut endpoint = Endpoint::from_shared(url.to_string())?.connect_timeout(CONNECT_TIMEOUT);
+        if url.starts_with("https://") {
+            endpoint = endpoint.tls_config(ClientTlsConfig::new().with_webpki_roots())?;
+        }
+        let channel = endpoint.connect().await?;
+        let mut client =
+            tonic::client::Grpc::new(channel).max_decoding_message_size(MAX_DECODING_MESSAGE_SIZE);
+        client.ready().await?;
+
+        let (requests, outbound) = mpsc::channel(4);
+        requests
+            .send(SubscribeRequest {
+                transactions: HashMap::from([(FILTER_NAME.to_string(), filter)]),
+                commitment: commitment_level(commitment),
+                ping: None,
+            })
+            .await?;
+        let outbound = futures_util::stream::unfold(outbound, |mut outbound| async move {
+            outbound.recv().await.map(|request| (request, outbound))
+        });
+
+        let mut request = tonic::Request::new(outbound);
+        if let Some(x_token) = x_token {
+            let x_token = AsciiMetadataValue::try_from(x_token)
+                .map_err(|_| anyhow!("grpcXToken is not a valid header value"))?;
+            request.metadata_mut().insert("x-token", x_token);
+        }
+        let updates = client
+            .streaming(
+                request,
+                PathAndQuery::from_static(SUBSCRIBE_PATH),
+                tonic_prost::ProstCodec::default(),
+            )
+            .await?
+            .into_inner();
+        Ok(YellowstoneStream { updates, requests })
+    }
+
+    /// The next transaction update, or None when the server ends the stream. Server pings
+    /// are answered on the way, as load balancers in front of providers close idle streams.
+    pub(crate) async fn next_transaction(&mut self) -> Result<Option<SubscribeUpdateTransaction>> {
+        while let Some(update) = self.updates.message().await? {
+            match update.update_oneof {
+                Some(UpdateOneof::Transaction(transaction)) => return Ok(Some(transaction)),
+                Some(UpdateOneof::Ping(_)) => {
+                    let pong = SubscribeRequest {
+                        ping: Some(SubscribeRequestPing { id: 1 }),
+                        ..Default::default()
+                    };
+                    self.requests.send(pong).await?;
+                }
+                Some(UpdateOneof::Pong(_)) | None => trace!("skipping non-transaction update"),
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sologger_log_transformer::log_context_transformer::from_yellowstone_transaction;
+    use sologger_log_transformer::yellowstone_proto::{
+        CompiledInstruction, Message, MessageHeader, SubscribeUpdatePing,
+        SubscribeUpdateTransactionInfo, Transaction, TransactionStatusMeta,
+    };
+    use std::convert::Infallible;
+    use std::sync::{Arc, Mutex};
+    use tonic::codegen::{http, Body, BoxFuture, BoxStream, Context, Poll, Service, StdError};
+    use tonic::transport::server::TcpIncoming;
+
+    const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+
+    /// Stand-in for a Yellowstone server: replays `updates` on every stream and records the
+    /// requests and x-tokens it received.
+    #[derive(Clone, Default)]
+    struct StandIn {
+        updates: Vec<SubscribeUpdate>,
+        requests: Arc<Mutex<Vec<SubscribeRequest>>>,
+        x_tokens: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl tonic::server::NamedService for StandIn {
+        const NAME: &'static str = "geyser.Geyser";
+    }
+
+    impl tonic::server::StreamingService<SubscribeRequest> for StandIn {
+        type Response = SubscribeUpdate;
+        type ResponseStream = BoxStream<SubscribeUpdate>;
+        type Future = BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+
+        fn call(
+            &mut self,
+            request: tonic::Request<tonic::Streaming<SubscribeRequest>>,
+        ) -> Self::Future {
+            let stand_in = self.clone();
+            Box::pin(async move {
+                if let Some(x_token) = request.metadata().get("x-token") {
+                    stand_in
+                        .x_tokens
+                        .lock()
+                        .unwrap()
+                        .push(x_token.to_str().unwrap().to_string());
+                }
+                let mut inbound = request.into_inner();
+                let requests = Arc::clone(&stand_in.requests);
+                tokio::spawn(async move {
+                    while let Ok(Some(request)) = inbound.message().await {
+                        requests.lock().unwrap().push(request);
+                    }
+                });
+                let updates = futures_util::stream::iter(stand_in.updates.into_iter().map(Ok));
+                Ok(tonic::Response::new(
+                    Box::pin(updates) as Self::ResponseStream
+                ))
+            })
+        }
+    }
+
+    impl<B> Service<http::Request<B>> for StandIn
+    where
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::Body>;
+        type Error = Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<B>) -> Self::Future {
+            let stand_in = self.clone();
+            Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(tonic_prost::ProstCodec::default());
+                Ok(grpc.streaming(stand_in, request).await)
+            })
+        }
+    }
+
+    async fn serve(stand_in: StandIn) -> String {
+        let incoming = TcpIncoming::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let url = format!("http://{}", incoming.local_addr().unwrap());
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(stand_in)
+                .serve_with_incoming(incoming),
+        );
+        url
+    }
+
+    fn transaction_update(slot: u64, index: u64) -> SubscribeUpdate {
+        let payer = [9u8; 32].to_vec();
+        let system_program = bs58::decode(SYSTEM_PROGRAM).into_vec().unwrap();
+        let info = SubscribeUpdateTransactionInfo {
+            signature: vec![7; 64],
+            is_vote: false,
+            transaction: Some(Transaction {
+                signatures: vec![vec![7; 64]],
+                message: Some(Message {
+                    header: Some(MessageHeader {
+                        num_required_signatures: 1,
+                        num_readonly_signed_accounts: 0,
+                        num_readonly_unsigned_accounts: 1,
+                    }),
+                    account_keys: vec![payer, system_program],
+                    recent_blockhash: vec![0; 32],
+                    instructions: vec![CompiledInstruction {
+                        program_id_index: 1,
+                        accounts: vec![0],
+                        data: vec![],
+                    }],
+                    versioned: false,
+                    address_table_lookups: vec![],
+                }),
+            }),
+            meta: Some(TransactionStatusMeta {
+                fee: 5000,
+                pre_balances: vec![1_000_000, 1],
+                post_balances: vec![995_000, 1],
+                log_messages: vec![
+                    format!("Program {} invoke [1]", SYSTEM_PROGRAM),
+                    format!("Program {} success", SYSTEM_PROGRAM),
+                ],
+                ..Default::default()
+            }),
+            index,
+        };
+        SubscribeUpdate {
+            filters: vec![FILTER_NAME.to_string()],
+            update_oneof: Some(UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                transaction: Some(info),
+                slot,
+            })),
+        }
+    }
+
+    #[test]
+    fn test_transaction_filter_from_selector() {
+        let filter = transaction_filter(&ProgramsSelector::new_all_programs(), false);
+        assert!(filter.account_include.is_empty());
+        assert_eq!(filter.vote, Some(false));
+
+        let selector = ProgramsSelector::new(&[SYSTEM_PROGRAM.to_string()]);
+        let filter = transaction_filter(&selector, true);
+        assert_eq!(filter.account_include, vec![SYSTEM_PROGRAM.to_string()]);
+        assert_eq!(filter.vote, None);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_streams_transactions_and_answers_pings() {
+        let stand_in = StandIn {
+            updates: vec![
+                SubscribeUpdate {
+                    filters: vec![],
+                    update_oneof: Some(UpdateOneof::Ping(SubscribeUpdatePing {})),
+                },
+                transaction_update(42, 3),
+            ],
+            ..Default::default()
+        };
+        let url = serve(stand_in.clone()).await;
+
+        let selector = ProgramsSelector::new_all_programs();
+        let mut stream = YellowstoneStream::subscribe(
+            &url,
+            Some("secret"),
+            transaction_filter(&selector, false),
+            Some(CommitmentConfig::confirmed()),
+        )
+        .await
+        .unwrap();
+
+        let update = stream.next_transaction().await.unwrap().unwrap();
+        let log_contexts = from_yellowstone_transaction(&update, &selector).unwrap();
+        assert_eq!(log_contexts.len(), 1);
+        assert_eq!(log_contexts[0].program_id, SYSTEM_PROGRAM);
+        assert_eq!(log_contexts[0].slot, 42);
+        assert_eq!(log_contexts[0].transaction_index, Some(3));
+        assert_eq!(log_contexts[0].fees.as_ref().unwrap().fee, 5000);
+        assert_eq!(log_contexts[0].balance_changes.len(), 1);
+        assert!(stream.next_transaction().await.unwrap().is_none());
+
+        assert_eq!(
+            *stand_in.x_tokens.lock().unwrap(),
+            vec!["secret".to_string()]
+        );
+        // The subscription, then the answer to the ping
+        for _ in 0..100 {
+            if stand_in.requests.lock().unwrap().len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let requests = stand_in.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].transactions.contains_key(FILTER_NAME));
+        assert_eq!(
+            requests[0].commitment,
+            Some(CommitmentLevel::Confirmed as i32)
+        );
+        assert_eq!(requests[1].ping, Some(SubscribeRequestPing { id: 1 }));
+    }
+}
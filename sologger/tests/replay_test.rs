@@ -0,0 +1,37 @@
+use std::process::Command;
+
+/// Replays a recorded NDJSON file through the binary: no validator or RPC needed, and
+/// with no transport configured the records come out as console trees.
+#[test]
+fn replay_recording_end_to_end() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sologger"))
+        .args([
+            "replay",
+            "./tests/fixtures/replay.ndjson",
+            "--fast",
+            "--config",
+            "./tests/config/replay-sologger-config.json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let headers: Vec<&str> = stdout.lines().filter(|line| line.starts_with("── slot")).collect();
+    assert_eq!(headers.len(), 3, "{}", stdout);
+    assert!(headers[0].starts_with("── slot 100 · 5h6xBEau"));
+    assert!(headers[0].ends_with("✓"));
+    assert!(headers[1].starts_with("── slot 101 · 4eSszcNt"));
+    assert!(headers[1].ends_with("✗ FAILED"));
+    assert!(headers[2].starts_with("── slot 102 · 3nB5TYrS"));
+}
+
+#[test]
+fn replay_without_file_is_a_usage_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sologger"))
+        .arg("replay")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("usage: sologger replay"));
+}
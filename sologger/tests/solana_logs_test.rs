@@ -0,0 +1,37 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Pipes `solana logs` output into `sologger --stdin`: with no config, every program is
+/// selected and the records come out as console trees.
+#[test]
+fn solana_logs_from_stdin_end_to_end() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sologger"))
+        .arg("--stdin")
+        .env_remove("SOLOGGER_APP_CONFIG_LOC")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let input = std::fs::read("./tests/fixtures/solana-logs.txt").unwrap();
+    // Dropping stdin closes it, which ends the input
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let headers: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("── slot"))
+        .collect();
+    assert_eq!(headers.len(), 2, "{}", stdout);
+    assert!(headers[0].starts_with("── slot 5 · 5h6xBEau"));
+    assert!(headers[0].ends_with("✓"));
+    assert!(headers[1].starts_with("── slot 6 · 4eSszcNt"));
+    assert!(headers[1].ends_with("✗ FAILED"));
+    assert!(
+        stdout.contains("custom program error: 0x1771"),
+        "{}",
+        stdout
+    );
+}